@@ -1,7 +1,7 @@
 //! The `cast` CLI: a Swiss Army knife for interacting with EVM smart contracts, sending
 //! transactions and getting chain data.
 
-use cast::args::run;
+use cast::{args::run, errors::CallRevertedError};
 
 #[cfg(all(feature = "jemalloc", unix))]
 #[global_allocator]
@@ -10,6 +10,7 @@
 fn main() {
     if let Err(err) = run() {
         let _ = foundry_common::sh_err!("{err:?}");
-        std::process::exit(1);
+        let code = if err.is::<CallRevertedError>() { CallRevertedError::EXIT_CODE } else { 1 };
+        std::process::exit(code);
     }
 }
@@ -482,6 +482,7 @@ pub async fn run_command(args: CastArgs) -> Result<()> {
             )?
         }
         CastSubcommand::Run(cmd) => cmd.run().await?,
+        CastSubcommand::TraceRender(cmd) => cmd.run().await?,
         CastSubcommand::SendTx(cmd) => cmd.run().await?,
         CastSubcommand::Tx { tx_hash, from, nonce, field, raw, rpc } => {
             let config = rpc.load_config()?;
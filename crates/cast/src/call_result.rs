@@ -0,0 +1,225 @@
+//! Structured results of `eth_call`s.
+
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::Error;
+use alloy_primitives::{hex, Bytes, Selector, U256};
+use alloy_sol_types::{Panic, Revert, SolError};
+use foundry_common::fmt::format_token;
+use foundry_evm::decode::RevertDecoder;
+use serde::Serialize;
+use std::fmt;
+
+/// The outcome of a call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum CallResult {
+    /// The call succeeded with the given raw output.
+    Success { output: Bytes },
+    /// The call reverted with the given raw revert data.
+    Revert { data: Bytes, reason: CallRevertReason },
+}
+
+impl CallResult {
+    /// Returns `true` if the call succeeded.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success { .. })
+    }
+}
+
+/// The output of `cast call --json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonCallOutput {
+    /// Whether the call succeeded.
+    pub success: bool,
+    /// The raw output of the call, or its revert data.
+    pub raw: Bytes,
+    /// The decoded return values, if the return types are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<Vec<String>>,
+    /// The estimated gas of the call, with `--estimate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<u64>,
+    /// The human-readable revert reason.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// The decoded revert reason.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<CallRevertReason>,
+}
+
+impl JsonCallOutput {
+    /// Creates the output of a successful call.
+    pub fn success(raw: Bytes, decoded: Option<Vec<String>>, gas: Option<u64>) -> Self {
+        Self { success: true, raw, decoded, gas, revert_reason: None, error: None }
+    }
+
+    /// Creates the output of a reverted call.
+    pub fn revert(data: Bytes, reason: CallRevertReason) -> Self {
+        Self {
+            success: false,
+            raw: data,
+            decoded: None,
+            gas: None,
+            revert_reason: Some(reason.to_string()),
+            error: Some(reason),
+        }
+    }
+}
+
+/// The decoded reason of a reverted call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CallRevertReason {
+    /// The call reverted without any data.
+    Empty,
+    /// Solidity's `Error(string)`.
+    Error { message: String },
+    /// Solidity's `Panic(uint256)`, along with the description of the panic code if known.
+    Panic { code: U256, message: Option<String> },
+    /// A custom error, decoded if its ABI is known.
+    #[serde(rename_all = "camelCase")]
+    Custom { selector: Selector, signature: Option<String>, args: Option<Vec<String>> },
+    /// Revert data too short to contain a selector.
+    Unknown,
+}
+
+impl CallRevertReason {
+    /// Decodes the revert data of a call, using the custom errors of the given decoder.
+    pub fn decode(data: &[u8], revert_decoder: &RevertDecoder) -> Self {
+        let Some((selector, args)) = data.split_first_chunk() else {
+            return if data.is_empty() { Self::Empty } else { Self::Unknown };
+        };
+        let selector = Selector::from(*selector);
+
+        if selector == Revert::SELECTOR {
+            if let Ok(revert) = Revert::abi_decode(data, false) {
+                return Self::Error { message: revert.reason };
+            }
+        }
+        if selector == Panic::SELECTOR {
+            if let Ok(panic) = Panic::abi_decode(data, false) {
+                let message = panic.kind().map(|kind| kind.as_str().to_string());
+                return Self::Panic { code: panic.code, message };
+            }
+        }
+
+        let errors = revert_decoder.errors.get(&selector).map(Vec::as_slice).unwrap_or_default();
+        let decoded = errors.iter().find_map(|error: &Error| {
+            let decoded = error.abi_decode_input(args, false).ok()?;
+            Some((error.signature(), decoded.iter().map(format_token).collect()))
+        });
+        match decoded {
+            Some((signature, args)) => {
+                Self::Custom { selector, signature: Some(signature), args: Some(args) }
+            }
+            None => Self::Custom { selector, signature: None, args: None },
+        }
+    }
+}
+
+impl fmt::Display for CallRevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("<empty revert data>"),
+            Self::Error { message } => f.write_str(message),
+            Self::Panic { code, message } => {
+                write!(f, "panic: {}", message.as_deref().unwrap_or("unknown panic code"))?;
+                write!(f, " ({code:#x})")
+            }
+            Self::Custom { selector, signature: Some(signature), args: Some(args) } => {
+                let name = signature.split('(').next().unwrap_or(signature);
+                write!(f, "{name}({})", args.join(", "))?;
+                write!(f, " ({})", hex::encode_prefixed(selector))
+            }
+            Self::Custom { selector, .. } => write!(f, "custom error {selector}"),
+            Self::Unknown => f.write_str("<invalid revert data>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_sol_types::sol;
+
+    sol! {
+        error InsufficientBalance(uint256 available, uint256 required);
+    }
+
+    #[test]
+    fn decodes_revert_reasons() {
+        let decoder = RevertDecoder::new();
+
+        assert_eq!(CallRevertReason::decode(&[], &decoder), CallRevertReason::Empty);
+        assert_eq!(CallRevertReason::decode(&[1, 2], &decoder), CallRevertReason::Unknown);
+
+        let data = Revert::from("not owner").abi_encode();
+        assert_eq!(
+            CallRevertReason::decode(&data, &decoder),
+            CallRevertReason::Error { message: "not owner".to_string() }
+        );
+
+        let data = Panic { code: U256::from(0x11) }.abi_encode();
+        let reason = CallRevertReason::decode(&data, &decoder);
+        assert_eq!(
+            reason,
+            CallRevertReason::Panic {
+                code: U256::from(0x11),
+                message: Some("arithmetic underflow or overflow".to_string())
+            }
+        );
+        assert_eq!(reason.to_string(), "panic: arithmetic underflow or overflow (0x11)");
+    }
+
+    #[test]
+    fn decodes_custom_errors() {
+        let data =
+            InsufficientBalance { available: U256::from(1), required: U256::from(2) }.abi_encode();
+
+        let reason = CallRevertReason::decode(&data, &RevertDecoder::new());
+        assert_eq!(
+            reason,
+            CallRevertReason::Custom {
+                selector: InsufficientBalance::SELECTOR.into(),
+                signature: None,
+                args: None
+            }
+        );
+
+        let mut decoder = RevertDecoder::new();
+        decoder.push_error(Error::parse("InsufficientBalance(uint256,uint256)").unwrap());
+        let reason = CallRevertReason::decode(&data, &decoder);
+        assert_eq!(
+            reason,
+            CallRevertReason::Custom {
+                selector: InsufficientBalance::SELECTOR.into(),
+                signature: Some("InsufficientBalance(uint256,uint256)".to_string()),
+                args: Some(vec!["1".to_string(), "2".to_string()]),
+            }
+        );
+        assert!(reason.to_string().starts_with("InsufficientBalance(1, 2)"));
+    }
+
+    #[test]
+    fn serializes_json_call_output() {
+        let output = JsonCallOutput::success(Bytes::from([1]), Some(vec!["1".to_string()]), None);
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            serde_json::json!({ "success": true, "raw": "0x01", "decoded": ["1"] })
+        );
+
+        let data = Bytes::from(Revert::from("not owner").abi_encode());
+        let reason = CallRevertReason::decode(&data, &RevertDecoder::new());
+        let output = JsonCallOutput::revert(data.clone(), reason);
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            serde_json::json!({
+                "success": false,
+                "raw": data,
+                "revertReason": "not owner",
+                "error": { "kind": "error", "message": "not owner" },
+            })
+        );
+    }
+}
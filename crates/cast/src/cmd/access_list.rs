@@ -53,7 +53,7 @@ pub async fn run(self) -> Result<()> {
 
         let (tx, _) = CastTxBuilder::new(&provider, tx, &config)
             .await?
-            .with_to(to)
+            .with_to(to, block)
             .await?
             .with_code_sig_and_args(None, sig, args)
             .await?
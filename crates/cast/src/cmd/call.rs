@@ -1,42 +1,139 @@
 use crate::{
+    call_result::{CallResult, CallRevertReason, JsonCallOutput},
+    cmd::trace_render::TraceExport,
+    errors::CallRevertedError,
+    revm::{
+        primitives::{
+            AccessList, AuthorizationList, BlobExcessGasAndPrice, Bytecode, Env, EnvWithHandlerCfg,
+            ExecutionResult, KECCAK_EMPTY,
+        },
+        DatabaseRef,
+    },
     traces::TraceKind,
     tx::{CastTxBuilder, SenderKind},
-    Cast,
+    Cast, SimpleCast,
+};
+use alloy_consensus::{Transaction, TxEnvelope};
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_network::{AnyNetwork, AnyRpcBlock, TransactionBuilder, TransactionResponse};
+use alloy_primitives::{
+    hex, keccak256,
+    map::{AddressHashMap, HashMap},
+    Address, Bytes, Selector, TxKind, B256, U256, U64,
 };
-use alloy_primitives::{Address, Bytes, TxKind, U256};
+use alloy_provider::Provider;
 use alloy_rpc_types::{
-    state::{StateOverride, StateOverridesBuilder},
-    BlockId, BlockNumberOrTag,
+    state::{AccountOverride, StateOverride, StateOverridesBuilder},
+    trace::geth::{AccountState, PreStateMode},
+    AccessListResult, BlockId, BlockNumberOrTag, BlockOverrides, BlockTransactions,
+    TransactionRequest,
 };
+use alloy_serde::WithOtherFields;
+use alloy_transport::TransportError;
+use chrono::DateTime;
 use clap::Parser;
-use eyre::Result;
+use eyre::{Result, WrapErr};
 use foundry_cli::{
-    opts::{EthereumOpts, TransactionOpts},
-    utils::{self, handle_traces, parse_ether_value, TraceResult},
+    opts::{CliAuthorizationList, EthereumOpts, TransactionOpts},
+    utils::{
+        self, handle_traces, parse_ether_value, GasReportSort, TraceFormat, TraceOptions,
+        TraceResult,
+    },
+};
+use foundry_common::{
+    compile::ProjectCompiler,
+    ens::NameOrAddress,
+    fmt::{format_token, format_token_raw},
+    fs, is_known_system_sender,
+    provider::RetryProvider,
+    shell, ContractsByArtifact, DEV_CHAIN_ID, SYSTEM_TRANSACTION_TYPE,
 };
-use foundry_common::{ens::NameOrAddress, shell};
-use foundry_compilers::artifacts::EvmVersion;
+use foundry_compilers::{artifacts::EvmVersion, ArtifactId};
 use foundry_config::{
     figment::{
         self,
         value::{Dict, Map},
         Figment, Metadata, Profile,
     },
-    Config,
+    Chain, Config,
 };
 use foundry_evm::{
-    executors::TracingExecutor,
+    backend::Backend,
+    decode::RevertDecoder,
+    executors::{EvmError, Executor, TracingExecutor},
+    fork::CreateFork,
+    inspectors::OpcodePrinter,
     opts::EvmOpts,
-    traces::{InternalTraceMode, TraceMode},
+    precompiles::PRECOMPILES,
+    traces::{
+        identifier::{LocalTraceIdentifier, SignaturesIdentifier},
+        record_storage_reads, InternalTraceMode, TraceMode,
+    },
+    utils::{
+        apply_chain_and_block_specific_env_changes, configure_tx_env, configure_tx_req_env,
+        StateChangeset,
+    },
+};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
 };
 use regex::Regex;
-use std::{str::FromStr, sync::LazyLock};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::LazyLock,
+    time::Duration,
+};
 
 // matches override pattern <address>:<slot>:<value>
 // e.g. 0x123:0x1:0x1234
 static OVERRIDE_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^([^:]+):([^:]+):([^:]+)$").unwrap());
 
+/// The default maximum estimated size of a trace, see `--max-trace-bytes`.
+const DEFAULT_MAX_TRACE_BYTES: usize = 100_000_000;
+
+/// The storage slots of accounts, as loaded from `--override-storage-dump`.
+#[derive(Clone, Debug, Default)]
+pub struct StorageDumps {
+    /// Slots replacing the whole storage of an account, from `address:path` dumps.
+    pub storage: Vec<(Address, Vec<(B256, B256)>)>,
+    /// The state left by previous calls, from dumps written with `--state-dump`, whose slots are
+    /// layered on top of the storage of the accounts.
+    pub state: StateDump,
+}
+
+/// The state of an account touched by a call, as written with `--state-dump`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AccountDump {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// The code of the account, only set for the contracts created by the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The storage slots changed by the call.
+    pub storage: BTreeMap<B256, B256>,
+}
+
+impl AccountDump {
+    /// Merges the state of a later call into this one, which it takes precedence over.
+    pub fn merge(&mut self, other: Self) {
+        self.balance = other.balance.or(self.balance);
+        self.nonce = other.nonce.or(self.nonce);
+        self.code = other.code.or(self.code.take());
+        self.storage.extend(other.storage);
+    }
+}
+
+/// The accounts touched by a call, as written with `--state-dump`.
+pub type StateDump = BTreeMap<Address, AccountDump>;
+
 /// CLI arguments for `cast call`.
 ///
 /// ## State Override Flags
@@ -46,7 +143,13 @@
 /// * `--override-balance <address>:<balance>` - Override the balance of an account
 /// * `--override-nonce <address>:<nonce>` - Override the nonce of an account
 /// * `--override-code <address>:<code>` - Override the code of an account
+/// * `--override-code <address>:@<contract>` - Override the code of an account with the deployed
+///   code of a local artifact, requires `--with-local-artifacts`
 /// * `--override-state <address>:<slot>:<value>` - Override a storage slot of an account
+/// * `--override-storage-dump <address>:<path>` - Override the storage of an account with the slots
+///   of a JSON file
+/// * `--override-storage-dump <path>` - Apply the storage changes of a dump written with
+///   `--state-dump`
 ///
 /// Multiple overrides can be specified for the same account. For example:
 ///
@@ -58,24 +161,155 @@
 ///   --override-state 0x123:0x1:0x1234
 ///   --override-state-diff 0x123:0x1:0x1234
 /// ```
+///
+/// An EIP-7702 authorization passed with `--auth` sets the code of the authority, so it cannot be
+/// combined with `--override-code` for the same account. If no signer is available, an
+/// authorization delegating to an address is simulated by overriding the code of the sender.
 #[derive(Debug, Parser)]
 pub struct CallArgs {
     /// The destination of the transaction.
-    #[arg(value_parser = NameOrAddress::from_str)]
-    to: Option<NameOrAddress>,
+    #[arg(value_name = "TO")]
+    to: Option<String>,
 
     /// The signature of the function to call.
     sig: Option<String>,
 
     /// The arguments of the function to call.
+    ///
+    /// One argument can be `-` to read it from stdin, e.g. for large `bytes` arguments.
     args: Vec<String>,
 
     /// Raw hex-encoded data for the transaction. Used instead of \[SIG\] and \[ARGS\].
+    ///
+    /// Can be repeated to execute several calls sequentially against the same state, in which
+    /// case later calls see the state changes of earlier ones. Multiple calls can only be executed
+    /// with `--trace`.
     #[arg(
         long,
         conflicts_with_all = &["sig", "args"]
     )]
-    data: Option<String>,
+    data: Vec<String>,
+
+    /// Execute the calls of a file sequentially against the same state, one hex-encoded calldata
+    /// per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    /// Can only be used with `--trace`.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "trace",
+        conflicts_with_all = &["sig", "args", "data", "args_json", "from_tx"]
+    )]
+    batch_file: Option<PathBuf>,
+
+    /// Execute the same call against each address of a file, one address per line.
+    ///
+    /// The function to call is given with `--sig` or `--data`. Empty lines and lines starting
+    /// with `#` are ignored. The results are printed keyed by address, and a failing call doesn't
+    /// stop the other ones. With `--trace`, the calls are executed one after the other on the
+    /// same fork.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = &["to", "batch_file", "from_tx", "identify_only", "block_range", "diff_against", "sig_from"]
+    )]
+    to_list: Option<PathBuf>,
+
+    /// The signature of the function to call against each address of `--to-list`, followed by
+    /// its arguments.
+    ///
+    /// Example: `cast call --to-list tokens.txt --sig "balanceOf(address)(uint256)" 0x...`
+    #[arg(
+        long = "sig",
+        value_names = ["SIG", "ARGS"],
+        num_args = 1..,
+        allow_negative_numbers = true,
+        requires = "to_list",
+        conflicts_with_all = &["data", "args_json"]
+    )]
+    to_list_sig: Vec<String>,
+
+    /// Simulate a bundle of signed transactions, given as a JSON file with an array of raw
+    /// transactions.
+    ///
+    /// The transactions are executed in order on a fork, so that each one sees the state changes
+    /// of the previous ones. Their senders are recovered from their signatures, and their combined
+    /// trace is printed along with the outcome of each transaction.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = &["to", "sig", "args", "data", "batch_file", "to_list", "args_json", "from_tx", "identify_only"]
+    )]
+    simulate_bundle: Option<PathBuf>,
+
+    /// The arguments of the function to call, as a JSON object mapping the parameter names of
+    /// the signature to their values.
+    ///
+    /// Example: '{"to":"0x...","amount":"100"}'
+    #[arg(long, value_name = "JSON", conflicts_with_all = &["args", "data"])]
+    args_json: Option<String>,
+
+    /// Take the signature of the function to call from an ABI, given as `<contract>:<function>`.
+    ///
+    /// The function is looked up in the ABI of the local artifact of the contract with
+    /// `--with-local-artifacts`, or in the `--abi` ABIs. Overloads are resolved by the number of
+    /// arguments. All the positional arguments after the destination are function arguments.
+    ///
+    /// Example: `cast call 0x... --sig-from MyToken:transfer 0x... 100`
+    #[arg(
+        long,
+        value_name = "CONTRACT:FUNCTION",
+        conflicts_with_all = &["data", "batch_file", "args_json", "from_tx", "simulate_bundle"]
+    )]
+    sig_from: Option<String>,
+
+    /// The types to ABI-decode the raw output of the call as, e.g. `(uint256,address)`.
+    ///
+    /// This overrides the output types of the function signature, e.g. when it resolves to the
+    /// wrong overload. Decoding falls back to printing the raw hex output if it fails.
+    #[arg(long, visible_alias = "returns", value_name = "TYPE", value_parser = parse_output_abi)]
+    output_abi: Option<DynSolType>,
+
+    /// Replay the calldata of an existing transaction.
+    ///
+    /// The recipient, value and sender of the transaction are used unless they are explicitly
+    /// provided.
+    #[arg(long, value_name = "TX_HASH", conflicts_with_all = &["sig", "args", "data", "args_json"])]
+    from_tx: Option<B256>,
+
+    /// Execute the call from the given address without resolving any wallet.
+    ///
+    /// Unlike `--from`, this signals that nothing is signed: the configured wallet, if any, is
+    /// ignored, and authorizations are simulated instead of signed.
+    #[arg(long, value_name = "ADDRESS", conflicts_with = "from")]
+    impersonate: Option<Address>,
+
+    /// Set the balance of the sender to the given amount before executing the call.
+    ///
+    /// The balance is only set in the local fork, so that calls sending value can be traced from
+    /// accounts without funds, e.g. with `--impersonate`.
+    /// Can only be used with `--trace`.
+    #[arg(
+        long,
+        value_name = "AMOUNT",
+        value_parser = parse_ether_value,
+        requires = "trace",
+        conflicts_with = "simulate_bundle"
+    )]
+    fund_sender: Option<U256>,
+
+    /// Only identify the local artifact matching the deployed code of the destination, without
+    /// executing the call.
+    ///
+    /// Prints the artifact, the contract name and the bytecode diff score of the match.
+    /// Can only be used with `--with-local-artifacts`.
+    #[arg(
+        long,
+        requires = "with_local_artifacts",
+        conflicts_with_all = &["sig", "args", "data", "batch_file", "trace"]
+    )]
+    identify_only: bool,
 
     /// Forks the remote rpc, executes the transaction locally and prints a trace
     #[arg(long, default_value_t = false)]
@@ -86,14 +320,247 @@ pub struct CallArgs {
     #[arg(long, requires = "trace")]
     debug: bool,
 
+    /// Identifies internal functions in traces.
+    ///
+    /// `--decode-internal` decodes all internal functions, while `--decode-internal=boundaries`
+    /// only decodes the ones defined outside of the called contract and the contracts it inherits
+    /// from, such as library functions.
+    #[arg(
+        long,
+        requires = "trace",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "full",
+        value_name = "MODE"
+    )]
+    decode_internal: Option<InternalTraceMode>,
+
+    /// Show the storage slots read by each call, along with their values.
+    ///
+    /// Repeated reads of a slot within a call are only shown again if its value changed.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    show_storage_reads: bool,
+
+    /// The maximum estimated size of the trace, in bytes.
+    ///
+    /// The execution is aborted with an error once the trace exceeds it, instead of exhausting
+    /// the memory on pathological executions, e.g. of deeply recursive contracts. Use 0 to
+    /// disable the limit.
+    /// Can only be used with `--trace`.
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_TRACE_BYTES, requires = "trace")]
+    max_trace_bytes: usize,
+
+    /// The format of the printed trace.
+    ///
+    /// The JSON format prints a single document for all the calls, and can't be combined with
+    /// `--show-storage-reads`, `--trace-stack-depth-limit` or `--trace-filter`.
+    /// Can only be used with `--trace`.
+    #[arg(long, value_enum, default_value_t, requires = "trace")]
+    trace_format: TraceFormat,
+
+    /// Print a gas report of the trace, grouped by contract and function.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    gas_report: bool,
+
+    /// The order of the contracts and functions of the gas report.
+    #[arg(long, value_enum, default_value_t, requires = "gas_report")]
+    sort_by: GasReportSort,
+
+    /// Only print the first functions of each contract of the gas report, in the order of
+    /// `--sort-by`.
+    #[arg(long, value_name = "N", requires = "gas_report")]
+    top: Option<usize>,
+
+    /// Print the gas used by the executed opcodes, grouped by category: storage, memory, state,
+    /// calls, logs and compute.
+    ///
+    /// The gas forwarded by calls is attributed to the opcodes of the called contract.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    gas_by_opcode: bool,
+
+    /// Maximum depth of the printed trace tree, including internal calls.
+    /// Deeper calls are replaced with a marker of how many were omitted.
+    /// Can only be used with `--trace`.
+    #[arg(long, value_name = "DEPTH", requires = "trace")]
+    trace_stack_depth_limit: Option<usize>,
+
+    /// Only print the calls to the given address, along with the calls leading to them and the
+    /// calls they make.
+    ///
+    /// The other calls are replaced with a marker of how many were omitted. Can be repeated.
+    /// Can only be used with `--trace`.
+    #[arg(long = "trace-filter", value_name = "ADDRESS", requires = "trace")]
+    trace_filter: Vec<Address>,
+
+    /// Print every executed opcode as it is executed, along with its gas cost.
+    ///
+    /// The stack before each opcode is printed with `-vvvv`, and the memory words written by each
+    /// opcode with `-vvvvv`.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    trace_opcodes: bool,
+
+    /// Print the events emitted during the call, decoded and grouped by emitting contract.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    decode_events: bool,
+
+    /// Print the function selectors called during the call, along with their signature if it was
+    /// identified.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    inspect_selectors: bool,
+
+    /// Decode the constructor arguments of the contracts created during the call, once they are
+    /// identified with the project artifacts, and print them at the start of their creation.
+    /// Can only be used with `--trace` and `--with-local-artifacts`.
+    #[arg(long, requires_all = ["trace", "with_local_artifacts"])]
+    identify_creation: bool,
+
+    /// Narrate the call in plain language, with one sentence per call naming the identified
+    /// contracts and the decoded functions and arguments.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    explain: bool,
+
+    /// Exit with code 2 if the call reverts, instead of printing the revert and succeeding.
+    ///
+    /// Other failures, such as RPC errors, still exit with code 1.
+    #[arg(long)]
+    revert_on_failure: bool,
+
+    /// Execute the transactions of the pending block before the call, so that it sees the state
+    /// they would produce.
+    ///
+    /// This is expensive and depends on the node exposing the pending block. If it doesn't, the
+    /// call is executed without them.
+    /// Can only be used with `--trace`, at the latest or pending block.
     #[arg(long, requires = "trace")]
-    decode_internal: bool,
+    include_pending: bool,
+
+    /// Execute the call at the position of the transaction with the given index in the block,
+    /// after replaying the transactions preceding it on top of the state of the parent block.
+    ///
+    /// Transactions that can't be executed, such as the system transactions of L2s, are skipped.
+    /// Can only be used with `--trace`.
+    #[arg(
+        long,
+        value_name = "INDEX",
+        requires = "trace",
+        conflicts_with_all = ["include_pending", "fork_state_file", "simulate_bundle"]
+    )]
+    tx_index: Option<usize>,
+
+    /// Write the state left by the call to a JSON file, to reuse it later with
+    /// `--override-storage-dump <PATH>`.
+    ///
+    /// Only the accounts touched by the call are included, with their balance and nonce, the
+    /// code of the contracts it created, and the storage slots it changed.
+    /// Can only be used with `--trace`.
+    #[arg(long, value_name = "PATH", requires = "trace")]
+    state_dump: Option<PathBuf>,
+
+    /// Continue from the state left by previous calls, and save the state left by this one.
+    ///
+    /// The state of the file is applied on top of the current state of the accounts, as with
+    /// `--override-storage-dump <PATH>`, and the state left by the call is merged into it. The
+    /// file is created if it does not exist, and can't be used by multiple calls at
+    /// the same time.
+    /// Can only be used with `--trace`.
+    #[arg(long, value_name = "PATH", requires = "trace", conflicts_with = "simulate_bundle")]
+    continue_state: Option<PathBuf>,
+
+    /// Use the state dumped by `anvil --dump-state` as the fork source instead of the RPC.
+    ///
+    /// The accounts and storage of the file are loaded into the local executor, and the block
+    /// environment is taken from its metadata when present. Nothing is fetched from the RPC, so
+    /// the call can be traced fully offline.
+    /// Can only be used with `--trace`.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "trace",
+        conflicts_with_all = ["simulate_bundle", "include_pending", "from_tx", "block", "identify_only"]
+    )]
+    fork_state_file: Option<PathBuf>,
+
+    /// Don't read or write the RPC storage cache of the traced block.
+    ///
+    /// By default, the state fetched from the RPC for a block is cached on disk, like for forked
+    /// tests, so that tracing again at the same block doesn't fetch it again. The cache directory
+    /// can be configured with `rpc_cache_path`.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace", conflicts_with = "fork_state_file")]
+    no_fork_cache: bool,
 
     /// Labels to apply to the traces; format: `address:label`.
     /// Can only be used with `--trace`.
     #[arg(long, requires = "trace")]
     labels: Vec<String>,
 
+    /// Write the traces to a JSON file, to render them again later with `cast trace-render`
+    /// without executing the call.
+    ///
+    /// The traces are written before being identified, so that they can be rendered with other
+    /// labels and artifacts.
+    /// Can only be used with `--trace`.
+    #[arg(long, value_name = "PATH", requires = "trace", conflicts_with = "simulate_bundle")]
+    trace_export: Option<PathBuf>,
+
+    /// Print the state of the accounts touched by the call before it was executed, in the format
+    /// of geth's `prestateTracer`, instead of the trace.
+    ///
+    /// The balance, nonce, code and accessed storage slots of each account are included.
+    /// Can only be used with `--trace`, for a single call.
+    #[arg(
+        long,
+        requires = "trace",
+        conflicts_with_all = ["batch_file", "to_list", "simulate_bundle", "debug", "trace_export"]
+    )]
+    prestate: bool,
+
+    /// Stub a precompile, so that the calls to it return the given data instead of executing it,
+    /// e.g. to make `ecrecover` return a fixed address.
+    ///
+    /// Only the precompiles from `0x01` (ecrecover) to `0x0a` (point evaluation) can be stubbed,
+    /// unless `--force` is passed. The calls to stubbed precompiles are still traced.
+    /// Format: address:returndata
+    /// Can only be used with `--trace`.
+    #[arg(long = "override-precompile", value_name = "ADDRESS:RETURNDATA", requires = "trace")]
+    precompile_overrides: Vec<String>,
+
+    /// Allow `--override-precompile` to stub addresses that are not precompiles.
+    #[arg(long, requires = "precompile_overrides")]
+    force: bool,
+
+    /// Stub the calls to a function of a contract, so that they return the given data instead of
+    /// executing its code, e.g. to make an oracle return a fixed price.
+    ///
+    /// The selector is either given as hex, e.g. `0x70a08231`, or as a function signature, e.g.
+    /// `balanceOf(address)`. Calls are matched on the called address, so delegatecalls made by
+    /// the contract are not stubbed. If the same address and selector are given more than once,
+    /// the last one is used, and a stubbed call takes precedence over `--override-precompile`.
+    /// The stubbed calls are still traced; they use no gas, and do not transfer their value.
+    /// Format: address:selector:returndata
+    /// Can only be used with `--trace`.
+    #[arg(
+        long = "call-override-return",
+        value_name = "ADDRESS:SELECTOR:RETURNDATA",
+        requires = "trace"
+    )]
+    call_return_overrides: Vec<String>,
+
+    /// Additional ABIs to decode the call with, e.g. to resolve the custom errors of unverified
+    /// contracts.
+    ///
+    /// Either a path to a JSON ABI or artifact file, or an inline JSON ABI. Can be repeated, and
+    /// is merged with the local artifacts when using `--with-local-artifacts`.
+    #[arg(long = "abi", value_name = "PATH_OR_JSON")]
+    abis: Vec<String>,
+
     /// The EVM Version to use.
     /// Can only be used with `--trace`.
     #[arg(long, requires = "trace")]
@@ -101,9 +568,113 @@ pub struct CallArgs {
 
     /// The block height to query at.
     ///
-    /// Can also be the tags earliest, finalized, safe, latest, or pending.
-    #[arg(long, short)]
-    block: Option<BlockId>,
+    /// Can also be the tags earliest, finalized, safe, latest, or pending, `genesis` for block 0,
+    /// or a negative number of blocks before the latest one, e.g. `-10`.
+    #[arg(long, short, allow_negative_numbers = true)]
+    block: Option<CallBlock>,
+
+    /// Override the timestamp of the block the call is executed in.
+    ///
+    /// This only changes the block context seen by the EVM, e.g. `block.timestamp`, not the block
+    /// whose state is used, see `--block`. Without `--trace`, the RPC node must support block
+    /// overrides in `eth_call`.
+    #[arg(long, value_name = "TIMESTAMP")]
+    block_timestamp: Option<u64>,
+
+    /// Override the number of the block the call is executed in.
+    ///
+    /// This only changes the block context seen by the EVM, e.g. `block.number`, not the block
+    /// whose state is used, see `--block`. Without `--trace`, the RPC node must support block
+    /// overrides in `eth_call`.
+    #[arg(long, value_name = "NUMBER")]
+    block_number_override: Option<u64>,
+
+    /// The versioned hash of a blob of the call, as read by `BLOBHASH`. Can be repeated.
+    ///
+    /// With `--trace`, the blob gas is paid at the blob base fee of the block unless
+    /// `--blob-gas-price` is set.
+    #[arg(long = "blob-versioned-hash", value_name = "HASH", conflicts_with = "blob")]
+    blob_versioned_hashes: Vec<B256>,
+
+    /// Override the blob base fee of the block the call is executed in, as read by
+    /// `BLOBBASEFEE`, in wei.
+    ///
+    /// Can only be used with `--trace`, as it can't be overridden in `eth_call`.
+    #[arg(long, value_name = "FEE", requires = "trace")]
+    blob_base_fee: Option<u128>,
+
+    /// Execute the call at each block of the range and print the blocks at which the output
+    /// changes.
+    ///
+    /// An optional step can be given to only sample every n-th block.
+    /// Cannot be used with `--trace`.
+    #[arg(
+        long,
+        value_name = "START:END[:STEP]",
+        value_parser = BlockRange::from_str,
+        conflicts_with_all = &["block", "trace"]
+    )]
+    block_range: Option<BlockRange>,
+
+    /// Execute the call again on each new block, and print the output with the timestamp of the
+    /// block whenever it changes.
+    ///
+    /// New blocks are subscribed to, which requires a WebSocket RPC URL, unless
+    /// `--watch-interval` is set.
+    /// Cannot be used with `--trace`.
+    #[arg(
+        long,
+        conflicts_with_all = ["trace", "block", "block_range", "diff_against", "to_list", "simulate_bundle", "identify_only"]
+    )]
+    watch: bool,
+
+    /// Poll for new blocks every given number of seconds instead of subscribing to them, e.g.
+    /// over HTTP.
+    #[arg(long, value_name = "SECONDS", requires = "watch")]
+    watch_interval: Option<u64>,
+
+    /// Also execute the call on the given RPC endpoint, and fail if its outcome differs.
+    ///
+    /// The outputs or revert reasons of both nodes are printed when they differ. Both nodes are
+    /// called with the same state overrides and at the same block, which defaults to the latest
+    /// block of the primary node.
+    /// Cannot be used with `--trace`.
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["trace", "block_range", "estimate", "simulate_bundle"]
+    )]
+    diff_against: Option<String>,
+
+    /// Also estimate the gas used by the call, and print it after the output.
+    ///
+    /// State overrides are applied to the estimation too.
+    /// Cannot be used with `--trace` or block overrides.
+    #[arg(
+        long,
+        conflicts_with_all = &["trace", "block_range", "block_timestamp", "block_number_override"]
+    )]
+    estimate: bool,
+
+    /// Print decoded tuples and arrays over multiple indented lines, with the fields of tuples
+    /// labeled with the names of the return values, instead of each value on a single line.
+    #[arg(long)]
+    pretty: bool,
+
+    /// Print the decoded return values on a single line, separated by tabs, e.g. to split them
+    /// with `cut` or `read` in shell scripts.
+    ///
+    /// Cannot be used with `--trace` or `--pretty`.
+    #[arg(long, conflicts_with_all = ["trace", "raw", "pretty"])]
+    tsv: bool,
+
+    /// Print the raw ABI-encoded output of the call, without decoding it even if the return types
+    /// of the function are known, e.g. to pipe it into another command.
+    ///
+    /// The JSON output always includes the raw output.
+    /// Cannot be used with `--trace`.
+    #[arg(long, conflicts_with_all = ["trace", "output_abi", "pretty"])]
+    raw: bool,
 
     /// Enable Odyssey features.
     #[arg(long, alias = "alphanet")]
@@ -122,30 +693,106 @@ pub struct CallArgs {
     #[arg(long, visible_alias = "la")]
     pub with_local_artifacts: bool,
 
+    /// Skip the identification of the traced contracts, which can be slow for large traces.
+    ///
+    /// Addresses are rendered as is unless labeled with `--labels`, and calls are only decoded
+    /// from the signatures of their selectors.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace", conflicts_with = "with_local_artifacts")]
+    quick: bool,
+
+    /// Require the traced contracts that could not be identified locally to be looked up on
+    /// Etherscan, failing before executing the call if no Etherscan API key is configured.
+    ///
+    /// Etherscan is otherwise used whenever an API key is configured, unless `--no-etherscan` is
+    /// passed. Each address is only looked up once.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace", conflicts_with_all = ["quick", "no_etherscan"])]
+    abi_from_etherscan: bool,
+
+    /// Don't look up the traced contracts on Etherscan, even if an API key is configured.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    no_etherscan: bool,
+
+    /// Render the addresses of the traces that have a reverse ENS record as `name.eth (0x...)`.
+    ///
+    /// Each address is looked up once from the RPC at the block of the call, which can be slow
+    /// for large traces.
+    /// Addresses labeled with `--labels` keep their label.
+    /// Can only be used with `--trace`.
+    #[arg(long, requires = "trace")]
+    ens_names: bool,
+
+    /// Don't make any network request besides the ones to the RPC.
+    ///
+    /// Traces are only identified from the local artifacts and the cached signatures, and ENS
+    /// names can't be used as the destination. Fails instead of reaching Etherscan or OpenChain,
+    /// so that traces are reproducible, e.g. in CI.
+    #[arg(long, conflicts_with_all = ["abi_from_etherscan", "ens_names", "diff_against"])]
+    offline: bool,
+
     /// Override the balance of an account.
     /// Format: address:balance
+    ///
+    /// The balance can also be a delta relative to the current balance, e.g. `+1ether` or
+    /// `-0.5ether`.
     #[arg(long = "override-balance", value_name = "ADDRESS:BALANCE")]
     pub balance_overrides: Option<Vec<String>>,
 
     /// Override the nonce of an account.
     /// Format: address:nonce
+    ///
+    /// The nonce can also be `auto` for the current nonce of the account at the block, optionally
+    /// with an offset, e.g. `auto+1` or `auto-1`.
     #[arg(long = "override-nonce", value_name = "ADDRESS:NONCE")]
     pub nonce_overrides: Option<Vec<String>>,
 
     /// Override the code of an account.
+    ///
+    /// The code can also be the deployed code of a local artifact, given as `@<name>` or
+    /// `@<identifier>`, which requires `--with-local-artifacts`. An empty code, e.g. `0x`, clears
+    /// the code of the account.
     /// Format: address:code
     #[arg(long = "override-code", value_name = "ADDRESS:CODE")]
     pub code_overrides: Option<Vec<String>>,
 
     /// Override the state of an account.
+    ///
+    /// The slot can also be the slot of a mapping entry, given as `key@slot`, e.g. `0x...@5` for
+    /// the entry of an address in the mapping at slot 5. Nested mappings are given from the
+    /// outermost key, as in Solidity: `k1@k2@5` is the slot of `mapping[k1][k2]`. Keys are
+    /// addresses if they are 20 bytes long, and uint256 otherwise.
     /// Format: address:slot:value
     #[arg(long = "override-state", value_name = "ADDRESS:SLOT:VALUE")]
     pub state_overrides: Option<Vec<String>>,
 
     /// Override the state diff of an account.
+    ///
+    /// The slot can also be the slot of a mapping entry, as for `--override-state`.
     /// Format: address:slot:value
     #[arg(long = "override-state-diff", value_name = "ADDRESS:SLOT:VALUE")]
     pub state_diff_overrides: Option<Vec<String>>,
+
+    /// Override the storage of an account with the slots from a JSON file.
+    ///
+    /// The file must contain an object mapping 32-byte hex slots to values. Slots set with
+    /// `--override-state` take precedence over the ones from the file.
+    ///
+    /// Without an address, the file must be a dump written with `--state-dump`, whose slots are
+    /// applied on top of the current storage of each account, along with the balances, nonces
+    /// and code of the dump. The other overrides take precedence over them.
+    /// Format: address:path or path
+    #[arg(long = "override-storage-dump", value_name = "[ADDRESS:]PATH")]
+    pub storage_dump_overrides: Option<Vec<String>>,
+
+    /// Only validate the overrides, reporting all the invalid ones at once, without executing the
+    /// call.
+    ///
+    /// The overrides are always validated before connecting to the RPC, so that an invalid
+    /// override doesn't mask the others.
+    #[arg(long)]
+    check_overrides: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -169,172 +816,532 @@ pub enum CallSubcommands {
         /// Examples: 1ether, 10gwei, 0.01ether
         #[arg(long, value_parser = parse_ether_value)]
         value: Option<U256>,
+
+        /// The salt to deploy the contract with through the CREATE2 deployer.
+        ///
+        /// The predicted address of the contract is printed.
+        #[arg(long)]
+        salt: Option<B256>,
+
+        /// The address of the CREATE2 deployer to use with `--salt`.
+        ///
+        /// Defaults to the configured `create2_deployer`.
+        #[arg(long, requires = "salt")]
+        deployer: Option<Address>,
     },
 }
 
 impl CallArgs {
     pub async fn run(self) -> Result<()> {
-        let figment = Into::<Figment>::into(&self.eth).merge(&self);
-        let evm_opts = figment.extract::<EvmOpts>()?;
-        let mut config = Config::from_provider(figment)?.sanitized();
-        let state_overrides = self.get_state_overrides()?;
-
+        let labels = self.labels.clone();
+        let trace_filter = self.trace_filter.clone();
         let Self {
-            to,
-            mut sig,
-            mut args,
-            mut tx,
-            eth,
-            command,
-            block,
-            trace,
-            evm_version,
+            with_local_artifacts,
+            quick,
             debug,
             decode_internal,
-            labels,
-            data,
-            with_local_artifacts,
+            show_storage_reads,
+            trace_format,
+            gas_report,
+            sort_by,
+            top,
+            gas_by_opcode,
+            trace_stack_depth_limit,
+            decode_events,
+            inspect_selectors,
+            identify_creation,
+            explain,
+            revert_on_failure,
+            no_etherscan,
+            ens_names,
             ..
         } = self;
+        let trace_export = self.trace_export.clone();
+        let Some(TracedCalls { mut traces, batch, targets, config, chain, block, abis }) =
+            self.execute().await?
+        else {
+            return Ok(());
+        };
+        if let Some(path) = trace_export {
+            let export = TraceExport { chain, traces, targets: targets.clone() };
+            export.write(&path)?;
+            traces = export.traces;
+        }
+        let options = TraceOptions {
+            labels,
+            with_local_artifacts,
+            skip_identification: quick,
+            with_etherscan: !no_etherscan,
+            ens_names,
+            block,
+            abis: &abis,
+            debug,
+            decode_internal: decode_internal.unwrap_or_default(),
+            storage_reads: show_storage_reads,
+            trace_format,
+            gas_report,
+            gas_report_sort: sort_by,
+            gas_report_top: top,
+            gas_by_opcode,
+            depth_limit: trace_stack_depth_limit,
+            trace_filter: &trace_filter,
+            decode_events,
+            inspect_selectors,
+            identify_creation,
+            explain,
+        };
+        // The traces of all the calls are printed as a single JSON document.
+        let json_traces = trace_format == TraceFormat::Json;
+        let mut merged = Vec::new();
+        let revert_decoder = RevertDecoder::new().with_abis(&abis);
+        let mut first_revert = None;
+        for (i, trace) in traces.into_iter().enumerate() {
+            let revert_reason = trace_revert_reason(&trace, &revert_decoder);
+            if batch {
+                let target = targets.as_ref().map(|targets| targets[i]);
+                if first_revert.is_none() {
+                    first_revert = revert_reason.map(|reason| match target {
+                        Some(target) => format!("{target}: {reason}"),
+                        None => format!("call #{}: {reason}", i + 1),
+                    });
+                }
+                if !shell::is_json() && !json_traces {
+                    if i > 0 {
+                        sh_println!()?;
+                    }
+                    match target {
+                        Some(target) => sh_println!("{target}:")?,
+                        None => sh_println!("Call #{}:", i + 1)?,
+                    }
+                }
+            } else {
+                first_revert = revert_reason;
+            }
+            if json_traces {
+                merged.push(trace);
+            } else {
+                handle_traces(trace, &config, chain, options.clone()).await?;
+            }
+        }
+        if !merged.is_empty() {
+            handle_traces(merge_trace_results(merged), &config, chain, options).await?;
+        }
 
-        if let Some(data) = data {
-            sig = Some(data);
+        if let Some(reason) = first_revert.filter(|_| revert_on_failure) {
+            return Err(CallRevertedError { reason }.into());
         }
+        Ok(())
+    }
 
-        let provider = utils::get_provider(&config)?;
-        let sender = SenderKind::from_wallet_opts(eth.wallet).await?;
-        let from = sender.address();
+    /// Executes the call locally and returns its trace, without printing it.
+    ///
+    /// Requires `--trace`. Multiple calls are merged into a single result, with one trace arena
+    /// per call in order of execution. State dumps are written as with [`run`](Self::run).
+    pub async fn run_traced(self) -> Result<TraceResult> {
+        if !self.trace {
+            eyre::bail!("Tracing the call requires --trace");
+        }
+        if self.simulate_bundle.is_some() {
+            eyre::bail!("--simulate-bundle prints its own traces and can't be traced separately");
+        }
+        let traced = self.execute().await?;
+        let TracedCalls { traces, .. } =
+            traced.ok_or_else(|| eyre::eyre!("The call was not executed locally"))?;
+        Ok(merge_trace_results(traces))
+    }
 
-        let code = if let Some(CallSubcommands::Create {
-            code,
-            sig: create_sig,
-            args: create_args,
-            value,
-        }) = command
-        {
-            sig = create_sig;
-            args = create_args;
-            if let Some(value) = value {
-                tx.value = Some(value);
-            }
-            Some(code)
-        } else {
-            None
+    /// Executes the call, returning the traces of the calls executed locally with `--trace` for
+    /// the caller to render. The output of all the other calls is printed directly.
+    async fn execute(self) -> Result<Option<TracedCalls>> {
+        self.validate_trace_format()?;
+        self.validate_overrides()?;
+        if self.check_overrides {
+            sh_println!("All overrides are valid")?;
+            return Ok(None);
+        }
+
+        let figment = Into::<Figment>::into(&self.eth).merge(&self);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::from_provider(figment)?.sanitized();
+        let provider = utils::get_provider(&config)?;
+        let block = match self.block {
+            Some(block) => Some(block.resolve(&provider).await?),
+            None => None,
         };
+        let fork_state = self.fork_state_file.as_deref().map(ForkState::load).transpose()?;
+        let mut ctx = CallContext { config, evm_opts, provider, block, fork_state };
+        if self.identify_only {
+            self.run_identify_only(&ctx).await?;
+            return Ok(None);
+        }
 
-        let (tx, func) = CastTxBuilder::new(&provider, tx, &config)
-            .await?
-            .with_to(to)
-            .await?
-            .with_code_sig_and_args(code, sig, args)
-            .await?
-            .build_raw(sender)
-            .await?;
+        // Held until the end of the call, so that concurrent calls can't overwrite the state.
+        let _state_lock = self.continue_state.as_deref().map(StateLock::acquire).transpose()?;
+        let known_contracts = self.known_contracts(&ctx.config)?;
+        let state_overrides = self.resolve_state_overrides(&ctx, known_contracts.as_ref()).await?;
+        // Checked before executing the calls, whose traces are only identified at the end.
+        if self.abi_from_etherscan {
+            ensure_etherscan_key(&ctx.config, Some(ctx.chain().await?))?;
+        }
+        if let Some(path) = self.simulate_bundle.clone() {
+            self.run_bundle(&path, ctx, state_overrides).await?;
+            return Ok(None);
+        }
 
-        if trace {
-            if let Some(BlockId::Number(BlockNumberOrTag::Number(block_number))) = self.block {
-                // Override Config `fork_block_number` (if set) with CLI value.
-                config.fork_block_number = Some(block_number);
+        let mut call =
+            self.prepare_call(&mut ctx, state_overrides, known_contracts.as_ref()).await?;
+        if self.trace || call.generate_access_list {
+            let opts = self.local_executor_opts(&ctx, &call)?;
+            let (mut executor, chain, block_gas_limit) =
+                local_executor(&mut ctx, &mut call.tx, opts).await?;
+            if call.generate_access_list {
+                // In JSON mode, the access list is printed instead of executing the call.
+                let Some(access_list) = generate_access_list(&executor, &call)? else {
+                    return Ok(None);
+                };
+                call.tx.set_access_list(access_list);
             }
+            if self.trace {
+                return self.run_trace(executor, ctx, call, chain, block_gas_limit);
+            }
+        }
 
-            let create2_deployer = evm_opts.create2_deployer;
-            let (mut env, fork, chain, odyssey) =
-                TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        let rpc_call = self.rpc_call(&call);
+        if let Some(targets) = &call.targets {
+            self.run_to_list(rpc_call, &ctx, targets).await?;
+        } else if let Some(url) = &self.diff_against {
+            self.run_diff(rpc_call, &ctx, url, &call.revert_decoder).await?;
+        } else if self.watch {
+            self.run_watch(rpc_call, &ctx).await?;
+        } else if let Some(block_range) = &self.block_range {
+            self.run_range(rpc_call, &ctx, block_range).await?;
+        } else {
+            self.run_plain(rpc_call, &ctx, call.revert_decoder.clone()).await?;
+        }
+        Ok(None)
+    }
 
-            // modify settings that usually set in eth_call
-            env.cfg.disable_block_gas_limit = true;
-            env.block.gas_limit = U256::MAX;
+    /// Loads the storage dumps passed with `--override-storage-dump`.
+    pub fn load_storage_dumps(&self) -> eyre::Result<StorageDumps> {
+        let mut dumps = StorageDumps::default();
+        for override_str in self.storage_dump_overrides.iter().flatten() {
+            match storage_dump_target(override_str) {
+                (Some(addr), path) => dumps.storage.push((addr, read_storage_dump(path)?)),
+                (None, path) => merge_state_dump(&mut dumps.state, read_state_dump(path)?),
+            }
+        }
+        if let Some(path) = self.continue_state.as_deref().filter(|path| path.exists()) {
+            merge_state_dump(&mut dumps.state, read_state_dump(path)?);
+        }
+        Ok(dumps)
+    }
 
-            let trace_mode = TraceMode::Call
-                .with_debug(debug)
-                .with_decode_internal(if decode_internal {
-                    InternalTraceMode::Full
-                } else {
-                    InternalTraceMode::None
-                })
-                .with_state_changes(shell::verbosity() > 4);
-            let mut executor = TracingExecutor::new(
-                env,
-                fork,
-                evm_version,
-                trace_mode,
-                odyssey,
-                create2_deployer,
-            )?;
-
-            let value = tx.value.unwrap_or_default();
-            let input = tx.inner.input.into_input().unwrap_or_default();
-            let tx_kind = tx.inner.to.expect("set by builder");
-
-            let trace = match tx_kind {
-                TxKind::Create => {
-                    let deploy_result = executor.deploy(from, input, value, None);
-                    TraceResult::try_from(deploy_result)?
+    /// Ensures that the options only applying to the human-readable trace are not combined with
+    /// the JSON trace format.
+    fn validate_trace_format(&self) -> Result<()> {
+        if self.trace_format != TraceFormat::Json {
+            return Ok(());
+        }
+        let pretty_only = [
+            ("--show-storage-reads", self.show_storage_reads),
+            ("--trace-stack-depth-limit", self.trace_stack_depth_limit.is_some()),
+            ("--trace-filter", !self.trace_filter.is_empty()),
+        ];
+        if let Some((flag, _)) = pretty_only.into_iter().find(|&(_, set)| set) {
+            eyre::bail!("{flag} can't be used with --trace-format json");
+        }
+        Ok(())
+    }
+
+    /// Validates the syntax of all the overrides without fetching the current state, reporting all
+    /// the invalid overrides at once instead of failing on the first one.
+    ///
+    /// Conflicting overrides of the full storage and of the state diff of an account are reported
+    /// as well.
+    pub fn validate_overrides(&self) -> eyre::Result<()> {
+        let mut errors = Vec::new();
+        let mut check = |flag: &str, override_str: &str, result: Result<()>| {
+            if let Err(err) = result {
+                errors.push(format!("--{flag} {override_str}: {err:#}"));
+            }
+        };
+
+        for override_str in self.balance_overrides.iter().flatten() {
+            let result = address_value_override(override_str).and_then(|(addr, balance)| {
+                addr.parse::<Address>()?;
+                match balance.strip_prefix(['+', '-']) {
+                    Some(delta) => parse_ether_value(delta).map(drop),
+                    None => Ok(balance.parse::<U256>().map(drop)?),
                 }
-                TxKind::Call(to) => TraceResult::from_raw(
-                    executor.transact_raw(from, to, input, value)?,
-                    TraceKind::Execution,
-                ),
-            };
+            });
+            check("override-balance", override_str, result);
+        }
+        for override_str in self.nonce_overrides.iter().flatten() {
+            let result = address_value_override(override_str).and_then(|(addr, nonce)| {
+                addr.parse::<Address>()?;
+                match nonce.strip_prefix("auto") {
+                    Some(offset) => parse_nonce_offset(nonce, offset).map(drop),
+                    None => Ok(nonce.parse::<u64>().map(drop)?),
+                }
+            });
+            check("override-nonce", override_str, result);
+        }
+        for override_str in self.code_overrides.iter().flatten() {
+            let result = address_value_override(override_str).and_then(|(addr, code)| {
+                addr.parse::<Address>()?;
+                if !code.starts_with('@') {
+                    Bytes::from_str(code)?;
+                } else if !self.with_local_artifacts {
+                    eyre::bail!("Using an artifact requires --with-local-artifacts");
+                }
+                Ok(())
+            });
+            check("override-code", override_str, result);
+        }
 
-            handle_traces(
-                trace,
-                &config,
-                chain,
-                labels,
-                with_local_artifacts,
-                debug,
-                decode_internal,
-            )
-            .await?;
+        // A state override or a storage dump of an account replaces its whole storage, while a
+        // state diff or a dump written with `--state-dump` only changes the given slots.
+        let mut state_addresses = HashSet::new();
+        for override_str in self.state_overrides.iter().flatten() {
+            let result = address_slot_value_override(override_str);
+            if let Ok((addr, ..)) = result {
+                state_addresses.insert(addr);
+            }
+            check("override-state", override_str, result.map(drop));
+        }
+        let mut state_dumps = Vec::new();
+        for override_str in self.storage_dump_overrides.iter().flatten() {
+            let result = match storage_dump_target(override_str) {
+                (Some(addr), path) => {
+                    state_addresses.insert(addr);
+                    read_storage_dump(path).map(drop)
+                }
+                (None, path) => read_state_dump(path).map(|dump| {
+                    state_dumps.push(("override-storage-dump", override_str.as_str(), dump))
+                }),
+            };
+            check("override-storage-dump", override_str, result);
+        }
+        if let Some(path) = self.continue_state.as_ref().filter(|path| path.exists()) {
+            let path_str = path.to_str().unwrap_or_default();
+            match read_state_dump(path) {
+                Ok(dump) => state_dumps.push(("continue-state", path_str, dump)),
+                Err(err) => check("continue-state", path_str, Err(err)),
+            }
+        }
 
-            return Ok(());
+        let ensure_no_state = |addr: &Address| {
+            if state_addresses.contains(addr) {
+                eyre::bail!(
+                    "Cannot also override the full storage of {addr} with --override-state or \
+                     --override-storage-dump"
+                );
+            }
+            Ok(())
+        };
+        for &(flag, override_str, ref dump) in &state_dumps {
+            let result = (dump.iter())
+                .filter(|(_, account)| !account.storage.is_empty())
+                .try_for_each(|(addr, _)| ensure_no_state(addr));
+            check(flag, override_str, result);
+        }
+        for override_str in self.state_diff_overrides.iter().flatten() {
+            let result = address_slot_value_override(override_str)
+                .and_then(|(addr, ..)| ensure_no_state(&addr));
+            check("override-state-diff", override_str, result);
         }
 
-        sh_println!(
-            "{}",
-            Cast::new(provider).call(&tx, func.as_ref(), block, state_overrides).await?
-        )?;
+        for override_str in &self.precompile_overrides {
+            let result = parse_precompile_overrides(std::slice::from_ref(override_str), self.force);
+            check("override-precompile", override_str, result.map(drop));
+        }
+        for override_str in &self.call_return_overrides {
+            let result = parse_call_return_overrides(std::slice::from_ref(override_str));
+            check("call-override-return", override_str, result.map(drop));
+        }
 
+        if !errors.is_empty() {
+            eyre::bail!("Invalid overrides:\n  {}", errors.join("\n  "));
+        }
         Ok(())
     }
-    /// Parse state overrides from command line arguments
-    pub fn get_state_overrides(&self) -> eyre::Result<StateOverride> {
+
+    /// Returns `true` if an `--override-code` refers to the code of a local artifact.
+    fn has_artifact_code_overrides(&self) -> bool {
+        self.code_overrides.iter().flatten().any(|override_str| {
+            address_value_override(override_str).is_ok_and(|(_, code)| code.starts_with('@'))
+        })
+    }
+
+    /// Returns the accounts whose `--override-balance` is a delta.
+    fn delta_balance_accounts(&self) -> eyre::Result<Vec<Address>> {
+        let mut accounts = Vec::new();
+        for override_str in self.balance_overrides.iter().flatten() {
+            let (addr, balance) = address_value_override(override_str)?;
+            if balance.starts_with(['+', '-']) {
+                accounts.push(addr.parse()?);
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Returns the accounts whose `--override-nonce` is `auto`.
+    fn auto_nonce_accounts(&self) -> eyre::Result<Vec<Address>> {
+        let mut accounts = Vec::new();
+        for override_str in self.nonce_overrides.iter().flatten() {
+            let (addr, nonce) = address_value_override(override_str)?;
+            if nonce.starts_with("auto") {
+                accounts.push(addr.parse()?);
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Fetches the current balances of the accounts whose `--override-balance` is a delta.
+    pub async fn fetch_delta_balances<P: Provider<AnyNetwork>>(
+        &self,
+        provider: &P,
+        block: Option<BlockId>,
+    ) -> eyre::Result<AddressHashMap<U256>> {
+        let mut balances = AddressHashMap::default();
+        for addr in self.delta_balance_accounts()? {
+            let balance = provider.get_balance(addr).block_id(block.unwrap_or_default()).await?;
+            balances.insert(addr, balance);
+        }
+        Ok(balances)
+    }
+
+    /// Fetches the current nonces of the accounts whose `--override-nonce` is `auto`.
+    pub async fn fetch_auto_nonces<P: Provider<AnyNetwork>>(
+        &self,
+        provider: &P,
+        block: Option<BlockId>,
+    ) -> eyre::Result<AddressHashMap<u64>> {
+        let mut nonces = AddressHashMap::default();
+        for addr in self.auto_nonce_accounts()? {
+            let nonce =
+                provider.get_transaction_count(addr).block_id(block.unwrap_or_default()).await?;
+            nonces.insert(addr, nonce);
+        }
+        Ok(nonces)
+    }
+
+    /// Parse state overrides from command line arguments, merged with the given storage dumps.
+    ///
+    /// Slots from `--override-state` take precedence over the ones from the storage dumps.
+    /// Balance deltas are applied to the given current balances, see
+    /// [`fetch_delta_balances`](Self::fetch_delta_balances), and `auto` nonces are resolved from
+    /// the given current nonces, see [`fetch_auto_nonces`](Self::fetch_auto_nonces).
+    pub fn get_state_overrides(
+        &self,
+        storage_dumps: StorageDumps,
+        current_balances: &AddressHashMap<U256>,
+        current_nonces: &AddressHashMap<u64>,
+        known_contracts: Option<&ContractsByArtifact>,
+    ) -> eyre::Result<StateOverride> {
         let mut state_overrides_builder = StateOverridesBuilder::default();
 
+        // A storage dump or state override replaces the whole storage of the account, which can't
+        // be combined with a state diff.
+        let mut state_diff_addresses = self
+            .state_diff_overrides
+            .iter()
+            .flatten()
+            .map(|override_str| Ok(address_slot_value_override(override_str)?.0))
+            .collect::<Result<HashSet<Address>>>()?;
+        state_diff_addresses.extend(
+            (storage_dumps.state.iter())
+                .filter(|(_, account)| !account.storage.is_empty())
+                .map(|(addr, _)| *addr),
+        );
+        for (addr, _) in &storage_dumps.storage {
+            if state_diff_addresses.contains(addr) {
+                eyre::bail!(
+                    "Cannot use both --override-storage-dump and --override-state-diff for {addr}: \
+                     a storage dump replaces the full storage of the account"
+                );
+            }
+        }
+        for override_str in self.state_overrides.iter().flatten() {
+            let addr = address_slot_value_override(override_str)?.0;
+            if state_diff_addresses.contains(&addr) {
+                eyre::bail!(
+                    "Cannot use both --override-state and --override-state-diff for {addr}: \
+                     a state override replaces the full storage of the account, while a state \
+                     diff only changes the given slots"
+                );
+            }
+        }
+
+        // Storage dumps are merged before the state overrides so that those take precedence.
+        // Each account's storage must be set at once, as setting it replaces the previous one.
+        let mut states: BTreeMap<Address, Vec<(B256, B256)>> = BTreeMap::new();
+        for (addr, storage) in storage_dumps.storage {
+            states.entry(addr).or_default().extend(storage);
+        }
+
+        // The balances, nonces and code left by previous calls are applied first as well.
+        for (addr, account) in &storage_dumps.state {
+            if let Some(balance) = account.balance {
+                state_overrides_builder = state_overrides_builder.with_balance(*addr, balance);
+            }
+            if let Some(nonce) = account.nonce {
+                state_overrides_builder = state_overrides_builder.with_nonce(*addr, nonce);
+            }
+            if let Some(code) = &account.code {
+                state_overrides_builder = state_overrides_builder.with_code(*addr, code.clone());
+            }
+        }
+
         // Parse balance overrides
         for override_str in self.balance_overrides.iter().flatten() {
             let (addr, balance) = address_value_override(override_str)?;
-            state_overrides_builder =
-                state_overrides_builder.with_balance(addr.parse()?, balance.parse()?);
+            let addr = addr.parse()?;
+            let balance = parse_balance_override(addr, balance, current_balances.get(&addr))?;
+            state_overrides_builder = state_overrides_builder.with_balance(addr, balance);
         }
 
         // Parse nonce overrides
         for override_str in self.nonce_overrides.iter().flatten() {
             let (addr, nonce) = address_value_override(override_str)?;
-            state_overrides_builder =
-                state_overrides_builder.with_nonce(addr.parse()?, nonce.parse()?);
+            let addr = addr.parse()?;
+            let nonce = parse_nonce_override(addr, nonce, current_nonces.get(&addr))?;
+            state_overrides_builder = state_overrides_builder.with_nonce(addr, nonce);
         }
 
         // Parse code overrides
         for override_str in self.code_overrides.iter().flatten() {
             let (addr, code_str) = address_value_override(override_str)?;
-            state_overrides_builder =
-                state_overrides_builder.with_code(addr.parse()?, Bytes::from_str(code_str)?);
+            let code = match code_str.strip_prefix('@') {
+                Some(name) => artifact_deployed_code(name, known_contracts)?,
+                None => Bytes::from_str(code_str)?,
+            };
+            state_overrides_builder = state_overrides_builder.with_code(addr.parse()?, code);
         }
 
         // Parse state overrides
         for override_str in self.state_overrides.iter().flatten() {
             let (addr, slot, value) = address_slot_value_override(override_str)?;
-            state_overrides_builder =
-                state_overrides_builder.with_state(addr, [(slot.into(), value.into())]);
+            states.entry(addr).or_default().push((slot.into(), value.into()));
+        }
+        for (addr, state) in states {
+            state_overrides_builder = state_overrides_builder.with_state(addr, state);
         }
 
-        // Parse state diff overrides
+        // Parse state diff overrides, on top of the dumped state diffs. As for the state, each
+        // account's state diff must be set at once.
+        let mut state_diffs: BTreeMap<Address, Vec<(B256, B256)>> = BTreeMap::new();
+        for (addr, account) in storage_dumps.state {
+            if !account.storage.is_empty() {
+                state_diffs.entry(addr).or_default().extend(account.storage);
+            }
+        }
         for override_str in self.state_diff_overrides.iter().flatten() {
             let (addr, slot, value) = address_slot_value_override(override_str)?;
-            state_overrides_builder =
-                state_overrides_builder.with_state_diff(addr, [(slot.into(), value.into())]);
+            state_diffs.entry(addr).or_default().push((slot.into(), value.into()));
+        }
+        for (addr, state_diff) in state_diffs {
+            state_overrides_builder = state_overrides_builder.with_state_diff(addr, state_diff);
         }
 
         Ok(state_overrides_builder.build())
@@ -357,64 +1364,4177 @@ fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
             map.insert("evm_version".into(), figment::value::Value::serialize(evm_version)?);
         }
 
+        if self.no_fork_cache {
+            map.insert("no_storage_caching".into(), true.into());
+        }
+
+        if self.offline {
+            map.insert("offline".into(), true.into());
+        }
+
         Ok(Map::from([(Config::selected_profile(), map)]))
     }
 }
 
-/// Parse an override string in the format address:value.
-fn address_value_override(address_override: &str) -> Result<(&str, &str)> {
-    address_override.split_once(':').ok_or_else(|| {
-        eyre::eyre!("Invalid override {address_override}. Expected <address>:<value>")
+/// An inclusive range of blocks to execute a call at, see `--block-range`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockRange {
+    pub start: u64,
+    pub end: u64,
+    pub step: u64,
+}
+
+impl BlockRange {
+    /// Returns the block numbers of the range.
+    pub fn blocks(&self) -> impl Iterator<Item = u64> {
+        (self.start..=self.end).step_by(self.step as usize)
+    }
+}
+
+impl FromStr for BlockRange {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s.split(':').collect::<Vec<_>>();
+        let (start, end, step) = match parts[..] {
+            [start, end] => (start, end, "1"),
+            [start, end, step] => (start, end, step),
+            _ => eyre::bail!("Invalid block range {s}. Expected <start>:<end>[:<step>]"),
+        };
+
+        let range = Self { start: start.parse()?, end: end.parse()?, step: step.parse()? };
+        if range.start > range.end {
+            eyre::bail!("Invalid block range {s}: start block is after end block");
+        }
+        if range.step == 0 {
+            eyre::bail!("Invalid block range {s}: step must be greater than 0");
+        }
+        Ok(range)
+    }
+}
+
+/// A block to execute a call at, see `--block`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallBlock {
+    /// An absolute block number, hash or tag.
+    Id(BlockId),
+    /// A number of blocks before the latest one.
+    BeforeLatest(u64),
+}
+
+impl CallBlock {
+    /// Resolves the block, looking up the latest block number if it is relative.
+    pub async fn resolve<P: Provider<AnyNetwork>>(self, provider: &P) -> Result<BlockId> {
+        match self {
+            Self::Id(id) => Ok(id),
+            Self::BeforeLatest(offset) => {
+                let latest = provider.get_block_number().await?;
+                let number = latest.checked_sub(offset).ok_or_else(|| {
+                    eyre::eyre!("Invalid block -{offset}: the latest block is only {latest}")
+                })?;
+                Ok(BlockId::number(number))
+            }
+        }
+    }
+}
+
+impl FromStr for CallBlock {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "genesis" {
+            return Ok(Self::Id(BlockId::number(0)));
+        }
+        if let Some(offset) = s.strip_prefix('-') {
+            let offset = offset.parse().map_err(|_| eyre::eyre!("Invalid relative block {s}"))?;
+            return Ok(Self::BeforeLatest(offset));
+        }
+        Ok(Self::Id(s.parse()?))
+    }
+}
+
+/// Applies state overrides to the executor's backend, so that locally executed calls observe the
+/// same state as `eth_call` with the overrides.
+fn apply_state_overrides(executor: &mut Executor, state_overrides: StateOverride) -> Result<()> {
+    for (address, account) in state_overrides {
+        if let Some(balance) = account.balance {
+            executor.set_balance(address, balance)?;
+        }
+        if let Some(nonce) = account.nonce {
+            executor.set_nonce(address, nonce)?;
+        }
+        if let Some(code) = account.code {
+            executor.set_code(address, Bytecode::new_raw(code))?;
+        }
+        if let Some(state) = account.state {
+            let storage =
+                state.into_iter().map(|(slot, value)| (slot.into(), value.into())).collect();
+            executor.set_storage(address, storage)?;
+        }
+        for (slot, value) in account.state_diff.into_iter().flatten() {
+            executor.set_storage_slot(address, slot.into(), value.into())?;
+        }
+    }
+    Ok(())
+}
+
+/// Configures the tracer of the executor to record the storage reads, see `--show-storage-reads`.
+fn enable_storage_reads(executor: &mut Executor) {
+    if let Some(tracer) = &mut executor.inspector_mut().tracer {
+        record_storage_reads(tracer.config_mut());
+    }
+}
+
+/// Configures the tracer of the executor to record all the steps, see `--gas-by-opcode`.
+fn enable_opcode_gas(executor: &mut Executor) {
+    if let Some(tracer) = &mut executor.inspector_mut().tracer {
+        let config = tracer.config_mut();
+        config.record_steps = true;
+        config.record_opcodes_filter = None;
+    }
+}
+
+/// Aborts the execution once the trace exceeds `max_bytes`, see `--max-trace-bytes`.
+fn limit_trace_size(executor: &mut Executor, max_bytes: usize) {
+    executor.inspector_mut().set_max_trace_bytes((max_bytes != 0).then_some(max_bytes));
+}
+
+/// Replaces the error of a call that reverted on the RPC with its decoded revert reason, e.g. the
+/// description of a `Panic` code, which not all nodes include in their error message.
+fn decode_call_error(err: eyre::Report, revert_decoder: &RevertDecoder) -> eyre::Report {
+    let data = err
+        .downcast_ref::<TransportError>()
+        .and_then(|err| err.as_error_resp())
+        .and_then(|payload| payload.as_revert_data());
+    match data {
+        Some(data) => {
+            eyre::eyre!("execution reverted: {}", CallRevertReason::decode(&data, revert_decoder))
+        }
+        None => err,
+    }
+}
+
+/// Formats the block a call is made at, which is the latest one if `None`.
+fn format_block_id(block: Option<BlockId>) -> String {
+    match block {
+        Some(BlockId::Number(BlockNumberOrTag::Number(number))) => number.to_string(),
+        Some(block) => block.to_string(),
+        None => BlockNumberOrTag::Latest.to_string(),
+    }
+}
+
+/// Parses the stubbed precompiles of `--override-precompile`, given as `address:returndata`.
+///
+/// Only the addresses of the precompiles can be stubbed, unless `force` is set.
+fn parse_precompile_overrides(overrides: &[String], force: bool) -> Result<AddressHashMap<Bytes>> {
+    overrides
+        .iter()
+        .map(|override_str| {
+            let (addr, output) = override_str.split_once(':').ok_or_else(|| {
+                eyre::eyre!(
+                    "Invalid precompile override `{override_str}`. Expected <address>:<returndata>"
+                )
+            })?;
+            let addr = Address::from_str(addr)
+                .wrap_err_with(|| format!("Invalid address in `{override_str}`"))?;
+            if !force && !PRECOMPILES.contains(&addr) {
+                eyre::bail!("{addr} is not a precompile, pass --force to stub it anyway");
+            }
+            let output = Bytes::from_str(output)
+                .wrap_err_with(|| format!("Invalid return data in `{override_str}`"))?;
+            Ok((addr, output))
+        })
+        .collect()
+}
+
+/// Parses the stubbed calls of `--call-override-return`, given as `address:selector:returndata`.
+///
+/// The selector is either hex or a function signature. Later overrides of the same address and
+/// selector replace the earlier ones.
+fn parse_call_return_overrides(
+    overrides: &[String],
+) -> Result<HashMap<(Address, Selector), Bytes>> {
+    overrides
+        .iter()
+        .map(|override_str| {
+            let [addr, selector, output] = override_str.splitn(3, ':').collect::<Vec<_>>()[..]
+            else {
+                eyre::bail!(
+                    "Invalid call override `{override_str}`. \
+                     Expected <address>:<selector>:<returndata>"
+                )
+            };
+            let addr = Address::from_str(addr)
+                .wrap_err_with(|| format!("Invalid address in `{override_str}`"))?;
+            let selector = if selector.starts_with("0x") {
+                Selector::from_str(selector)
+                    .wrap_err_with(|| format!("Invalid selector in `{override_str}`"))?
+            } else {
+                Function::parse(selector)
+                    .wrap_err_with(|| format!("Invalid function signature in `{override_str}`"))?
+                    .selector()
+            };
+            let output = Bytes::from_str(output)
+                .wrap_err_with(|| format!("Invalid return data in `{override_str}`"))?;
+            Ok(((addr, selector), output))
+        })
+        .collect()
+}
+
+/// Compiles the project to get its artifacts.
+fn compile_local_artifacts(config: &Config) -> Result<ContractsByArtifact> {
+    if !shell::is_json() {
+        sh_println!("Compiling project to generate artifacts")?;
+    }
+    let output = ProjectCompiler::new().compile(&config.project()?)?;
+    Ok(ContractsByArtifact::new(
+        output.artifact_ids().map(|(id, artifact)| (id, artifact.clone().into())),
+    ))
+}
+
+/// Returns the deployed code of the local artifact with the given name or identifier, for
+/// `--override-code <address>:@<name>`.
+fn artifact_deployed_code(
+    name: &str,
+    known_contracts: Option<&ContractsByArtifact>,
+) -> Result<Bytes> {
+    let known_contracts = known_contracts.ok_or_else(|| {
+        eyre::eyre!("Using an artifact in --override-code requires --with-local-artifacts")
+    })?;
+    let (id, contract) = known_contracts
+        .find_by_name_or_identifier(name)?
+        .ok_or_else(|| eyre::eyre!("No artifact found for {name} in --override-code"))?;
+    contract.deployed_bytecode().cloned().ok_or_else(|| {
+        eyre::eyre!(
+            "Artifact {} has no deployed code; it may be abstract or need linking",
+            id.identifier()
+        )
     })
 }
 
-/// Parse an override string in the format address:slot:value.
-fn address_slot_value_override(address_override: &str) -> Result<(Address, U256, U256)> {
-    let captures = OVERRIDE_PATTERN.captures(address_override).ok_or_else(|| {
-        eyre::eyre!("Invalid override {address_override}. Expected <address>:<slot>:<value>")
+/// Returns the signature of the function of `--sig-from`, given as `<contract>:<function>`,
+/// taking `num_args` arguments.
+///
+/// The function is looked up in the local artifact of the contract if `known_contracts` is set,
+/// and in all the `--abi` ABIs otherwise.
+fn signature_from_abi(
+    sig_from: &str,
+    num_args: usize,
+    known_contracts: Option<&ContractsByArtifact>,
+    abis: &[JsonAbi],
+) -> Result<String> {
+    let (contract, name) = sig_from.rsplit_once(':').ok_or_else(|| {
+        eyre::eyre!("Invalid --sig-from {sig_from}. Expected <contract>:<function>")
     })?;
+    let functions = if let Some(known_contracts) = known_contracts {
+        let (_, artifact) = known_contracts
+            .find_by_name_or_identifier(contract)?
+            .ok_or_else(|| eyre::eyre!("No artifact found for {contract} in --sig-from"))?;
+        artifact.abi.function(name).cloned().unwrap_or_default()
+    } else if !abis.is_empty() {
+        abis.iter().filter_map(|abi| abi.function(name)).flatten().cloned().collect()
+    } else {
+        eyre::bail!("--sig-from requires --with-local-artifacts or --abi");
+    };
+    if functions.is_empty() {
+        eyre::bail!("No function {name} found in the ABI of {contract}");
+    }
 
-    Ok((
-        captures[1].parse()?, // Address
-        captures[2].parse()?, // Slot (U256)
-        captures[3].parse()?, // Value (U256)
-    ))
+    let mut signatures = functions
+        .iter()
+        .filter(|function| function.inputs.len() == num_args)
+        .map(|function| {
+            if function.outputs.is_empty() {
+                function.signature()
+            } else {
+                function.signature_with_outputs()
+            }
+        })
+        .collect::<Vec<_>>();
+    signatures.sort();
+    signatures.dedup();
+    match &signatures[..] {
+        [signature] => Ok(signature.clone()),
+        [] => eyre::bail!(
+            "No function {name} of {contract} takes {num_args} arguments; found {}",
+            functions.iter().map(|function| function.signature()).collect::<Vec<_>>().join(", ")
+        ),
+        _ => eyre::bail!(
+            "Function {name} of {contract} is ambiguous with {num_args} arguments: {}",
+            signatures.join(", ")
+        ),
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use alloy_primitives::hex;
+/// Identifies the local artifact matching the code deployed at the given address, for
+/// `--identify-only`.
+async fn identify_artifact<P: Provider<AnyNetwork>>(
+    provider: &P,
+    config: &Config,
+    to: Option<&NameOrAddress>,
+    block: Option<BlockId>,
+) -> Result<()> {
+    let to = to.ok_or_else(|| eyre::eyre!("--identify-only requires a destination address"))?;
+    let address = to.resolve_at(provider, block).await?;
+    let code = provider.get_code_at(address).block_id(block.unwrap_or_default()).await?;
+    if code.is_empty() {
+        eyre::bail!("No code deployed at {address}");
+    }
 
-    #[test]
-    fn can_parse_call_data() {
-        let data = hex::encode("hello");
-        let args = CallArgs::parse_from(["foundry-cli", "--data", data.as_str()]);
-        assert_eq!(args.data, Some(data));
+    let known_contracts = compile_local_artifacts(config)?;
+    let (id, score) = identify_code(&known_contracts, address, &code)?;
 
-        let data = hex::encode_prefixed("hello");
-        let args = CallArgs::parse_from(["foundry-cli", "--data", data.as_str()]);
-        assert_eq!(args.data, Some(data));
+    if shell::is_json() {
+        let identity = serde_json::json!({
+            "artifact": id.identifier(),
+            "contract": id.name,
+            "score": score,
+        });
+        sh_println!("{}", serde_json::to_string_pretty(&identity)?)?;
+    } else {
+        sh_println!("Artifact: {}", id.identifier())?;
+        sh_println!("Contract: {}", id.name)?;
+        sh_println!("Score: {score}")?;
     }
+    Ok(())
+}
 
-    #[test]
-    fn can_parse_state_overrides() {
-        let args = CallArgs::parse_from([
-            "foundry-cli",
-            "--override-balance",
-            "0x123:0x1234",
-            "--override-nonce",
-            "0x123:1",
-            "--override-code",
-            "0x123:0x1234",
-            "--override-state",
-            "0x123:0x1:0x1234",
-        ]);
+/// Returns the local artifact matching the given code deployed at `address`, along with its
+/// bytecode diff score.
+fn identify_code<'a>(
+    known_contracts: &'a ContractsByArtifact,
+    address: Address,
+    code: &[u8],
+) -> Result<(&'a ArtifactId, f64)> {
+    let identifier = LocalTraceIdentifier::new(known_contracts);
+    let Some((id, _, score)) = identifier.identify_code_with_score(code, &[]) else {
+        eyre::bail!("No local artifact matches the code deployed at {address}");
+    };
+    Ok((id, score))
+}
 
-        assert_eq!(args.balance_overrides, Some(vec!["0x123:0x1234".to_string()]));
-        assert_eq!(args.nonce_overrides, Some(vec!["0x123:1".to_string()]));
-        assert_eq!(args.code_overrides, Some(vec!["0x123:0x1234".to_string()]));
-        assert_eq!(args.state_overrides, Some(vec!["0x123:0x1:0x1234".to_string()]));
+/// Executes the transactions of the pending block on top of the forked latest block, and moves the
+/// environment to the pending block.
+///
+/// Nodes that don't expose the pending block are warned about, and the state is left untouched.
+async fn apply_pending_transactions<P: Provider<AnyNetwork>>(
+    executor: &mut Executor,
+    provider: &P,
+) -> Result<()> {
+    let block = match provider.get_block(BlockId::pending()).full().await {
+        Ok(Some(block)) => block,
+        Ok(None) => {
+            sh_warn!("The RPC node does not expose the pending block, ignoring --include-pending")?;
+            return Ok(());
+        }
+        Err(err) => {
+            sh_warn!("Failed to fetch the pending block, ignoring --include-pending: {err}")?;
+            return Ok(());
+        }
+    };
+    let BlockTransactions::Full(txs) = &block.transactions else {
+        sh_warn!("The RPC node returned no pending transactions, ignoring --include-pending")?;
+        return Ok(());
+    };
+
+    let env = executor.env_mut();
+    env.block.number = U256::from(block.header.number);
+    env.block.timestamp = U256::from(block.header.timestamp);
+    env.block.coinbase = block.header.beneficiary;
+    if let Some(base_fee) = block.header.base_fee_per_gas {
+        env.block.basefee = U256::from(base_fee);
+    }
+
+    if !shell::is_json() {
+        sh_println!("Executing {} pending transactions.", txs.len())?;
+    }
+    let mut env =
+        EnvWithHandlerCfg::new_with_spec_id(Box::new(executor.env().clone()), executor.spec_id());
+    let mut skipped = 0;
+    for tx in txs {
+        configure_tx_env(&mut env, &tx.inner);
+
+        // Pending transactions may be invalid against the forked state, e.g. because of their
+        // nonce, in which case they are skipped.
+        let executed = if Transaction::to(tx).is_some() {
+            executor.transact_with_env(env.clone()).is_ok()
+        } else {
+            !matches!(executor.deploy_with_env(env.clone(), None), Err(EvmError::Eyre(_)))
+        };
+        if !executed {
+            trace!(tx=?tx.tx_hash(), "skipping invalid pending transaction");
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        sh_warn!("Skipped {skipped} pending transactions that could not be executed")?;
+    }
+
+    Ok(())
+}
+
+/// Executes the transactions of the block preceding the given index, which must be at most the
+/// number of transactions of the block, on top of the state of its parent block, and sets the
+/// block environment of the executor to the block.
+///
+/// Transactions that can't be executed are skipped, like for `--include-pending`.
+fn replay_block_transactions(
+    executor: &mut Executor,
+    block: &AnyRpcBlock,
+    index: usize,
+) -> Result<()> {
+    let number = block.header.number;
+    let BlockTransactions::Full(txs) = &block.transactions else {
+        eyre::bail!("The RPC node returned no transactions for block {number}")
+    };
+
+    let env = executor.env_mut();
+    env.block.number = U256::from(number);
+    env.block.timestamp = U256::from(block.header.timestamp);
+    env.block.coinbase = block.header.beneficiary;
+    env.block.difficulty = block.header.difficulty;
+    env.block.prevrandao = Some(block.header.mix_hash.unwrap_or_default());
+    if let Some(base_fee) = block.header.base_fee_per_gas {
+        env.block.basefee = U256::from(base_fee);
+    }
+    apply_chain_and_block_specific_env_changes::<AnyNetwork>(env, block);
+
+    let mut env =
+        EnvWithHandlerCfg::new_with_spec_id(Box::new(executor.env().clone()), executor.spec_id());
+    let mut skipped = 0;
+    for tx in &txs[..index] {
+        if is_known_system_sender(tx.from()) ||
+            tx.transaction_type() == Some(SYSTEM_TRANSACTION_TYPE)
+        {
+            skipped += 1;
+            continue;
+        }
+        configure_tx_env(&mut env, &tx.inner);
+
+        // Reverted transactions are replayed as well, as their gas is still paid.
+        let executed = if Transaction::to(tx).is_some() {
+            executor.transact_with_env(env.clone()).is_ok()
+        } else {
+            !matches!(executor.deploy_with_env(env.clone(), None), Err(EvmError::Eyre(_)))
+        };
+        if !executed {
+            trace!(tx=?tx.tx_hash(), "skipping invalid block transaction");
+            skipped += 1;
+        }
+    }
+
+    if !shell::is_json() {
+        sh_println!("Replayed {} of {index} transactions of block {number}", index - skipped)?;
+    }
+    if skipped > 0 {
+        sh_warn!("Skipped {skipped} transactions that could not be executed")?;
+    }
+    Ok(())
+}
+
+impl CallArgs {
+    /// Identifies the local artifact matching the code deployed at the destination, for
+    /// `--identify-only`.
+    async fn run_identify_only(&self, ctx: &CallContext) -> Result<()> {
+        let to = self.to.as_deref().map(NameOrAddress::from_str).transpose()?;
+        ensure_offline_destination(&ctx.config, to.as_ref())?;
+        identify_artifact(&ctx.provider, &ctx.config, to.as_ref(), ctx.block).await
+    }
+
+    /// Compiles the project if the artifacts are needed to build the call or its overrides.
+    fn known_contracts(&self, config: &Config) -> Result<Option<ContractsByArtifact>> {
+        if !self.has_artifact_code_overrides() &&
+            (self.sig_from.is_none() || !self.with_local_artifacts)
+        {
+            return Ok(None);
+        }
+        if !self.with_local_artifacts {
+            eyre::bail!("Using an artifact in --override-code requires --with-local-artifacts");
+        }
+        Ok(Some(compile_local_artifacts(config)?))
+    }
+
+    /// Builds the state overrides of the call, resolving the balance deltas and `auto` nonces
+    /// against the state at the block of the call.
+    ///
+    /// With `--fork-state-file`, they are resolved against the state of the file instead, so that
+    /// nothing is fetched from the RPC.
+    async fn resolve_state_overrides(
+        &self,
+        ctx: &CallContext,
+        known_contracts: Option<&ContractsByArtifact>,
+    ) -> Result<StateOverride> {
+        let storage_dumps = self.load_storage_dumps()?;
+        let (mut current_balances, mut current_nonces) = match &ctx.fork_state {
+            Some(fork_state) => {
+                let balances = self.delta_balance_accounts()?;
+                let nonces = self.auto_nonce_accounts()?;
+                (
+                    balances.into_iter().map(|addr| (addr, fork_state.balance(addr))).collect(),
+                    nonces.into_iter().map(|addr| (addr, fork_state.nonce(addr))).collect(),
+                )
+            }
+            None => (
+                self.fetch_delta_balances(&ctx.provider, ctx.block).await?,
+                self.fetch_auto_nonces(&ctx.provider, ctx.block).await?,
+            ),
+        };
+        // The balances and nonces left by previous calls are the current ones.
+        for (addr, account) in &storage_dumps.state {
+            if let (Some(balance), Some(current)) =
+                (account.balance, current_balances.get_mut(addr))
+            {
+                *current = balance;
+            }
+            if let (Some(nonce), Some(current)) = (account.nonce, current_nonces.get_mut(addr)) {
+                *current = nonce;
+            }
+        }
+        self.get_state_overrides(storage_dumps, &current_balances, &current_nonces, known_contracts)
+    }
+
+    /// Returns the destination, signature and arguments of the call, along with the destinations
+    /// of `--to-list` and the calldata of a batch of calls.
+    fn call_input(
+        &self,
+        config: &Config,
+        known_contracts: Option<&ContractsByArtifact>,
+        abis: &[JsonAbi],
+    ) -> Result<CallInput> {
+        let mut sig = self.sig.clone();
+        let mut args = self.args.clone();
+
+        // The function called against each destination of `--to-list` is given with `--sig`, and
+        // the first destination is used to build the transaction.
+        let targets = self.to_list.as_deref().map(load_to_list).transpose()?;
+        let to = if let Some(targets) = &targets {
+            if self.command.is_some() {
+                eyre::bail!("--to-list cannot be combined with `--create`");
+            }
+            if let Some((to_list_sig, to_list_args)) = self.to_list_sig.split_first() {
+                sig = Some(to_list_sig.clone());
+                args = to_list_args.to_vec();
+            } else if self.data.is_empty() {
+                eyre::bail!("--to-list requires the function to call, given with --sig or --data");
+            }
+            Some(targets[0].into())
+        } else {
+            self.to.as_deref().map(NameOrAddress::from_str).transpose()?
+        };
+        ensure_offline_destination(config, to.as_ref())?;
+
+        // Multiple calls are executed one after the other on the same executor, using the first
+        // one to build the transaction.
+        let batch = load_batch_calldata(&self.data, self.batch_file.as_deref())?;
+        if let Some(batch) = &batch {
+            if targets.is_some() {
+                eyre::bail!("Multiple calls cannot be combined with --to-list");
+            }
+            if !self.trace {
+                eyre::bail!("Multiple calls can only be executed with `--trace`");
+            }
+            if self.command.is_some() {
+                eyre::bail!("Multiple calls cannot be combined with `--create`");
+            }
+            sig = batch.first().map(hex::encode_prefixed);
+        } else if let Some(data) = self.data.first() {
+            sig = Some(format!("0x{}", normalize_calldata(data)?));
+        } else if let Some(sig_from) = &self.sig_from {
+            // The positional signature is the first argument of the function.
+            args = sig.take().into_iter().chain(args).collect();
+            sig = Some(signature_from_abi(sig_from, args.len(), known_contracts, abis)?);
+        }
+
+        Ok(CallInput { to, sig, args, code: None, targets, batch })
+    }
+
+    /// Uses the transaction of `--from-tx` as the basis of the call, so that state overrides can
+    /// be layered on top of it.
+    ///
+    /// Its recipient, calldata, value and sender are used unless they are explicitly provided.
+    async fn replay_transaction(
+        &self,
+        provider: &RetryProvider,
+        input: &mut CallInput,
+        tx: &mut TransactionOpts,
+        sender: &mut SenderKind<'_>,
+    ) -> Result<()> {
+        let Some(tx_hash) = self.from_tx else { return Ok(()) };
+        let replayed = provider
+            .get_transaction_by_hash(tx_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("tx not found: {tx_hash:?}"))?;
+        let calldata = hex::encode_prefixed(Transaction::input(&replayed));
+        if let Some(replayed_to) = Transaction::to(&replayed) {
+            input.to = input.to.take().or(Some(replayed_to.into()));
+            input.sig = Some(calldata);
+        } else {
+            input.code = Some(calldata);
+        }
+        tx.value = tx.value.or(Some(Transaction::value(&replayed)));
+        if self.eth.wallet.from.is_none() && self.impersonate.is_none() {
+            *sender = replayed.from().into();
+        }
+        Ok(())
+    }
+
+    /// Funds the sender with `--fund-sender`, and simulates the delegation of an EIP-7702
+    /// authorization to an address if it can't be signed.
+    fn apply_sender_overrides(
+        &self,
+        state_overrides: &mut StateOverride,
+        tx: &mut TransactionOpts,
+        sender: &SenderKind<'_>,
+    ) -> Result<()> {
+        let from = sender.address();
+        if let Some(amount) = self.fund_sender {
+            // The balance of a dumped state is replaced, unlike an explicit override.
+            let overridden = self.balance_overrides.iter().flatten().any(|override_str| {
+                address_value_override(override_str)
+                    .is_ok_and(|(addr, _)| addr.parse::<Address>().is_ok_and(|addr| addr == from))
+            });
+            if overridden {
+                eyre::bail!("Cannot use both --fund-sender and --override-balance for {from}");
+            }
+            sh_eprintln!("Funding the sender {from} with {amount} wei")?;
+            state_overrides.entry(from).or_default().balance = Some(amount);
+        }
+
+        // An authorization delegating to an address can't be signed without a signer, so the
+        // delegation of the sender is simulated by overriding its code instead.
+        if let Some(CliAuthorizationList::Address(delegate)) = tx.auth {
+            if sender.as_signer().is_none() {
+                tx.auth = None;
+                ensure_no_code_override(state_overrides, from)?;
+                state_overrides.entry(from).or_default().code =
+                    Some(Bytecode::new_eip7702(delegate).original_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the transaction of the call along with the state it is executed against, shared by
+    /// all the ways of executing it.
+    async fn prepare_call(
+        &self,
+        ctx: &mut CallContext,
+        mut state_overrides: StateOverride,
+        known_contracts: Option<&ContractsByArtifact>,
+    ) -> Result<PreparedCall> {
+        if self.include_pending &&
+            !matches!(
+                ctx.block,
+                None | Some(BlockId::Number(BlockNumberOrTag::Latest | BlockNumberOrTag::Pending))
+            )
+        {
+            eyre::bail!("--include-pending can only be used at the latest or pending block");
+        }
+
+        let abis = self.abis.iter().map(|abi| load_abi(abi)).collect::<Result<Vec<_>>>()?;
+        let revert_decoder = RevertDecoder::new().with_abis(&abis);
+        let mut input = self.call_input(&ctx.config, known_contracts, &abis)?;
+
+        // `--access-list` without a value requests generating the access list. It is computed
+        // locally against a fork instead of via `eth_createAccessList` so that the state overrides
+        // are taken into account.
+        let mut tx = self.tx.clone();
+        let generate_access_list = matches!(tx.access_list, Some(None));
+        if generate_access_list {
+            tx.access_list = None;
+        }
+
+        let mut sender = match self.impersonate {
+            Some(address) => address.into(),
+            None => SenderKind::from_wallet_opts(self.eth.wallet.clone()).await?,
+        };
+        self.replay_transaction(&ctx.provider, &mut input, &mut tx, &mut sender).await?;
+        let from = sender.address();
+
+        // The chain and the nonce of the sender are otherwise fetched from the RPC.
+        if let Some(fork_state) = &ctx.fork_state {
+            ctx.config.chain = Some(fork_state.chain_id(&ctx.evm_opts).into());
+            tx.nonce = tx.nonce.or_else(|| Some(U64::from(fork_state.nonce(from))));
+        }
+        self.apply_sender_overrides(&mut state_overrides, &mut tx, &sender)?;
+
+        let mut create2 = None;
+        if let Some(CallSubcommands::Create { code, sig, args, value, salt, deployer }) =
+            &self.command
+        {
+            input.sig = sig.clone();
+            input.args = args.clone();
+            input.code = Some(code.clone());
+            if let Some(value) = value {
+                tx.value = Some(*value);
+            }
+            create2 = salt.map(|salt| (salt, deployer.unwrap_or(ctx.evm_opts.create2_deployer)));
+        }
+
+        let CallInput { to, sig, args, code, targets, batch } = input;
+        let ens_name = match &to {
+            Some(NameOrAddress::Name(name)) => Some(name.clone()),
+            _ => None,
+        };
+        let builder = CastTxBuilder::new(&ctx.provider, tx, &ctx.config)
+            .await?
+            .with_to(to, ctx.block)
+            .await?;
+        let builder = if let Some(args_json) = &self.args_json {
+            if !args.is_empty() {
+                eyre::bail!("Cannot use both positional arguments and --args-json");
+            }
+            builder.with_code_sig_and_json_args(code, sig, args_json).await?
+        } else {
+            builder.with_code_sig_and_args(code, sig, args).await?
+        };
+        let (mut tx, func) = builder.build_raw(sender).await?;
+        if !self.blob_versioned_hashes.is_empty() {
+            tx.blob_versioned_hashes = Some(self.blob_versioned_hashes.clone());
+        }
+
+        // Resolving to the wrong contract would go unnoticed otherwise.
+        if let (Some(name), Some(TxKind::Call(address))) = (ens_name, tx.inner.to) {
+            sh_eprintln!("Resolved {name} to {address} at block {}", format_block_id(ctx.block))?;
+        }
+
+        for auth in tx.authorization_list.iter().flatten() {
+            if let Ok(authority) = auth.recover_authority() {
+                ensure_no_code_override(&state_overrides, authority)?;
+            }
+        }
+
+        // Route the creation through the CREATE2 deployer, which expects the salt followed by the
+        // init code as calldata.
+        if let Some((salt, deployer)) = create2 {
+            let init_code = tx.inner.input.input().cloned().unwrap_or_default();
+            let address = deployer.create2_from_code(salt, &init_code);
+            if !shell::is_json() {
+                sh_println!("Predicted CREATE2 address: {address}")?;
+            }
+            tx.set_to(deployer);
+            tx.set_input([salt.as_slice(), &init_code].concat());
+        }
+
+        Ok(PreparedCall {
+            tx,
+            func,
+            from,
+            state_overrides,
+            block_overrides: self.block_overrides(),
+            batch,
+            targets,
+            generate_access_list,
+            abis,
+            revert_decoder,
+        })
+    }
+
+    /// Returns the overrides of the block context of `--block-timestamp` and
+    /// `--block-number-override`, if any.
+    fn block_overrides(&self) -> Option<BlockOverrides> {
+        (self.block_timestamp.is_some() || self.block_number_override.is_some()).then(|| {
+            BlockOverrides {
+                number: self.block_number_override.map(U256::from),
+                time: self.block_timestamp,
+                ..Default::default()
+            }
+        })
+    }
+
+    /// Returns the options of the executor of the calls executed locally.
+    fn local_executor_opts(
+        &self,
+        ctx: &CallContext,
+        call: &PreparedCall,
+    ) -> Result<LocalExecutorOpts> {
+        Ok(LocalExecutorOpts {
+            block: ctx.block,
+            tx_index: self.tx_index,
+            include_pending: self.include_pending,
+            evm_version: self.evm_version,
+            debug: self.debug,
+            decode_internal: self.decode_internal,
+            gas_by_opcode: self.gas_by_opcode,
+            show_storage_reads: self.show_storage_reads,
+            max_trace_bytes: self.max_trace_bytes,
+            state_overrides: call.state_overrides.clone(),
+            precompile_overrides: parse_precompile_overrides(
+                &self.precompile_overrides,
+                self.force,
+            )?,
+            call_return_overrides: parse_call_return_overrides(&self.call_return_overrides)?,
+            block_timestamp: self.block_timestamp,
+            block_number_override: self.block_number_override,
+            blob_base_fee: self.blob_base_fee,
+        })
+    }
+
+    /// Executes the calls locally with `--trace`, returning their traces for the caller to
+    /// render.
+    fn run_trace(
+        &self,
+        mut executor: TracingExecutor,
+        ctx: CallContext,
+        call: PreparedCall,
+        chain: Option<Chain>,
+        block_gas_limit: u64,
+    ) -> Result<Option<TracedCalls>> {
+        let PreparedCall { tx, from, batch, targets, abis, .. } = call;
+        let calls = match (batch, &targets) {
+            (Some(batch), _) => {
+                let Some(TxKind::Call(to)) = tx.inner.to else {
+                    eyre::bail!("Multiple calls require a destination address");
+                };
+                Some(batch.into_iter().map(|input| (to, input)).collect())
+            }
+            (None, Some(targets)) => {
+                let input = tx.inner.input.input().cloned().unwrap_or_default();
+                Some(targets.iter().map(|target| (*target, input.clone())).collect())
+            }
+            (None, None) => None,
+        };
+        let batch = calls.is_some();
+        let opts = TraceCallOpts {
+            trace_opcodes: self.trace_opcodes,
+            prestate: self.prestate,
+            state_dump: self.state_dump.as_deref(),
+            continue_state: self.continue_state.as_deref(),
+        };
+        let Some(traces) = trace_calls(&mut executor, &tx, from, block_gas_limit, calls, opts)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(TracedCalls {
+            traces,
+            batch,
+            targets,
+            config: ctx.config,
+            chain,
+            block: ctx.block,
+            abis,
+        }))
+    }
+
+    /// Returns the call to execute over RPC, decoded with the forced output types if any.
+    fn rpc_call<'a>(&'a self, call: &'a PreparedCall) -> RpcCall<'a> {
+        // Forced output types take precedence over the ones of the function signature, and the
+        // output is printed as is without them.
+        let func = if self.output_abi.is_some() || self.raw { None } else { call.func.as_ref() };
+        RpcCall {
+            tx: &call.tx,
+            func,
+            state_overrides: &call.state_overrides,
+            block_overrides: call.block_overrides.as_ref(),
+            output_abi: self.output_abi.as_ref(),
+            separator: if self.tsv { "\t" } else { "\n" },
+        }
+    }
+
+    /// Executes the call on each of the `--to-list` targets, reporting the failures along with
+    /// the outputs.
+    async fn run_to_list(
+        &self,
+        call: RpcCall<'_>,
+        ctx: &CallContext,
+        targets: &[Address],
+    ) -> Result<()> {
+        let cast = Cast::new(ctx.provider.clone()).with_tsv_output(self.tsv);
+        let mut results = serde_json::Map::new();
+        let mut failures = 0;
+        for target in targets {
+            let mut tx = call.tx.clone();
+            tx.set_to(*target);
+            let output = RpcCall { tx: &tx, ..call }.output(&cast, ctx.block).await;
+            let result = match output {
+                Ok(output) => {
+                    if !shell::is_json() {
+                        sh_println!("{target}: {output}")?;
+                    }
+                    serde_json::json!({ "output": output })
+                }
+                Err(err) => {
+                    failures += 1;
+                    if !shell::is_json() {
+                        sh_err!("{target}: {err}")?;
+                    }
+                    serde_json::json!({ "error": err.to_string() })
+                }
+            };
+            results.insert(target.to_string(), result);
+        }
+        if shell::is_json() {
+            sh_println!("{}", serde_json::to_string_pretty(&results)?)?;
+        }
+        if failures > 0 {
+            eyre::bail!("{failures} of {} calls failed", targets.len());
+        }
+        Ok(())
+    }
+
+    /// Executes the call on the primary node and on the one of `--diff-against` at the same block,
+    /// failing if the outcomes differ.
+    async fn run_diff(
+        &self,
+        call: RpcCall<'_>,
+        ctx: &CallContext,
+        url: &str,
+        revert_decoder: &RevertDecoder,
+    ) -> Result<()> {
+        let block = match ctx.block {
+            Some(block) => block,
+            None => BlockId::number(ctx.provider.get_block_number().await?),
+        };
+        let primary_url = ctx.config.get_rpc_url_or_localhost_http()?.into_owned();
+        let mut secondary_config = ctx.config.clone();
+        secondary_config.eth_rpc_url = Some(url.to_string());
+        let primary = Cast::new(ctx.provider.clone())
+            .with_pretty_output(self.pretty)
+            .with_tsv_output(self.tsv);
+        let secondary = Cast::new(utils::get_provider(&secondary_config)?)
+            .with_pretty_output(self.pretty)
+            .with_tsv_output(self.tsv);
+
+        let primary_outcome = call.outcome(&primary, block, revert_decoder).await?;
+        let secondary_outcome = call.outcome(&secondary, block, revert_decoder).await?;
+        let matches = primary_outcome == secondary_outcome;
+        if shell::is_json() {
+            let result = serde_json::json!({
+                "matches": matches,
+                "primary": primary_outcome,
+                "secondary": secondary_outcome,
+            });
+            sh_println!("{}", serde_json::to_string_pretty(&result)?)?;
+        } else if matches {
+            sh_println!("{primary_outcome}")?;
+        } else {
+            sh_println!("- {primary_url}: {primary_outcome}")?;
+            sh_println!("+ {url}: {secondary_outcome}")?;
+        }
+        if !matches {
+            eyre::bail!("The outcome of the call differs between the two nodes");
+        }
+        Ok(())
+    }
+
+    /// Executes the call on each new block with `--watch`, printing the output with the timestamp
+    /// of the block whenever it changes, failures included.
+    async fn run_watch(&self, call: RpcCall<'_>, ctx: &CallContext) -> Result<()> {
+        let mut blocks = new_blocks(&ctx.config, &ctx.provider, self.watch_interval).await?;
+        let cast = Cast::new(ctx.provider.clone()).with_tsv_output(self.tsv);
+        let mut last_output = None;
+        while let Some((number, timestamp)) = blocks.next().await.transpose()? {
+            let output = call
+                .output(&cast, Some(BlockId::number(number)))
+                .await
+                .unwrap_or_else(|err| format!("Error: {err}"));
+            if last_output.as_ref() != Some(&output) {
+                let time = DateTime::from_timestamp(timestamp as i64, 0)
+                    .map_or_else(|| timestamp.to_string(), |time| time.to_rfc3339());
+                sh_println!("[{time}] {number}: {output}")?;
+                last_output = Some(output);
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the call at each block of the `--block-range`, only printing the blocks at which
+    /// the output changes.
+    async fn run_range(
+        &self,
+        call: RpcCall<'_>,
+        ctx: &CallContext,
+        block_range: &BlockRange,
+    ) -> Result<()> {
+        // The outputs at each block are printed on a single line.
+        let cast = Cast::new(ctx.provider.clone()).with_tsv_output(self.tsv);
+        let mut last_output = None;
+        for block_number in block_range.blocks() {
+            let output = call.output(&cast, Some(BlockId::number(block_number))).await?;
+            if last_output.as_ref() != Some(&output) {
+                sh_println!("{block_number}: {output}")?;
+                last_output = Some(output);
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the call over RPC and prints its output, followed by its gas with `--estimate`.
+    async fn run_plain(
+        &self,
+        call: RpcCall<'_>,
+        ctx: &CallContext,
+        revert_decoder: RevertDecoder,
+    ) -> Result<()> {
+        let cast = Cast::new(ctx.provider.clone())
+            .with_pretty_output(self.pretty)
+            .with_tsv_output(self.tsv);
+        let (output, gas) = if shell::is_json() || self.revert_on_failure || !self.abis.is_empty() {
+            let Some(outcome) = self.call_outcome(call, &cast, ctx, revert_decoder).await? else {
+                return Ok(());
+            };
+            outcome
+        } else if self.estimate {
+            let (output, gas) = cast
+                .call_with_gas(call.tx, call.func, ctx.block, call.state_overrides.clone())
+                .await
+                .map_err(|err| decode_call_error(err, &revert_decoder))?;
+            (output, Some(gas))
+        } else {
+            let output = cast
+                .call_with_overrides(
+                    call.tx,
+                    call.func,
+                    ctx.block,
+                    call.state_overrides.clone(),
+                    call.block_overrides.cloned(),
+                )
+                .await
+                .map_err(|err| decode_call_error(err, &revert_decoder))?;
+            (output, None)
+        };
+        sh_println!("{}", call.decode(output)?)?;
+        if let Some(gas) = gas {
+            sh_println!("{gas}")?;
+        }
+        Ok(())
+    }
+
+    /// Executes the call over RPC as a structured result, so that reverts can be told apart from
+    /// other failures, returning its output and gas.
+    ///
+    /// In JSON mode the outcome is printed as is, and `None` is returned.
+    async fn call_outcome(
+        &self,
+        call: RpcCall<'_>,
+        cast: &Cast<RetryProvider>,
+        ctx: &CallContext,
+        mut revert_decoder: RevertDecoder,
+    ) -> Result<Option<(String, Option<u64>)>> {
+        let result = cast
+            .call_result(
+                call.tx,
+                ctx.block,
+                call.state_overrides.clone(),
+                call.block_overrides.cloned(),
+                &revert_decoder,
+            )
+            .await?;
+        let (data, mut reason) = match result {
+            CallResult::Success { output } => {
+                let gas = if self.estimate {
+                    Some(cast.estimate_gas(call.tx, ctx.block, call.state_overrides.clone()).await?)
+                } else {
+                    None
+                };
+                if shell::is_json() {
+                    let decoded = match (call.output_abi, call.func) {
+                        (Some(ty), _) => decode_output_values(&output, ty),
+                        (None, Some(func)) => {
+                            Some(cast.decode_call_output(call.tx, func, ctx.block, &output).await?)
+                        }
+                        (None, None) => None,
+                    };
+                    let decoded =
+                        decoded.map(|values| values.iter().map(format_token_raw).collect());
+                    let result = JsonCallOutput::success(output, decoded, gas);
+                    sh_println!("{}", serde_json::to_string_pretty(&result)?)?;
+                    return Ok(None);
+                }
+                let output = cast.format_call_output(call.tx, call.func, ctx.block, output).await?;
+                return Ok(Some((output, gas)));
+            }
+            CallResult::Revert { data, reason } => (data, reason),
+        };
+
+        // Look up the signature of unknown custom errors.
+        if let CallRevertReason::Custom { selector, signature: None, .. } = reason {
+            let identifier = SignaturesIdentifier::from_config(&ctx.config)?;
+            if let Some(error) = identifier.identify_error(selector).await {
+                revert_decoder.push_error(error);
+                reason = CallRevertReason::decode(&data, &revert_decoder);
+            }
+        }
+        if shell::is_json() {
+            let result = JsonCallOutput::revert(data, reason.clone());
+            sh_println!("{}", serde_json::to_string_pretty(&result)?)?;
+        }
+        if self.revert_on_failure {
+            return Err(CallRevertedError { reason: reason.to_string() }.into());
+        }
+        // The revert is part of the JSON output, which is not an error.
+        if shell::is_json() {
+            return Ok(None);
+        }
+        eyre::bail!("execution reverted: {reason}");
+    }
+
+    /// Executes a bundle of signed transactions in order on a fork, printing the outcome of each
+    /// transaction and their combined trace.
+    async fn run_bundle(
+        self,
+        path: &Path,
+        ctx: CallContext,
+        state_overrides: StateOverride,
+    ) -> Result<()> {
+        let CallContext { mut config, evm_opts, block, .. } = ctx;
+        let txs = load_bundle(path)?;
+        let abis = self.abis.iter().map(|abi| load_abi(abi)).collect::<Result<Vec<_>>>()?;
+        let revert_decoder = RevertDecoder::new().with_abis(&abis);
+
+        if let Some(BlockId::Number(BlockNumberOrTag::Number(block_number))) = block {
+            config.fork_block_number = Some(block_number);
+        }
+        let create2_deployer = evm_opts.create2_deployer;
+        let (mut env, fork, chain, odyssey) =
+            TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        env.cfg.disable_block_gas_limit = true;
+        env.block.gas_limit = U256::MAX;
+
+        let trace_mode = TraceMode::Call
+            .with_debug(self.debug)
+            .with_decode_internal(self.decode_internal.unwrap_or_default())
+            .with_state_changes(shell::verbosity() > 4);
+        let mut executor = TracingExecutor::new(
+            env,
+            fork,
+            self.evm_version,
+            trace_mode,
+            odyssey,
+            create2_deployer,
+        )?;
+        if self.gas_by_opcode {
+            enable_opcode_gas(&mut executor);
+        }
+        if self.show_storage_reads {
+            enable_storage_reads(&mut executor);
+        }
+        limit_trace_size(&mut executor, self.max_trace_bytes);
+        apply_state_overrides(&mut executor, state_overrides)?;
+        executor.inspector_mut().set_precompile_overrides(parse_precompile_overrides(
+            &self.precompile_overrides,
+            self.force,
+        )?);
+        executor
+            .inspector_mut()
+            .set_call_return_overrides(parse_call_return_overrides(&self.call_return_overrides)?);
+        if let Some(timestamp) = self.block_timestamp {
+            executor.env_mut().block.timestamp = U256::from(timestamp);
+        }
+        if let Some(number) = self.block_number_override {
+            executor.env_mut().block.number = U256::from(number);
+        }
+
+        let mut env = EnvWithHandlerCfg::new_with_spec_id(
+            Box::new(executor.env().clone()),
+            executor.spec_id(),
+        );
+        let mut traces = Vec::with_capacity(txs.len());
+        let mut gas_used = 0;
+        let mut first_revert = None;
+        for (i, (from, tx)) in txs.into_iter().enumerate() {
+            let hash = *tx.tx_hash();
+            let tx = TransactionRequest::from_transaction_with_sender(tx, from);
+            configure_tx_req_env(&mut env, &tx, None)?;
+
+            // Each transaction is committed, so that the next ones see its state changes.
+            let trace = if env.tx.transact_to.is_create() {
+                TraceResult::try_from(executor.deploy_with_env(env.clone(), None))
+                    .map_err(eyre::Report::from)
+            } else {
+                executor
+                    .transact_with_env(env.clone())
+                    .map(|result| TraceResult::from_raw(result, TraceKind::Execution))
+            }
+            .wrap_err_with(|| format!("transaction #{} ({hash}) could not be executed", i + 1))?;
+
+            let revert_reason = trace_revert_reason(&trace, &revert_decoder);
+            if !shell::is_json() {
+                let outcome = match &revert_reason {
+                    None => format!("success, gas used: {}", trace.gas_used),
+                    Some(reason) => format!("reverted: {reason}, gas used: {}", trace.gas_used),
+                };
+                sh_println!("Transaction #{} {hash} from {from}: {outcome}", i + 1)?;
+            }
+            if first_revert.is_none() {
+                first_revert =
+                    revert_reason.map(|reason| format!("transaction #{}: {reason}", i + 1));
+            }
+            gas_used += trace.gas_used;
+            traces.extend(trace.traces.into_iter().flatten());
+        }
+        if !shell::is_json() {
+            sh_println!()?;
+        }
+        let result = TraceResult {
+            success: first_revert.is_none(),
+            traces: Some(traces),
+            gas_used,
+            out_of_gas: false,
+            gas_limit: None,
+        };
+        let options = TraceOptions {
+            labels: self.labels,
+            with_local_artifacts: self.with_local_artifacts,
+            skip_identification: self.quick,
+            with_etherscan: !self.no_etherscan,
+            ens_names: self.ens_names,
+            block,
+            abis: &abis,
+            debug: self.debug,
+            decode_internal: self.decode_internal.unwrap_or_default(),
+            storage_reads: self.show_storage_reads,
+            trace_format: self.trace_format,
+            gas_report: self.gas_report,
+            gas_report_sort: self.sort_by,
+            gas_report_top: self.top,
+            gas_by_opcode: self.gas_by_opcode,
+            depth_limit: self.trace_stack_depth_limit,
+            trace_filter: &self.trace_filter,
+            decode_events: self.decode_events,
+            inspect_selectors: self.inspect_selectors,
+            identify_creation: self.identify_creation,
+            explain: self.explain,
+        };
+        handle_traces(result, &config, chain, options).await?;
+
+        if let Some(reason) = first_revert.filter(|_| self.revert_on_failure) {
+            return Err(CallRevertedError { reason }.into());
+        }
+        Ok(())
+    }
+}
+
+/// Builds the environment of a traced transaction whose gas price or fees are set, so that they are
+/// reflected in `tx.gasprice` and charged to the sender.
+///
+/// Returns `None` if no fees are set, in which case the transaction is executed with a zero gas
+/// price and base fee, as with `eth_call`.
+fn tx_env_with_fees(
+    executor: &Executor,
+    tx: &TransactionRequest,
+    gas_limit: u64,
+) -> Result<Option<EnvWithHandlerCfg>> {
+    if tx.gas_price.is_none() &&
+        tx.max_fee_per_gas.is_none() &&
+        tx.max_priority_fee_per_gas.is_none()
+    {
+        return Ok(None);
+    }
+    let mut env =
+        EnvWithHandlerCfg::new_with_spec_id(Box::new(executor.env().clone()), executor.spec_id());
+    let tx = TransactionRequest { gas: Some(gas_limit), ..tx.clone() };
+    configure_tx_req_env(&mut env, &tx, None)?;
+    Ok(Some(env))
+}
+
+/// Loads a bundle of raw signed transactions from a JSON array, along with their recovered
+/// senders.
+fn load_bundle(path: &Path) -> Result<Vec<(Address, TxEnvelope)>> {
+    let raw_txs: Vec<String> = fs::read_json_file(path)?;
+    if raw_txs.is_empty() {
+        eyre::bail!("no transactions found in bundle {}", path.display());
+    }
+    raw_txs
+        .iter()
+        .enumerate()
+        .map(|(i, raw_tx)| {
+            let tx = SimpleCast::decode_raw_transaction(raw_tx)
+                .wrap_err_with(|| format!("invalid transaction #{} in bundle", i + 1))?;
+            let from = tx.recover_signer().wrap_err_with(|| {
+                format!("could not recover the sender of transaction #{} in bundle", i + 1)
+            })?;
+            Ok((from, tx))
+        })
+        .collect()
+}
+
+/// Ensures that the code of an account delegated with an EIP-7702 authorization is not overridden.
+///
+/// The authorization sets the code of the account, so it would conflict with `--override-code`.
+fn ensure_no_code_override(state_overrides: &StateOverride, authority: Address) -> Result<()> {
+    if state_overrides.get(&authority).is_some_and(|account| account.code.is_some()) {
+        eyre::bail!(
+            "Cannot use both --auth and --override-code for {authority}: \
+             the authorization sets the code of the account"
+        );
+    }
+    Ok(())
+}
+
+/// The configuration and RPC connection a call is executed with.
+struct CallContext {
+    config: Config,
+    evm_opts: EvmOpts,
+    provider: RetryProvider,
+    /// The block the call is executed at, the latest one if `None`.
+    block: Option<BlockId>,
+    /// The state of `--fork-state-file`, used instead of forking the RPC.
+    fork_state: Option<ForkState>,
+}
+
+impl CallContext {
+    /// Returns the chain of the call, from the fork state, the config or the RPC.
+    async fn chain(&self) -> Result<Chain> {
+        Ok(match (&self.fork_state, self.config.chain) {
+            (Some(fork_state), _) => fork_state.chain_id(&self.evm_opts).into(),
+            (None, Some(chain)) => chain,
+            (None, None) => self.provider.get_chain_id().await?.into(),
+        })
+    }
+}
+
+/// The destination, signature and arguments of a call, before its transaction is built.
+struct CallInput {
+    to: Option<NameOrAddress>,
+    sig: Option<String>,
+    args: Vec<String>,
+    /// The code of a contract creation.
+    code: Option<String>,
+    /// The destinations of `--to-list`.
+    targets: Option<Vec<Address>>,
+    /// The calldata of multiple calls executed one after the other.
+    batch: Option<Vec<Bytes>>,
+}
+
+/// A call built from the arguments, ready to be executed over RPC or locally.
+struct PreparedCall {
+    tx: WithOtherFields<TransactionRequest>,
+    func: Option<Function>,
+    from: Address,
+    state_overrides: StateOverride,
+    block_overrides: Option<BlockOverrides>,
+    /// The calldata of multiple calls executed one after the other.
+    batch: Option<Vec<Bytes>>,
+    /// The destinations of `--to-list`.
+    targets: Option<Vec<Address>>,
+    /// Whether the access list of the call is generated, see `--access-list`.
+    generate_access_list: bool,
+    abis: Vec<JsonAbi>,
+    revert_decoder: RevertDecoder,
+}
+
+/// The options of the executor of the calls executed locally with `--trace` or `--access-list`.
+struct LocalExecutorOpts {
+    block: Option<BlockId>,
+    tx_index: Option<usize>,
+    include_pending: bool,
+    evm_version: Option<EvmVersion>,
+    debug: bool,
+    decode_internal: Option<InternalTraceMode>,
+    gas_by_opcode: bool,
+    show_storage_reads: bool,
+    max_trace_bytes: usize,
+    state_overrides: StateOverride,
+    precompile_overrides: AddressHashMap<Bytes>,
+    call_return_overrides: HashMap<(Address, Selector), Bytes>,
+    block_timestamp: Option<u64>,
+    block_number_override: Option<u64>,
+    blob_base_fee: Option<u128>,
+}
+
+/// Creates the executor of the calls executed locally, forked at the requested block or
+/// transaction, with the overrides applied on top of its state.
+///
+/// Returns the executor along with its chain and the gas limit of the block, which is lifted for
+/// the executor as it is for `eth_call`.
+async fn local_executor(
+    ctx: &mut CallContext,
+    tx: &mut WithOtherFields<TransactionRequest>,
+    opts: LocalExecutorOpts,
+) -> Result<(TracingExecutor, Option<Chain>, u64)> {
+    let evm_opts = ctx.evm_opts.clone();
+    let provider = &ctx.provider;
+    let fork_state = ctx.fork_state.as_ref();
+    let config = &mut ctx.config;
+    let LocalExecutorOpts {
+        block,
+        tx_index,
+        include_pending,
+        evm_version,
+        debug,
+        decode_internal,
+        gas_by_opcode,
+        show_storage_reads,
+        max_trace_bytes,
+        state_overrides,
+        precompile_overrides,
+        call_return_overrides,
+        block_timestamp,
+        block_number_override,
+        blob_base_fee,
+    } = opts;
+
+    if let Some(BlockId::Number(BlockNumberOrTag::Number(block_number))) = block {
+        // Override Config `fork_block_number` (if set) with CLI value.
+        config.fork_block_number = Some(block_number);
+    }
+
+    // The state at a transaction of the block is the one of the parent block, with the
+    // preceding transactions replayed on top of it.
+    let replayed_block = match tx_index {
+        Some(index) => {
+            let replayed_block = provider
+                .get_block(block.unwrap_or(BlockId::latest()))
+                .full()
+                .await?
+                .ok_or_else(|| eyre::eyre!("block {} not found", format_block_id(block)))?;
+            let number = replayed_block.header.number;
+            let tx_count = replayed_block.transactions.len();
+            if index > tx_count {
+                eyre::bail!(
+                    "--tx-index {index} is out of range, block {number} has {tx_count} \
+                     transactions"
+                );
+            }
+            config.fork_block_number = Some(
+                number
+                    .checked_sub(1)
+                    .ok_or_else(|| eyre::eyre!("--tx-index can't be used at genesis"))?,
+            );
+            Some((replayed_block, index))
+        }
+        None => None,
+    };
+
+    let create2_deployer = evm_opts.create2_deployer;
+    let (mut env, fork, chain, odyssey) = match &fork_state {
+        Some(fork_state) => fork_state.evm_material(evm_opts),
+        None => TracingExecutor::get_fork_material(config, evm_opts).await?,
+    };
+
+    // modify settings that usually set in eth_call
+    let block_gas_limit = env.block.gas_limit.saturating_to();
+    env.cfg.disable_block_gas_limit = true;
+    env.block.gas_limit = U256::MAX;
+
+    let trace_mode = TraceMode::Call
+        .with_debug(debug)
+        .with_decode_internal(decode_internal.unwrap_or_default())
+        .with_state_changes(shell::verbosity() > 4);
+    let mut executor =
+        TracingExecutor::new(env, fork, evm_version, trace_mode, odyssey, create2_deployer)?;
+    if gas_by_opcode {
+        enable_opcode_gas(&mut executor);
+    }
+    if show_storage_reads {
+        enable_storage_reads(&mut executor);
+    }
+    limit_trace_size(&mut executor, max_trace_bytes);
+    if let Some((replayed_block, index)) = &replayed_block {
+        replay_block_transactions(&mut executor, replayed_block, *index)?;
+    }
+    if include_pending {
+        apply_pending_transactions(&mut executor, provider).await?;
+    }
+    if let Some(fork_state) = &fork_state {
+        apply_state_overrides(&mut executor, fork_state.state_overrides())?;
+    }
+    apply_state_overrides(&mut executor, state_overrides)?;
+    executor.inspector_mut().set_precompile_overrides(precompile_overrides);
+    executor.inspector_mut().set_call_return_overrides(call_return_overrides);
+    if let Some(timestamp) = block_timestamp {
+        executor.env_mut().block.timestamp = U256::from(timestamp);
+    }
+    if let Some(number) = block_number_override {
+        executor.env_mut().block.number = U256::from(number);
+    }
+    if let Some(blob_gasprice) = blob_base_fee {
+        let block = &mut executor.env_mut().block;
+        let excess_blob_gas = block
+            .blob_excess_gas_and_price
+            .as_ref()
+            .map(|blob| blob.excess_blob_gas)
+            .unwrap_or_default();
+        block.blob_excess_gas_and_price =
+            Some(BlobExcessGasAndPrice { excess_blob_gas, blob_gasprice });
+    }
+    if tx.blob_versioned_hashes.is_some() {
+        let env = executor.env_mut();
+        // Without a fee cap, the blob gas is paid at the blob base fee of the block.
+        tx.max_fee_per_blob_gas = tx.max_fee_per_blob_gas.or_else(|| env.block.get_blob_gasprice());
+        env.tx.blob_hashes = tx.blob_versioned_hashes.clone().unwrap_or_default();
+        env.tx.max_fee_per_blob_gas = tx.max_fee_per_blob_gas.map(U256::from);
+    }
+    executor.env_mut().tx.authorization_list =
+        tx.authorization_list.clone().map(AuthorizationList::Signed);
+
+    Ok((executor, chain, block_gas_limit))
+}
+
+/// Generates the access list of the call locally, see `--access-list`.
+///
+/// In JSON mode, the access list is printed along with the gas used by the call and its error
+/// instead, and `None` is returned.
+fn generate_access_list(executor: &Executor, call: &PreparedCall) -> Result<Option<AccessList>> {
+    let value = call.tx.value.unwrap_or_default();
+    let input = call.tx.inner.input.input().cloned().unwrap_or_default();
+    let tx_kind = call.tx.inner.to.expect("set by builder");
+    let (access_list, result) = executor.create_access_list(call.from, tx_kind, input, value)?;
+    if !shell::is_json() {
+        return Ok(Some(access_list));
+    }
+
+    let error = match &result {
+        ExecutionResult::Success { .. } => None,
+        ExecutionResult::Revert { output, .. } => Some(call.revert_decoder.decode(output, None)),
+        ExecutionResult::Halt { reason, .. } => Some(format!("{reason:?}")),
+    };
+    let result = AccessListResult { access_list, gas_used: U256::from(result.gas_used()), error };
+    sh_println!("{}", serde_json::to_string(&result)?)?;
+    Ok(None)
+}
+
+/// The options of the calls executed locally with `--trace`.
+struct TraceCallOpts<'a> {
+    trace_opcodes: bool,
+    prestate: bool,
+    state_dump: Option<&'a Path>,
+    continue_state: Option<&'a Path>,
+}
+
+/// Executes the calls locally with `--trace`, returning their traces in order of execution.
+///
+/// The transaction itself is executed without a batch of calls, which are otherwise committed one
+/// after the other. Returns `None` if the prestate was printed instead.
+fn trace_calls(
+    executor: &mut TracingExecutor,
+    tx: &WithOtherFields<TransactionRequest>,
+    from: Address,
+    block_gas_limit: u64,
+    calls: Option<Vec<(Address, Bytes)>>,
+    opts: TraceCallOpts<'_>,
+) -> Result<Option<Vec<TraceResult>>> {
+    if opts.prestate && calls.is_some() {
+        eyre::bail!("--prestate can only be used with a single call");
+    }
+
+    // The block gas limit is lifted, but an explicit `--gas-limit` caps the execution so that
+    // out-of-gas failures happen as they would on-chain.
+    let gas_limit = tx.gas;
+    if let Some(gas_limit) = gas_limit {
+        executor.set_gas_limit(gas_limit);
+    }
+    if opts.trace_opcodes {
+        let verbosity = shell::verbosity();
+        executor.set_opcode_printer(Some(OpcodePrinter::new(verbosity > 3, verbosity > 4)));
+    }
+    executor.env_mut().tx.access_list =
+        tx.access_list.clone().map(|access_list| access_list.0).unwrap_or_default();
+    // The sender is charged for the whole gas limit up front, which then defaults to the one of
+    // the block rather than the lifted one.
+    let fee_env = tx_env_with_fees(executor, tx, gas_limit.unwrap_or(block_gas_limit))?;
+
+    let value = tx.value.unwrap_or_default();
+    let mut changes = StateDump::new();
+    let traces = match calls {
+        Some(calls) => {
+            let mut traces = Vec::with_capacity(calls.len());
+            for (to, input) in calls {
+                // Each call is committed, so that the next ones see its state changes.
+                let result = match &fee_env {
+                    Some(env) => {
+                        let mut env = env.clone();
+                        env.tx.transact_to = TxKind::Call(to);
+                        env.tx.data = input;
+                        executor.transact_with_env(env)?
+                    }
+                    None => executor.transact_raw(from, to, input, value)?,
+                };
+                record_state_changes(&mut changes, &result.state_changeset);
+                traces.push(
+                    TraceResult::from_raw(result, TraceKind::Execution).with_gas_limit(gas_limit),
+                );
+            }
+            traces
+        }
+        None => {
+            // The state before the call, which is committed to the executor.
+            let pre_state = opts.prestate.then(|| executor.backend().clone());
+            let mut prestate_frame = None;
+            let input = tx.inner.input.input().cloned().unwrap_or_default();
+            let trace = match tx.inner.to.expect("set by builder") {
+                TxKind::Create => {
+                    let deploy_result = match fee_env {
+                        Some(env) => executor.deploy_with_env(env, None),
+                        None => executor.deploy(from, input, value, None),
+                    };
+                    let changeset = match &deploy_result {
+                        Ok(result) => Some(&result.raw.state_changeset),
+                        Err(EvmError::Execution(err)) => Some(&err.raw.state_changeset),
+                        Err(_) => None,
+                    };
+                    if let Some(changeset) = changeset {
+                        record_state_changes(&mut changes, changeset);
+                        if let Some(db) = &pre_state {
+                            prestate_frame = Some(build_prestate(changeset, db)?);
+                        }
+                    }
+                    TraceResult::try_from(deploy_result)?.with_gas_limit(gas_limit)
+                }
+                TxKind::Call(to) => {
+                    let result = match fee_env {
+                        Some(env) => executor.transact_with_env(env)?,
+                        None => executor.transact_raw(from, to, input, value)?,
+                    };
+                    record_state_changes(&mut changes, &result.state_changeset);
+                    if let Some(db) = &pre_state {
+                        prestate_frame = Some(build_prestate(&result.state_changeset, db)?);
+                    }
+                    TraceResult::from_raw(result, TraceKind::Execution).with_gas_limit(gas_limit)
+                }
+            };
+            if let Some(prestate) = prestate_frame {
+                write_state_changes(changes, opts.state_dump, opts.continue_state)?;
+                sh_println!("{}", serde_json::to_string_pretty(&prestate)?)?;
+                return Ok(None);
+            }
+            vec![trace]
+        }
+    };
+
+    write_state_changes(changes, opts.state_dump, opts.continue_state)?;
+    Ok(Some(traces))
+}
+
+/// Writes the storage changes of the calls to the `--state-dump` and `--continue-state` files.
+fn write_state_changes(
+    changes: StateDump,
+    state_dump: Option<&Path>,
+    continue_state: Option<&Path>,
+) -> Result<()> {
+    if let Some(path) = state_dump {
+        write_state_dump(path, &changes)?;
+    }
+    if let Some(path) = continue_state {
+        write_continued_state(path, changes)?;
+    }
+    Ok(())
+}
+
+/// A call executed over RPC, along with how to decode its output.
+#[derive(Clone, Copy)]
+struct RpcCall<'a> {
+    tx: &'a WithOtherFields<TransactionRequest>,
+    func: Option<&'a Function>,
+    state_overrides: &'a StateOverride,
+    block_overrides: Option<&'a BlockOverrides>,
+    output_abi: Option<&'a DynSolType>,
+    separator: &'a str,
+}
+
+impl RpcCall<'_> {
+    /// Decodes the output with the forced output types, if any.
+    fn decode(&self, output: String) -> Result<String> {
+        match self.output_abi {
+            Some(ty) => decode_raw_output(output, ty, self.separator),
+            None => Ok(output),
+        }
+    }
+
+    /// Executes the call at the given block, returning its decoded output.
+    async fn output<P: Provider<AnyNetwork>>(
+        &self,
+        cast: &Cast<P>,
+        block: Option<BlockId>,
+    ) -> Result<String> {
+        let output = cast
+            .call_with_overrides(
+                self.tx,
+                self.func,
+                block,
+                self.state_overrides.clone(),
+                self.block_overrides.cloned(),
+            )
+            .await?;
+        self.decode(output)
+    }
+
+    /// Executes the call on the node of `cast`, returning its decoded output or revert reason.
+    async fn outcome<P: Provider<AnyNetwork>>(
+        &self,
+        cast: &Cast<P>,
+        block: BlockId,
+        revert_decoder: &RevertDecoder,
+    ) -> Result<String> {
+        let result = cast
+            .call_result(
+                self.tx,
+                Some(block),
+                self.state_overrides.clone(),
+                self.block_overrides.cloned(),
+                revert_decoder,
+            )
+            .await?;
+        match result {
+            CallResult::Success { output } => {
+                let output =
+                    cast.format_call_output(self.tx, self.func, Some(block), output).await?;
+                self.decode(output)
+            }
+            CallResult::Revert { reason, .. } => Ok(format!("execution reverted: {reason}")),
+        }
+    }
+}
+
+/// Parse a balance override, either absolute or a delta relative to the current balance.
+fn parse_balance_override(addr: Address, balance: &str, current: Option<&U256>) -> Result<U256> {
+    let (delta, negative) = match (balance.strip_prefix('+'), balance.strip_prefix('-')) {
+        (Some(delta), _) => (delta, false),
+        (_, Some(delta)) => (delta, true),
+        _ => return Ok(balance.parse()?),
+    };
+    let current =
+        current.ok_or_else(|| eyre::eyre!("Missing current balance of {addr} to apply delta"))?;
+    let delta = parse_ether_value(delta)?;
+    if negative {
+        current.checked_sub(delta).ok_or_else(|| {
+            eyre::eyre!(
+                "Balance override {balance} for {addr} would underflow the current balance of \
+                 {current} wei"
+            )
+        })
+    } else {
+        current
+            .checked_add(delta)
+            .ok_or_else(|| eyre::eyre!("Balance override {balance} for {addr} overflows"))
+    }
+}
+
+/// Parse a nonce override, either absolute or `auto` with an optional offset relative to the
+/// current nonce.
+fn parse_nonce_override(addr: Address, nonce: &str, current: Option<&u64>) -> Result<u64> {
+    let Some(offset) = nonce.strip_prefix("auto") else { return Ok(nonce.parse()?) };
+    let current =
+        *current.ok_or_else(|| eyre::eyre!("Missing current nonce of {addr} to resolve `auto`"))?;
+    let offset = parse_nonce_offset(nonce, offset)?;
+    current.checked_add_signed(offset).ok_or_else(|| {
+        eyre::eyre!(
+            "Nonce override {nonce} for {addr} is out of range of the current nonce {current}"
+        )
+    })
+}
+
+/// Parse the offset of an `auto` nonce override, i.e. what follows `auto` in `nonce`.
+fn parse_nonce_offset(nonce: &str, offset: &str) -> Result<i64> {
+    match offset {
+        "" => Ok(0),
+        offset if offset.starts_with(['+', '-']) => offset
+            .trim_start_matches('+')
+            .parse()
+            .wrap_err_with(|| format!("Invalid nonce offset in {nonce}")),
+        _ => eyre::bail!("Invalid nonce override {nonce}. Expected `auto`, `auto+N` or `auto-N`"),
+    }
+}
+
+/// Parse an override string in the format address:value.
+fn address_value_override(address_override: &str) -> Result<(&str, &str)> {
+    address_override.split_once(':').ok_or_else(|| {
+        eyre::eyre!("Invalid override {address_override}. Expected <address>:<value>")
+    })
+}
+
+/// Records the state of the accounts touched by a call: their balance and nonce, the code of the
+/// created contracts and the changed storage slots.
+fn record_state_changes(changes: &mut StateDump, changeset: &StateChangeset) {
+    for (address, account) in changeset {
+        if !account.is_touched() {
+            continue;
+        }
+        let dump = changes.entry(*address).or_default();
+        dump.balance = Some(account.info.balance);
+        dump.nonce = Some(account.info.nonce);
+        if account.is_created() {
+            dump.code = account.info.code.as_ref().map(Bytecode::original_bytes);
+        }
+        for (slot, value) in account.storage.iter().filter(|(_, value)| value.is_changed()) {
+            dump.storage.insert((*slot).into(), value.present_value.into());
+        }
+    }
+}
+
+/// Merges the state left by a later call into `state`.
+fn merge_state_dump(state: &mut StateDump, changes: StateDump) {
+    for (addr, account) in changes {
+        state.entry(addr).or_default().merge(account);
+    }
+}
+
+/// Writes the state changes recorded with `--state-dump`.
+fn write_state_dump(path: &Path, changes: &StateDump) -> Result<()> {
+    fs::write_pretty_json_file(path, changes)?;
+    if !shell::is_json() {
+        sh_println!("State dump written to {}", path.display())?;
+    }
+    Ok(())
+}
+
+/// An account of a dump written with `--state-dump`, whose storage is parsed like the one of an
+/// `address:path` storage dump.
+#[derive(Deserialize)]
+struct RawAccountDump {
+    balance: Option<U256>,
+    nonce: Option<u64>,
+    code: Option<Bytes>,
+    #[serde(default)]
+    storage: BTreeMap<String, String>,
+}
+
+/// Reads a dump of multiple accounts written with `--state-dump`.
+fn read_state_dump(path: &Path) -> Result<StateDump> {
+    let dump: BTreeMap<Address, RawAccountDump> = fs::read_json_file(path)?;
+    dump.into_iter()
+        .map(|(addr, RawAccountDump { balance, nonce, code, storage })| {
+            let storage = parse_storage_dump(path, storage)?.into_iter().collect();
+            Ok((addr, AccountDump { balance, nonce, code, storage }))
+        })
+        .collect()
+}
+
+/// Builds the output of geth's `prestateTracer` for a call, from the accounts it touched and the
+/// state before it was executed, see `--prestate`.
+///
+/// The storage slots accessed by the call are included with their value before the call.
+fn build_prestate(changeset: &StateChangeset, db: &Backend) -> Result<PreStateMode> {
+    let mut prestate = PreStateMode::default();
+    for (addr, account) in changeset {
+        let info = db.basic_ref(*addr)?.unwrap_or_default();
+        let code = match info.code {
+            Some(code) => Some(code.original_bytes()),
+            None if info.code_hash == KECCAK_EMPTY => None,
+            None => Some(db.code_by_hash_ref(info.code_hash)?.original_bytes()),
+        };
+        let mut state = AccountState::from_account_info(info.nonce, info.balance, code);
+        for (slot, value) in &account.storage {
+            state.storage.insert((*slot).into(), value.original_value.into());
+        }
+        prestate.0.insert(*addr, state);
+    }
+    Ok(prestate)
+}
+
+/// Merges the state changes of a call into the state saved with `--continue-state`.
+fn write_continued_state(path: &Path, changes: StateDump) -> Result<()> {
+    let mut state = if path.exists() { read_state_dump(path)? } else { StateDump::new() };
+    merge_state_dump(&mut state, changes);
+    fs::write_pretty_json_file(path, &state)?;
+    Ok(())
+}
+
+/// The state dumped by `anvil --dump-state`, used as the fork source of `--fork-state-file`.
+#[derive(Debug, Deserialize)]
+struct ForkState {
+    /// The environment of the block the state was dumped at, missing in older dumps.
+    block: Option<ForkStateBlock>,
+    best_block_number: Option<U256>,
+    accounts: BTreeMap<Address, ForkStateAccount>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForkStateBlock {
+    number: U256,
+    coinbase: Address,
+    timestamp: U256,
+    gas_limit: U256,
+    basefee: U256,
+    difficulty: U256,
+    prevrandao: Option<B256>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForkStateAccount {
+    nonce: u64,
+    balance: U256,
+    code: Bytes,
+    storage: BTreeMap<U256, U256>,
+}
+
+impl ForkState {
+    fn load(path: &Path) -> Result<Self> {
+        fs::read_json_file(path)
+            .wrap_err_with(|| format!("failed to load fork state from {}", path.display()))
+    }
+
+    /// Returns the chain id of the call, which isn't part of the dump.
+    fn chain_id(&self, evm_opts: &EvmOpts) -> u64 {
+        evm_opts.env.chain_id.unwrap_or(DEV_CHAIN_ID)
+    }
+
+    /// Returns the nonce of the given account, or 0 if it isn't part of the dump.
+    fn nonce(&self, address: Address) -> u64 {
+        self.accounts.get(&address).map_or(0, |account| account.nonce)
+    }
+
+    /// Returns the balance of the given account, or 0 if it isn't part of the dump.
+    fn balance(&self, address: Address) -> U256 {
+        self.accounts.get(&address).map_or(U256::ZERO, |account| account.balance)
+    }
+
+    /// Returns the local environment of the executor, with the block of the dump.
+    fn evm_material(&self, evm_opts: EvmOpts) -> (Env, Option<CreateFork>, Option<Chain>, bool) {
+        let mut env = evm_opts.local_evm_env();
+        env.cfg.chain_id = self.chain_id(&evm_opts);
+        if let Some(block) = &self.block {
+            env.block.number = block.number;
+            env.block.coinbase = block.coinbase;
+            env.block.timestamp = block.timestamp;
+            env.block.gas_limit = block.gas_limit;
+            env.block.basefee = block.basefee;
+            env.block.difficulty = block.difficulty;
+            env.block.prevrandao = block.prevrandao.or(env.block.prevrandao);
+        } else if let Some(number) = self.best_block_number {
+            env.block.number = number;
+        }
+        let chain = Some(env.cfg.chain_id.into());
+        (env, None, chain, evm_opts.odyssey)
+    }
+
+    /// Returns the accounts of the dump as overrides replacing their whole state.
+    fn state_overrides(&self) -> StateOverride {
+        self.accounts
+            .iter()
+            .map(|(address, account)| {
+                let state = account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (B256::from(*slot), B256::from(*value)))
+                    .collect();
+                let account = AccountOverride {
+                    balance: Some(account.balance),
+                    nonce: Some(account.nonce),
+                    code: Some(account.code.clone()),
+                    state: Some(state),
+                    ..Default::default()
+                };
+                (*address, account)
+            })
+            .collect()
+    }
+}
+
+/// A lock on the state file of `--continue-state`, released when dropped.
+///
+/// The lock is a file next to the state file, so that calls in separate processes can't write the
+/// state at the same time.
+#[derive(Debug)]
+struct StateLock {
+    path: PathBuf,
+}
+
+impl StateLock {
+    /// Acquires the lock of the given state file, failing if it is already held.
+    fn acquire(state_path: &Path) -> Result<Self> {
+        let mut path = state_path.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => Ok(Self { path }),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => eyre::bail!(
+                "The state at {} is being used by another call. If that is not the case, remove \
+                 the stale lock file {}",
+                state_path.display(),
+                path.display()
+            ),
+            Err(err) => Err(eyre::Report::new(err)
+                .wrap_err(format!("failed to lock the state at {}", state_path.display()))),
+        }
+    }
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Ensures that the destination doesn't need to be resolved from ENS in offline mode.
+fn ensure_offline_destination(config: &Config, to: Option<&NameOrAddress>) -> Result<()> {
+    if let Some(NameOrAddress::Name(name)) = to.filter(|_| config.offline) {
+        eyre::bail!("Resolving the ENS name `{name}` can't be done in offline mode");
+    }
+    Ok(())
+}
+
+/// Ensures that an Etherscan API key is configured for `--abi-from-etherscan`.
+pub(crate) fn ensure_etherscan_key(config: &Config, chain: Option<Chain>) -> Result<()> {
+    if config.offline {
+        eyre::bail!("--abi-from-etherscan can't be used in offline mode");
+    }
+    if config.get_etherscan_config_with_chain(chain)?.is_none() {
+        eyre::bail!(
+            "--abi-from-etherscan requires an Etherscan API key, set it with --etherscan-api-key \
+             or the ETHERSCAN_API_KEY environment variable"
+        );
+    }
+    Ok(())
+}
+
+/// The calls executed locally with `--trace`, along with what is needed to render their traces.
+struct TracedCalls {
+    /// The traces of the calls, in order of execution.
+    traces: Vec<TraceResult>,
+    /// Whether multiple calls were executed.
+    batch: bool,
+    /// The destinations of the calls, when executed against each address of `--to-list`.
+    targets: Option<Vec<Address>>,
+    config: Config,
+    chain: Option<Chain>,
+    /// The block the calls were executed at.
+    block: Option<BlockId>,
+    abis: Vec<JsonAbi>,
+}
+
+/// Merges the traces of calls executed one after the other into a single result.
+fn merge_trace_results(mut results: Vec<TraceResult>) -> TraceResult {
+    if results.len() == 1 {
+        return results.remove(0);
+    }
+    TraceResult {
+        success: results.iter().all(|result| result.success),
+        gas_used: results.iter().map(|result| result.gas_used).sum(),
+        out_of_gas: results.iter().any(|result| result.out_of_gas),
+        gas_limit: results.first().and_then(|result| result.gas_limit),
+        traces: Some(
+            results.into_iter().flat_map(|result| result.traces.unwrap_or_default()).collect(),
+        ),
+    }
+}
+
+/// Returns the decoded revert reason of the top-level call of a trace, if it did not succeed.
+fn trace_revert_reason(result: &TraceResult, revert_decoder: &RevertDecoder) -> Option<String> {
+    if result.success {
+        return None;
+    }
+    if result.out_of_gas {
+        return Some("out of gas".to_string());
+    }
+    let output = result
+        .traces
+        .iter()
+        .flatten()
+        .find_map(|(_, arena)| arena.nodes().first())
+        .map(|node| node.trace.output.clone())
+        .unwrap_or_default();
+    Some(revert_decoder.decode(&output, None))
+}
+
+/// An ABI passed with `--abi`, either as is or within an artifact.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AbiFile {
+    Abi(JsonAbi),
+    Artifact { abi: JsonAbi },
+}
+
+/// Loads an ABI passed with `--abi`, either inline or from a JSON ABI or artifact file.
+pub(crate) fn load_abi(abi: &str) -> Result<JsonAbi> {
+    let (json, source) = if abi.trim_start().starts_with(['[', '{']) {
+        (abi.to_string(), "inline ABI")
+    } else {
+        (fs::read_to_string(abi)?, abi)
+    };
+    let file: AbiFile =
+        serde_json::from_str(&json).wrap_err_with(|| format!("invalid ABI in {source}"))?;
+    match file {
+        AbiFile::Abi(abi) | AbiFile::Artifact { abi } => Ok(abi),
+    }
+}
+
+/// Loads the calldata of the calls to execute sequentially, from either a repeated `--data` or a
+/// `--batch-file`.
+///
+/// Returns `None` if there is at most a single call.
+fn load_batch_calldata(data: &[String], batch_file: Option<&Path>) -> Result<Option<Vec<Bytes>>> {
+    if let Some(path) = batch_file {
+        let content = fs::read_to_string(path)?;
+        let calls = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| {
+                parse_calldata(line).wrap_err_with(|| format!("invalid calldata on line {}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if calls.is_empty() {
+            eyre::bail!("no calls found in batch file {}", path.display());
+        }
+        return Ok(Some(calls));
+    }
+
+    if data.len() < 2 {
+        return Ok(None);
+    }
+    data.iter().map(|data| parse_calldata(data)).collect::<Result<Vec<_>>>().map(Some)
+}
+
+/// Returns a stream of the number and timestamp of the new blocks, which are polled every
+/// `interval` seconds if set, or subscribed to otherwise.
+async fn new_blocks(
+    config: &Config,
+    provider: &RetryProvider,
+    interval: Option<u64>,
+) -> Result<BoxStream<'static, Result<(u64, u64)>>> {
+    if let Some(interval) = interval {
+        let interval = Duration::from_secs(interval);
+        let blocks = stream::unfold((provider.clone(), None), move |(provider, last)| async move {
+            loop {
+                if last.is_some() {
+                    tokio::time::sleep(interval).await;
+                }
+                let block = match provider.get_block(BlockId::latest()).await {
+                    Ok(block) => block,
+                    Err(err) => return Some((Err(err.into()), (provider, last))),
+                };
+                let Some(block) = block else {
+                    tokio::time::sleep(interval).await;
+                    continue;
+                };
+                let (number, timestamp) = (block.header.number, block.header.timestamp);
+                if last.is_none_or(|last| number > last) {
+                    return Some((Ok((number, timestamp)), (provider, Some(number))));
+                }
+            }
+        });
+        return Ok(blocks.boxed());
+    }
+
+    let url = config.get_rpc_url_or_localhost_http()?;
+    if !url.starts_with("ws") {
+        eyre::bail!(
+            "--watch subscribes to new blocks, which requires a WebSocket RPC URL; \
+             use --watch-interval to poll for them instead"
+        );
+    }
+    // The transport of `get_provider` doesn't support subscriptions, see `cast logs`.
+    let provider = alloy_provider::ProviderBuilder::<_, _, AnyNetwork>::default()
+        .connect(url.as_ref())
+        .await?;
+    let subscription = provider.subscribe_blocks().await?.into_stream();
+    // The provider is moved into the stream, as the subscription ends when it is dropped.
+    Ok(subscription
+        .map(move |header| {
+            let _ = &provider;
+            Ok((header.number, header.timestamp))
+        })
+        .boxed())
+}
+
+/// Loads the destinations of `--to-list`, one address per line.
+fn load_to_list(path: &Path) -> Result<Vec<Address>> {
+    let content = fs::read_to_string(path)?;
+    let targets = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            Address::from_str(line).wrap_err_with(|| format!("invalid address on line {}", i + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if targets.is_empty() {
+        eyre::bail!("no addresses found in {}", path.display());
+    }
+    Ok(targets)
+}
+
+/// Parses hex-encoded calldata, see [`normalize_calldata`].
+fn parse_calldata(data: &str) -> Result<Bytes> {
+    Ok(hex::decode(normalize_calldata(data)?)?.into())
+}
+
+/// Validates hex-encoded calldata, e.g. copied from a block explorer, returning its hex digits.
+///
+/// Surrounding whitespace and quotes, and the `0x` prefix are stripped.
+fn normalize_calldata(data: &str) -> Result<&str> {
+    let trimmed = data.trim().trim_matches(['"', '\'']).trim();
+    let digits =
+        trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")).unwrap_or(trimmed);
+    if let Some((i, c)) = digits.chars().enumerate().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        eyre::bail!("invalid calldata {data:?}: invalid hex character {c:?} at position {i}");
+    }
+    if digits.len() % 2 != 0 {
+        eyre::bail!(
+            "invalid calldata {data:?}: odd number of hex digits ({}), calldata must be whole bytes",
+            digits.len()
+        );
+    }
+    Ok(digits)
+}
+
+/// Parse the `--output-abi` types, wrapping a single type into a tuple.
+///
+/// Parsed by clap, so that invalid types are rejected before any request is made.
+fn parse_output_abi(s: &str) -> Result<DynSolType> {
+    let ty = DynSolType::parse(s).wrap_err_with(|| format!("invalid output ABI type: {s}"))?;
+    Ok(match ty {
+        DynSolType::Tuple(_) => ty,
+        ty => DynSolType::Tuple(vec![ty]),
+    })
+}
+
+/// ABI-decode the raw hex output of a call against the given types.
+///
+/// Falls back to the raw output with a warning if it can't be decoded.
+fn decode_raw_output(output: String, ty: &DynSolType, separator: &str) -> Result<String> {
+    let Some(values) = hex::decode(&output).ok().and_then(|data| decode_output_values(&data, ty))
+    else {
+        return Ok(output);
+    };
+
+    Ok(if shell::is_json() {
+        let tokens = values.iter().map(format_token_raw).collect::<Vec<_>>();
+        serde_json::to_string_pretty(&tokens)?
+    } else {
+        values.iter().map(format_token).collect::<Vec<_>>().join(separator)
+    })
+}
+
+/// ABI-decode the output of a call against the given types, warning if it can't be decoded.
+fn decode_output_values(data: &[u8], ty: &DynSolType) -> Option<Vec<DynSolValue>> {
+    match ty.abi_decode_params(data) {
+        Ok(DynSolValue::Tuple(values)) => Some(values),
+        Ok(value) => Some(vec![value]),
+        Err(err) => {
+            let _ = sh_warn!("Could not decode the output as {}: {err}", ty.sol_type_name());
+            None
+        }
+    }
+}
+
+/// Returns the account and the path of an `--override-storage-dump`.
+///
+/// A path without an address is a dump of multiple accounts written by `--state-dump`.
+fn storage_dump_target(override_str: &str) -> (Option<Address>, &Path) {
+    let account_dump = override_str
+        .split_once(':')
+        .and_then(|(addr, path)| Some((Address::from_str(addr).ok()?, path)));
+    match account_dump {
+        Some((addr, path)) => (Some(addr), Path::new(path)),
+        None => (None, Path::new(override_str)),
+    }
+}
+
+/// Reads the storage dump of a single account, see [`parse_storage_dump`].
+fn read_storage_dump(path: &Path) -> Result<Vec<(B256, B256)>> {
+    let dump: BTreeMap<String, String> = fs::read_json_file(path)?;
+    parse_storage_dump(path, dump)
+}
+
+/// Parse the slots of a storage dump, a JSON object mapping 32-byte hex slots to values.
+fn parse_storage_dump(path: &Path, dump: BTreeMap<String, String>) -> Result<Vec<(B256, B256)>> {
+    dump.into_iter()
+        .map(|(slot, value)| {
+            let slot = B256::from_str(&slot).map_err(|_| {
+                eyre::eyre!(
+                    "Invalid slot {slot} in storage dump {}. Expected a 32-byte hex string",
+                    path.display()
+                )
+            })?;
+            let value = U256::from_str(&value).map_err(|_| {
+                eyre::eyre!("Invalid value {value} in storage dump {}", path.display())
+            })?;
+            Ok((slot, value.into()))
+        })
+        .collect()
+}
+
+/// Parse an override string in the format address:slot:value.
+fn address_slot_value_override(address_override: &str) -> Result<(Address, U256, U256)> {
+    let captures = OVERRIDE_PATTERN.captures(address_override).ok_or_else(|| {
+        eyre::eyre!("Invalid override {address_override}. Expected <address>:<slot>:<value>")
+    })?;
+
+    Ok((
+        captures[1].parse()?,               // Address
+        parse_override_slot(&captures[2])?, // Slot (U256)
+        captures[3].parse()?,               // Value (U256)
+    ))
+}
+
+/// Parse the slot of a state override, either a slot number or the slot of a mapping entry given
+/// as `key@slot`.
+///
+/// Nested mappings are given from the outermost key, as in Solidity: `k1@k2@slot` is the slot of
+/// `mapping[k1][k2]`. Keys are addresses if they are 20 bytes long, and uint256 otherwise.
+fn parse_override_slot(slot: &str) -> Result<U256> {
+    let Some((keys, slot)) = slot.rsplit_once('@') else { return Ok(slot.parse()?) };
+    let mut slot = B256::from(slot.parse::<U256>()?);
+    for key in keys.split('@') {
+        let word = if key.strip_prefix("0x").is_some_and(|hex| hex.len() == 40) {
+            key.parse::<Address>().ok().map(Address::into_word)
+        } else {
+            key.parse::<U256>().ok().map(B256::from)
+        };
+        let Some(key) = word else { eyre::bail!("Invalid mapping key {key}") };
+        slot = keccak256([key.as_slice(), slot.as_slice()].concat());
+    }
+    Ok(U256::from_be_bytes(slot.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::revm::primitives::{Account, AccountInfo, EvmStorageSlot};
+    use alloy_json_rpc::ErrorPayload;
+    use alloy_primitives::{address, b256, hex};
+    use alloy_sol_types::{Panic, Revert, SolError};
+    use foundry_evm::{
+        precompiles::EC_RECOVER,
+        traces::{CallTraceArena, SparsedTraceArena},
+    };
+
+    #[test]
+    fn can_parse_call_data() {
+        let data = hex::encode("hello");
+        let args = CallArgs::parse_from(["foundry-cli", "--data", data.as_str()]);
+        assert_eq!(args.data, vec![data]);
+
+        let data = hex::encode_prefixed("hello");
+        let args = CallArgs::parse_from(["foundry-cli", "--data", data.as_str()]);
+        assert_eq!(args.data, vec![data]);
+    }
+
+    #[test]
+    fn can_normalize_call_data() {
+        assert_eq!(normalize_calldata("0xdeadBEEF").unwrap(), "deadBEEF");
+        assert_eq!(normalize_calldata("deadbeef").unwrap(), "deadbeef");
+        assert_eq!(normalize_calldata(" \"0XdeadBeef\"\n").unwrap(), "deadBeef");
+        assert_eq!(normalize_calldata("'0x'").unwrap(), "");
+        assert_eq!(parse_calldata(" 0x0102 ").unwrap(), Bytes::from([1, 2]));
+
+        let err = normalize_calldata("0xabc").unwrap_err().to_string();
+        assert!(err.contains("odd number of hex digits (3)"), "{err}");
+        let err = normalize_calldata("0xabzd").unwrap_err().to_string();
+        assert!(err.contains("invalid hex character 'z' at position 2"), "{err}");
+    }
+
+    #[test]
+    fn can_parse_trace_format() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace"]);
+        assert_eq!(args.trace_format, TraceFormat::Pretty);
+
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--trace-format", "json"]);
+        assert_eq!(args.trace_format, TraceFormat::Json);
+        args.validate_trace_format().unwrap();
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--trace-format",
+            "json",
+            "--trace-stack-depth-limit",
+            "2",
+        ]);
+        let err = args.validate_trace_format().unwrap_err();
+        assert!(err.to_string().contains("--trace-stack-depth-limit"), "{err}");
+    }
+
+    #[test]
+    fn can_parse_create2_args() {
+        let salt = B256::with_last_byte(1);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--create",
+            "0x00",
+            "--salt",
+            &salt.to_string(),
+            "--deployer",
+            &Address::ZERO.to_string(),
+        ]);
+        assert!(matches!(
+            args.command,
+            Some(CallSubcommands::Create { salt: Some(s), deployer: Some(Address::ZERO), .. })
+                if s == salt
+        ));
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--create",
+            "0x00",
+            "--deployer",
+            &Address::ZERO.to_string(),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_block_range() {
+        let args = CallArgs::parse_from(["foundry-cli", "--block-range", "10:20"]);
+        let range = args.block_range.unwrap();
+        assert_eq!(range, BlockRange { start: 10, end: 20, step: 1 });
+        assert_eq!(range.blocks().count(), 11);
+
+        let range = BlockRange::from_str("10:20:5").unwrap();
+        assert_eq!(range.blocks().collect::<Vec<_>>(), vec![10, 15, 20]);
+
+        assert!(BlockRange::from_str("20:10").is_err());
+        assert!(BlockRange::from_str("10:20:0").is_err());
+        assert!(BlockRange::from_str("10").is_err());
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "--block-range", "10:20", "--trace"]).is_err()
+        );
+    }
+
+    #[test]
+    fn can_parse_block() {
+        let args = CallArgs::parse_from(["foundry-cli", "--block", "-10"]);
+        assert_eq!(args.block, Some(CallBlock::BeforeLatest(10)));
+
+        let args = CallArgs::parse_from(["foundry-cli", "-b", "genesis"]);
+        assert_eq!(args.block, Some(CallBlock::Id(BlockId::number(0))));
+
+        let args = CallArgs::parse_from(["foundry-cli", "--block", "safe"]);
+        assert_eq!(args.block, Some(CallBlock::Id(BlockId::safe())));
+
+        assert!(CallBlock::from_str("-latest").is_err());
+    }
+
+    #[test]
+    fn can_parse_impersonate() {
+        let addr = Address::with_last_byte(1);
+        let args = CallArgs::parse_from(["foundry-cli", "--impersonate", &addr.to_string()]);
+        assert_eq!(args.impersonate, Some(addr));
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--impersonate",
+            &addr.to_string(),
+            "--from",
+            &addr.to_string(),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_trace_export() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--trace-export", "trace.json"]);
+        assert_eq!(args.trace_export, Some(PathBuf::from("trace.json")));
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--trace-export", "trace.json"]).is_err());
+    }
+
+    #[test]
+    fn merges_trace_results() {
+        let result = |success, gas_used| TraceResult {
+            success,
+            traces: Some(vec![(
+                TraceKind::Execution,
+                SparsedTraceArena { arena: Default::default(), ignored: Default::default() },
+            )]),
+            gas_used,
+            out_of_gas: false,
+            gas_limit: Some(100),
+        };
+        let merged = merge_trace_results(vec![result(true, 10), result(false, 20)]);
+        assert!(!merged.success);
+        assert_eq!(merged.gas_used, 30);
+        assert_eq!(merged.gas_limit, Some(100));
+        assert_eq!(merged.traces.unwrap().len(), 2);
+
+        let single = merge_trace_results(vec![result(true, 10)]);
+        assert!(single.success);
+        assert_eq!(single.gas_used, 10);
+    }
+
+    #[test]
+    fn requires_etherscan_key() {
+        let err = ensure_etherscan_key(&Config::default(), None).unwrap_err();
+        assert!(err.to_string().contains("requires an Etherscan API key"), "{err}");
+
+        let config = Config { etherscan_api_key: Some("key".to_string()), ..Default::default() };
+        ensure_etherscan_key(&config, Some(Chain::mainnet())).unwrap();
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--abi-from-etherscan"]).is_err());
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--trace",
+            "--quick",
+            "--abi-from-etherscan"
+        ])
+        .is_err());
+
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--no-etherscan"]);
+        assert!(args.no_etherscan);
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--trace",
+            "--no-etherscan",
+            "--abi-from-etherscan"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn decodes_panic_call_errors() {
+        let revert = |data: Vec<u8>| -> eyre::Report {
+            TransportError::ErrorResp(ErrorPayload {
+                code: 3,
+                message: "execution reverted".into(),
+                data: Some(serde_json::value::to_raw_value(&hex::encode_prefixed(data)).unwrap()),
+            })
+            .into()
+        };
+        let decoder = RevertDecoder::new();
+
+        let err =
+            decode_call_error(revert(Panic { code: U256::from(0x32) }.abi_encode()), &decoder);
+        assert_eq!(err.to_string(), "execution reverted: panic: array out-of-bounds access (0x32)");
+
+        let err =
+            decode_call_error(revert(Panic { code: U256::from(0x99) }.abi_encode()), &decoder);
+        assert_eq!(err.to_string(), "execution reverted: panic: unknown panic code (0x99)");
+
+        let err = decode_call_error(eyre::eyre!("connection refused"), &decoder);
+        assert_eq!(err.to_string(), "connection refused");
+    }
+
+    #[test]
+    fn formats_block_id() {
+        assert_eq!(format_block_id(None), "latest");
+        assert_eq!(format_block_id(Some(BlockId::number(100))), "100");
+        assert_eq!(format_block_id(Some(BlockId::safe())), "safe");
+    }
+
+    #[test]
+    fn can_parse_precompile_overrides() {
+        let ecrecover = format!("{}:{}", EC_RECOVER, Address::with_last_byte(1).into_word());
+        let overrides = parse_precompile_overrides(&[ecrecover], false).unwrap();
+        assert_eq!(overrides[&EC_RECOVER], Bytes::from(Address::with_last_byte(1).into_word().0));
+
+        let other = "0x0000000000000000000000000000000000001234:0x01".to_string();
+        let err = parse_precompile_overrides(&[other.clone()], false).unwrap_err();
+        assert!(err.to_string().contains("--force"), "{err}");
+        assert_eq!(parse_precompile_overrides(&[other], true).unwrap().len(), 1);
+
+        assert!(parse_precompile_overrides(&[EC_RECOVER.to_string()], false).is_err());
+        assert!(parse_precompile_overrides(&[format!("{EC_RECOVER}:0xzz")], false).is_err());
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "--override-precompile", "0x01:0x"]).is_err()
+        );
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--trace", "--force"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_call_return_overrides() {
+        let addr = Address::with_last_byte(1);
+        let overrides = parse_call_return_overrides(&[
+            format!("{addr}:0x70a08231:0x01"),
+            format!("{addr}:balanceOf(address):0x02"),
+            format!("{addr}:totalSupply():0x"),
+        ])
+        .unwrap();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides[&(addr, Selector::from(hex!("70a08231")))], Bytes::from([2]));
+        assert_eq!(overrides[&(addr, Selector::from(hex!("18160ddd")))], Bytes::new());
+
+        assert!(parse_call_return_overrides(&[format!("{addr}:0x01")]).is_err());
+        assert!(parse_call_return_overrides(&[format!("{addr}:0x70a0:0x")]).is_err());
+        assert!(parse_call_return_overrides(&[format!("{addr}:balanceOf(:0x")]).is_err());
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--call-override-return",
+            format!("{addr}:0x70a08231:0x").as_str()
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_identify_creation() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--la", "--identify-creation"]);
+        assert!(args.identify_creation);
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "--trace", "--identify-creation"]).is_err()
+        );
+    }
+
+    #[test]
+    fn can_parse_raw() {
+        let args = CallArgs::parse_from(["foundry-cli", "--raw"]);
+        assert!(args.raw);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--raw", "--trace"]).is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--raw", "--returns", "uint256"]).is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--raw", "--pretty"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_tsv() {
+        let args = CallArgs::parse_from(["foundry-cli", "--tsv"]);
+        assert!(args.tsv);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--tsv", "--trace"]).is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--tsv", "--pretty"]).is_err());
+    }
+
+    #[test]
+    fn offline_rejects_network_lookups() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--offline"]);
+        assert!(args.offline);
+        let figment = Into::<Figment>::into(&args.eth).merge(&args);
+        let config = Config::from_provider(figment).unwrap();
+        assert!(config.offline);
+
+        let name = NameOrAddress::Name("vitalik.eth".to_string());
+        let err = ensure_offline_destination(&config, Some(&name)).unwrap_err();
+        assert!(err.to_string().contains("offline mode"), "{err}");
+        ensure_offline_destination(&config, Some(&Address::ZERO.into())).unwrap();
+        ensure_offline_destination(&Config::default(), Some(&name)).unwrap();
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--trace",
+            "--offline",
+            "--abi-from-etherscan"
+        ])
+        .is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--trace", "--offline", "--ens-names"])
+            .is_err());
+    }
+
+    #[test]
+    fn can_parse_trace_filter() {
+        let [a, b] = [1, 2].map(Address::repeat_byte);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--trace-filter",
+            &a.to_string(),
+            "--trace-filter",
+            &b.to_string(),
+        ]);
+        assert_eq!(args.trace_filter, [a, b]);
+
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "--trace-filter", &a.to_string()]).is_err()
+        );
+    }
+
+    #[test]
+    fn can_parse_diff_against() {
+        let url = "http://localhost:8546";
+        let args = CallArgs::parse_from(["foundry-cli", "--diff-against", url]);
+        assert_eq!(args.diff_against.as_deref(), Some(url));
+
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "--trace", "--diff-against", url]).is_err()
+        );
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--block-range",
+            "1:2",
+            "--diff-against",
+            url
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_ens_names() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--ens-names"]);
+        assert!(args.ens_names);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--ens-names"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_fund_sender() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--fund-sender", "2ether"]);
+        assert_eq!(args.fund_sender, Some(U256::from(2_000_000_000_000_000_000u128)));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--fund-sender", "1"]).is_err());
+    }
+
+    #[test]
+    fn can_decode_raw_output_with_output_abi() {
+        let ty = parse_output_abi("(uint256,address)").unwrap();
+        let output = format!("0x{:064x}{:064x}", 42, 1);
+        let decoded = decode_raw_output(output.clone(), &ty, "\n").unwrap();
+        assert_eq!(decoded, "42\n0x0000000000000000000000000000000000000001");
+        let decoded = decode_raw_output(output, &ty, "\t").unwrap();
+        assert_eq!(decoded, "42\t0x0000000000000000000000000000000000000001");
+
+        let ty = parse_output_abi("uint256").unwrap();
+        assert_eq!(decode_raw_output(format!("0x{:064x}", 7), &ty, "\n").unwrap(), "7");
+
+        // Falls back to the raw output if it can't be decoded.
+        assert_eq!(decode_raw_output("0x01".to_string(), &ty, "\n").unwrap(), "0x01");
+
+        assert!(parse_output_abi("(uint256,").is_err());
+    }
+
+    #[test]
+    fn can_parse_max_trace_bytes() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace"]);
+        assert_eq!(args.max_trace_bytes, DEFAULT_MAX_TRACE_BYTES);
+
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--max-trace-bytes", "0"]);
+        assert_eq!(args.max_trace_bytes, 0);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--max-trace-bytes", "1000"]).is_err());
+    }
+
+    #[test]
+    fn can_resolve_sig_from_abi() {
+        let abi = JsonAbi::parse([
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "function safeTransfer(address to, uint256 amount)",
+            "function safeTransfer(address to, uint256 amount, bytes data)",
+            "function approve(address spender, uint256 amount) returns (bool)",
+            "function approve(uint256 id, address spender) returns (bool)",
+        ])
+        .unwrap();
+        let abis = [abi];
+
+        let to = Address::with_last_byte(1).to_string();
+        let args = CallArgs::parse_from(["foundry-cli", &to, "--sig-from", "Token:transfer", &to]);
+        assert_eq!(args.sig_from.as_deref(), Some("Token:transfer"));
+        // The first function argument is parsed as the positional signature.
+        assert_eq!(args.sig.as_deref(), Some(to.as_str()));
+
+        let resolve = |sig_from, num_args| signature_from_abi(sig_from, num_args, None, &abis);
+        assert_eq!(resolve("Token:transfer", 2).unwrap(), "transfer(address,uint256)(bool)");
+        assert_eq!(
+            resolve("Token:safeTransfer", 3).unwrap(),
+            "safeTransfer(address,uint256,bytes)"
+        );
+
+        let err = resolve("Token:transfer", 1).unwrap_err();
+        assert!(err.to_string().contains("takes 1 arguments"), "{err}");
+        let err = resolve("Token:approve", 2).unwrap_err();
+        assert!(err.to_string().contains("ambiguous"), "{err}");
+        assert!(resolve("Token:burn", 1).is_err());
+        assert!(resolve("transfer", 2).is_err());
+        assert!(signature_from_abi("Token:transfer", 2, None, &[]).is_err());
+    }
+
+    #[test]
+    fn can_parse_returns() {
+        let args = CallArgs::parse_from(["foundry-cli", "--returns", "(uint256)"]);
+        assert_eq!(args.output_abi, Some(DynSolType::Tuple(vec![DynSolType::Uint(256)])));
+
+        let args = CallArgs::parse_from(["foundry-cli", "--output-abi", "address"]);
+        assert_eq!(args.output_abi, Some(DynSolType::Tuple(vec![DynSolType::Address])));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--returns", "(uint256,"]).is_err());
+    }
+
+    #[test]
+    fn can_apply_balance_deltas() {
+        let addr = Address::with_last_byte(1);
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--override-balance", &format!("{addr}:+1ether")]);
+        let current_balances = AddressHashMap::from_iter([(addr, U256::from(1))]);
+        let overrides = args
+            .get_state_overrides(Default::default(), &current_balances, &Default::default(), None)
+            .unwrap();
+        let expected = U256::from(1_000_000_000_000_000_001u64);
+        assert_eq!(overrides[&addr].balance, Some(expected));
+
+        let one_ether = U256::from(1_000_000_000_000_000_000u64);
+        let balance = parse_balance_override(addr, "-0.5ether", Some(&one_ether)).unwrap();
+        assert_eq!(balance, U256::from(500_000_000_000_000_000u64));
+
+        let err = parse_balance_override(addr, "-2ether", Some(&one_ether)).unwrap_err();
+        assert!(err.to_string().contains("underflow"), "{err}");
+    }
+
+    #[test]
+    fn can_resolve_auto_nonces() {
+        let addr = Address::with_last_byte(1);
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--override-nonce", &format!("{addr}:auto+1")]);
+        let current_nonces = AddressHashMap::from_iter([(addr, 5)]);
+        let overrides = args
+            .get_state_overrides(Default::default(), &Default::default(), &current_nonces, None)
+            .unwrap();
+        assert_eq!(overrides[&addr].nonce, Some(6));
+
+        assert_eq!(parse_nonce_override(addr, "auto", Some(&5)).unwrap(), 5);
+        assert_eq!(parse_nonce_override(addr, "auto-5", Some(&5)).unwrap(), 0);
+        assert_eq!(parse_nonce_override(addr, "7", None).unwrap(), 7);
+        assert!(parse_nonce_override(addr, "auto", None).is_err());
+        assert!(parse_nonce_override(addr, "auto1", Some(&5)).is_err());
+
+        let err = parse_nonce_override(addr, "auto-6", Some(&5)).unwrap_err();
+        assert!(err.to_string().contains("out of range"), "{err}");
+    }
+
+    #[test]
+    fn can_parse_inspect_selectors() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--inspect-selectors"]);
+        assert!(args.inspect_selectors);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--inspect-selectors"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_explain() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--explain"]);
+        assert!(args.explain);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--explain"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_decode_events() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--decode-events"]);
+        assert!(args.decode_events);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--decode-events"]).is_err());
+    }
+
+    #[test]
+    fn can_load_batch_calldata() {
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--trace", "--data", "0x01", "--data", "0x0203"]);
+        let batch = load_batch_calldata(&args.data, None).unwrap().unwrap();
+        assert_eq!(batch, vec![Bytes::from([0x01]), Bytes::from([0x02, 0x03])]);
+
+        assert!(load_batch_calldata(&["0x01".to_string()], None).unwrap().is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calls.txt");
+        fs::write(&path, "# first call\n0x01\n\n  0203  \n").unwrap();
+        let batch = load_batch_calldata(&[], Some(&path)).unwrap().unwrap();
+        assert_eq!(batch, vec![Bytes::from([0x01]), Bytes::from([0x02, 0x03])]);
+
+        fs::write(&path, "0x01\nnot hex\n").unwrap();
+        let err = load_batch_calldata(&[], Some(&path)).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--batch-file", "calls.txt"]).is_err());
+    }
+
+    #[test]
+    fn can_load_to_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("targets.txt");
+        let first = Address::with_last_byte(1);
+        let second = Address::with_last_byte(2);
+        fs::write(&path, format!("# targets\n{first}\n\n  {second}  \n")).unwrap();
+        assert_eq!(load_to_list(&path).unwrap(), vec![first, second]);
+
+        fs::write(&path, format!("{first}\nnot an address\n")).unwrap();
+        let err = load_to_list(&path).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+
+        fs::write(&path, format!("{first}\n{second}\n")).unwrap();
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--to-list",
+            path.to_str().unwrap(),
+            "--sig",
+            "balanceOf(address)",
+            &first.to_string(),
+        ]);
+        let input = args.call_input(&Config::default(), None, &[]).unwrap();
+        assert_eq!(input.targets, Some(vec![first, second]));
+        assert!(matches!(input.to, Some(NameOrAddress::Address(to)) if to == first));
+        assert_eq!(input.sig.as_deref(), Some("balanceOf(address)"));
+        assert_eq!(input.args, vec![first.to_string()]);
+
+        let args = CallArgs::parse_from(["foundry-cli", "--to-list", path.to_str().unwrap()]);
+        let err = args.call_input(&Config::default(), None, &[]).unwrap_err();
+        assert!(err.to_string().contains("--sig or --data"), "{err}");
+
+        // The destination can't be given as well.
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--to-list",
+            "targets.txt",
+            &first.to_string(),
+            "--sig",
+            "balanceOf(address)",
+        ])
+        .is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--sig", "balanceOf(address)"]).is_err());
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--to-list",
+            "targets.txt",
+            "--batch-file",
+            "calls.txt",
+            "--trace",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_get_trace_revert_reason() {
+        let args = CallArgs::parse_from(["foundry-cli", "--revert-on-failure"]);
+        assert!(args.revert_on_failure);
+
+        let mut arena = CallTraceArena::default();
+        arena.nodes_mut()[0].trace.output = Revert::from("not owner").abi_encode().into();
+        let arena = SparsedTraceArena { arena, ignored: Default::default() };
+        let mut result = TraceResult {
+            success: true,
+            traces: Some(vec![(TraceKind::Execution, arena)]),
+            gas_used: 0,
+            out_of_gas: false,
+            gas_limit: None,
+        };
+        assert_eq!(trace_revert_reason(&result, &RevertDecoder::new()), None);
+
+        result.success = false;
+        assert_eq!(
+            trace_revert_reason(&result, &RevertDecoder::new()).as_deref(),
+            Some("not owner")
+        );
+
+        result.out_of_gas = true;
+        assert_eq!(
+            trace_revert_reason(&result, &RevertDecoder::new()).as_deref(),
+            Some("out of gas")
+        );
+    }
+
+    #[test]
+    fn can_load_abis() {
+        let json =
+            r#"[{"type":"error","name":"NotOwner","inputs":[{"name":"caller","type":"address"}]}]"#;
+        let abi = load_abi(json).unwrap();
+        assert!(abi.errors.contains_key("NotOwner"));
+
+        let artifact = format!(r#"{{"abi":{json},"bytecode":"0x"}}"#);
+        assert_eq!(load_abi(&artifact).unwrap(), abi);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abi.json");
+        std::fs::write(&path, &artifact).unwrap();
+        assert_eq!(load_abi(path.to_str().unwrap()).unwrap(), abi);
+
+        assert!(load_abi("[{").is_err());
+
+        // Custom errors of the ABI are decoded in reverts.
+        let mut arena = CallTraceArena::default();
+        let error = abi.errors["NotOwner"][0].clone();
+        let data = [error.selector().as_slice(), Address::ZERO.into_word().as_slice()].concat();
+        arena.nodes_mut()[0].trace.output = data.into();
+        let arena = SparsedTraceArena { arena, ignored: Default::default() };
+        let result = TraceResult {
+            success: false,
+            traces: Some(vec![(TraceKind::Execution, arena)]),
+            gas_used: 0,
+            out_of_gas: false,
+            gas_limit: None,
+        };
+        let revert_decoder = RevertDecoder::new().with_abis([&abi]);
+        assert_eq!(
+            trace_revert_reason(&result, &revert_decoder).as_deref(),
+            Some("NotOwner(0x0000000000000000000000000000000000000000)")
+        );
+    }
+
+    #[test]
+    fn can_load_bundle() {
+        use alloy_consensus::{SignableTransaction, TxLegacy};
+        use alloy_network::eip2718::Encodable2718;
+        use alloy_signer::SignerSync;
+
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        let tx = TxLegacy {
+            chain_id: Some(1),
+            to: TxKind::Call(Address::with_last_byte(1)),
+            gas_limit: 21000,
+            ..Default::default()
+        };
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let raw_tx = TxEnvelope::from(tx.into_signed(signature)).encoded_2718();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.json");
+        std::fs::write(&path, format!(r#"["{}"]"#, hex::encode_prefixed(raw_tx))).unwrap();
+        let bundle = load_bundle(&path).unwrap();
+        assert_eq!(bundle.len(), 1);
+        assert_eq!(bundle[0].0, signer.address());
+
+        std::fs::write(&path, r#"["0x1234"]"#).unwrap();
+        let err = load_bundle(&path).unwrap_err();
+        assert_eq!(err.to_string(), "invalid transaction #1 in bundle");
+
+        std::fs::write(&path, "[]").unwrap();
+        assert!(load_bundle(&path).is_err());
+
+        let args = CallArgs::parse_from(["foundry-cli", "--simulate-bundle", "bundle.json"]);
+        assert_eq!(args.simulate_bundle, Some(PathBuf::from("bundle.json")));
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--simulate-bundle",
+            "bundle.json",
+            "--data",
+            "0x"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_estimate() {
+        let args = CallArgs::parse_from(["foundry-cli", "--estimate"]);
+        assert!(args.estimate);
+
+        for flags in [&["--trace"][..], &["--block-timestamp", "1"], &["--block-range", "1:2"]] {
+            let args = ["foundry-cli", "--estimate"].iter().chain(flags);
+            assert!(CallArgs::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn can_trace_with_gas_price() {
+        let mut executor = TracingExecutor::new(
+            Default::default(),
+            None,
+            None,
+            TraceMode::Call,
+            false,
+            Address::ZERO,
+        )
+        .unwrap();
+        // Returns `tx.gasprice`.
+        let to = Address::repeat_byte(1);
+        let code = Bytecode::new_raw(hex!("3a5f5260205ff3").into());
+        executor.set_code(to, code).unwrap();
+        let from = Address::repeat_byte(2);
+        executor.set_balance(from, U256::from(1_000_000_000)).unwrap();
+
+        let tx = TransactionRequest::default().from(from).to(to);
+        assert!(tx_env_with_fees(&executor, &tx, 100_000).unwrap().is_none());
+
+        let legacy = TransactionRequest { gas_price: Some(7), ..tx.clone() };
+        let eip1559 = TransactionRequest {
+            max_fee_per_gas: Some(10),
+            max_priority_fee_per_gas: Some(3),
+            ..tx
+        };
+        for (tx, gas_price) in [(legacy, 7), (eip1559, 3)] {
+            let env = tx_env_with_fees(&executor, &tx, 100_000).unwrap().unwrap();
+            let result = executor.transact_with_env(env).unwrap();
+            assert_eq!(U256::from_be_slice(&result.result), U256::from(gas_price));
+        }
+    }
+
+    #[test]
+    fn can_parse_quick() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--quick"]);
+        assert!(args.quick);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--quick"]).is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--trace", "--quick", "--la"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_include_pending() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--include-pending"]);
+        assert!(args.include_pending);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--include-pending"]).is_err());
+    }
+
+    /// Returns a block with transfers of 1 wei from the signer to `to`, one per given nonce.
+    fn transfer_block(
+        number: u64,
+        signer: &alloy_signer_local::PrivateKeySigner,
+        to: Address,
+        nonces: &[u64],
+    ) -> AnyRpcBlock {
+        use alloy_consensus::{transaction::Recovered, SignableTransaction, TxLegacy};
+        use alloy_rpc_types::{Block, BlockTransactions};
+        use alloy_signer::SignerSync;
+
+        let txs = nonces
+            .iter()
+            .map(|&nonce| {
+                let tx = TxLegacy {
+                    nonce,
+                    to: TxKind::Call(to),
+                    value: U256::from(1),
+                    gas_limit: 21000,
+                    ..Default::default()
+                };
+                let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+                let envelope = TxEnvelope::from(tx.into_signed(signature));
+                alloy_rpc_types::Transaction {
+                    inner: Recovered::new_unchecked(envelope, signer.address()),
+                    block_hash: None,
+                    block_number: Some(number),
+                    transaction_index: None,
+                    effective_gas_price: None,
+                }
+            })
+            .collect();
+        let mut block = Block { transactions: BlockTransactions::Full(txs), ..Default::default() };
+        block.header.inner.number = number;
+        AnyRpcBlock::from(block)
+    }
+
+    #[tokio::test]
+    async fn applies_pending_transactions() {
+        use alloy_provider::{ProviderBuilder, RootProvider};
+        use alloy_transport::mock::Asserter;
+
+        let mut executor = TracingExecutor::new(
+            Default::default(),
+            None,
+            None,
+            TraceMode::Call,
+            false,
+            Address::ZERO,
+        )
+        .unwrap();
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        executor.set_balance(signer.address(), U256::from(10)).unwrap();
+        let to = Address::repeat_byte(1);
+
+        let asserter = Asserter::new();
+        let provider: RootProvider<AnyNetwork> =
+            ProviderBuilder::default().on_mocked_client(asserter.clone());
+
+        // Nodes without a pending block leave the state untouched.
+        asserter.push_success(&serde_json::Value::Null);
+        apply_pending_transactions(&mut executor, &provider).await.unwrap();
+        asserter.push_failure_msg("pending block not supported");
+        apply_pending_transactions(&mut executor, &provider).await.unwrap();
+        assert_eq!(executor.env().block.number, U256::ZERO);
+
+        // The second transaction reuses the nonce of the first one, so it is skipped.
+        asserter.push_success(&transfer_block(5, &signer, to, &[0, 0]));
+        apply_pending_transactions(&mut executor, &provider).await.unwrap();
+        assert_eq!(executor.env().block.number, U256::from(5));
+        assert_eq!(executor.get_balance(to).unwrap(), U256::from(1));
+        assert_eq!(executor.get_nonce(signer.address()).unwrap(), 1);
+    }
+
+    #[test]
+    fn replays_block_transactions() {
+        let mut executor = TracingExecutor::new(
+            Default::default(),
+            None,
+            None,
+            TraceMode::Call,
+            false,
+            Address::ZERO,
+        )
+        .unwrap();
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        executor.set_balance(signer.address(), U256::from(10)).unwrap();
+        let to = Address::repeat_byte(1);
+
+        // Only the transactions preceding the index are replayed.
+        let block = transfer_block(7, &signer, to, &[0, 1, 2]);
+        replay_block_transactions(&mut executor, &block, 2).unwrap();
+        assert_eq!(executor.env().block.number, U256::from(7));
+        assert_eq!(executor.get_balance(to).unwrap(), U256::from(2));
+        assert_eq!(executor.get_nonce(signer.address()).unwrap(), 2);
+    }
+
+    #[test]
+    fn can_parse_tx_index() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--tx-index", "3"]);
+        assert_eq!(args.tx_index, Some(3));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--tx-index", "3"]).is_err());
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--trace",
+            "--tx-index",
+            "3",
+            "--include-pending"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_trace_opcodes() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--trace-opcodes"]);
+        assert!(args.trace_opcodes);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--trace-opcodes"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_block_overrides() {
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--block-timestamp",
+            "1700000000",
+            "--block-number-override",
+            "100",
+        ]);
+        assert_eq!(args.block_timestamp, Some(1700000000));
+        assert_eq!(args.block_number_override, Some(100));
+    }
+
+    #[test]
+    fn can_parse_blob_context() {
+        let hash = B256::with_last_byte(1);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--blob-versioned-hash",
+            hash.to_string().as_str(),
+            "--blob-versioned-hash",
+            hash.to_string().as_str(),
+            "--blob-base-fee",
+            "100",
+        ]);
+        assert_eq!(args.blob_versioned_hashes, vec![hash, hash]);
+        assert_eq!(args.blob_base_fee, Some(100));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--blob-base-fee", "100"]).is_err());
+    }
+
+    #[test]
+    fn can_configure_rpc_retries() {
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--rpc-timeout", "5", "--rpc-retries", "3"]);
+        let figment = Into::<Figment>::into(&args.eth).merge(&args);
+
+        let config = Config::from_provider(figment.clone()).unwrap();
+        assert_eq!(config.eth_rpc_timeout, Some(5));
+        assert_eq!(config.eth_rpc_retries, Some(3));
+
+        let evm_opts = figment.extract::<EvmOpts>().unwrap();
+        assert_eq!(evm_opts.fork_timeout, Some(5));
+        assert_eq!(evm_opts.fork_transient_retries, Some(3));
+    }
+
+    #[test]
+    fn can_disable_fork_cache() {
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--no-fork-cache",
+            "--rpc-url",
+            "https://eth.example.com",
+        ]);
+        let figment = Into::<Figment>::into(&args.eth).merge(&args);
+
+        let config = Config::from_provider(figment).unwrap();
+        assert!(config.no_storage_caching);
+        assert!(!config.enable_caching("https://eth.example.com", 1u64));
+    }
+
+    #[test]
+    fn can_forward_rpc_headers_to_fork() {
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--rpc-header",
+            "X-Api-Key: secret",
+            "--rpc-url",
+            "https://eth.example.com",
+        ]);
+        let figment = Into::<Figment>::into(&args.eth).merge(&args);
+
+        let evm_opts = figment.extract::<EvmOpts>().unwrap();
+        assert_eq!(evm_opts.fork_headers, Some(vec!["X-Api-Key: secret".to_string()]));
+    }
+
+    #[test]
+    fn can_parse_decode_internal_mode() {
+        let to = Address::with_last_byte(1).to_string();
+        let args = CallArgs::parse_from(["foundry-cli", &to, "--trace", "--decode-internal"]);
+        assert_eq!(args.decode_internal, Some(InternalTraceMode::Full));
+
+        let args =
+            CallArgs::parse_from(["foundry-cli", &to, "--trace", "--decode-internal=boundaries"]);
+        assert_eq!(args.decode_internal, Some(InternalTraceMode::Boundaries));
+
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--decode-internal", &to]);
+        assert_eq!(args.decode_internal, Some(InternalTraceMode::Full));
+        assert_eq!(args.to.as_deref(), Some(to.as_str()));
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            &to,
+            "--trace",
+            "--decode-internal=internal"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_watch() {
+        let to = Address::with_last_byte(1).to_string();
+        let args = CallArgs::parse_from(["foundry-cli", &to, "--watch", "--watch-interval", "12"]);
+        assert!(args.watch);
+        assert_eq!(args.watch_interval, Some(12));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", &to, "--watch-interval", "12"]).is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", &to, "--watch", "--trace"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn watch_requires_ws_url() {
+        let config =
+            Config { eth_rpc_url: Some("http://localhost:8545".into()), ..Default::default() };
+        let provider = utils::get_provider(&config).unwrap();
+        let err = new_blocks(&config, &provider, None).await.err().unwrap();
+        assert!(err.to_string().contains("--watch-interval"), "{err}");
+    }
+
+    #[test]
+    fn can_parse_identify_only() {
+        let args = CallArgs::parse_from(["foundry-cli", "vitalik.eth", "--identify-only", "--la"]);
+        assert!(args.identify_only);
+
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "vitalik.eth", "--identify-only"]).is_err()
+        );
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "vitalik.eth",
+            "--identify-only",
+            "--la",
+            "--trace"
+        ])
+        .is_err());
+    }
+
+    /// Returns the artifacts of a `Counter` contract with the given deployed code.
+    fn counter_artifacts(code: Bytes) -> ContractsByArtifact {
+        use foundry_compilers::artifacts::{
+            BytecodeObject, CompactBytecode, CompactContractBytecode, CompactDeployedBytecode,
+        };
+
+        let id = ArtifactId {
+            path: PathBuf::from("out/Counter.sol/Counter.json"),
+            name: "Counter".to_string(),
+            source: PathBuf::from("src/Counter.sol"),
+            version: semver::Version::new(0, 8, 28),
+            build_id: String::new(),
+            profile: "default".to_string(),
+        };
+        let artifact = CompactContractBytecode {
+            abi: Some(Default::default()),
+            bytecode: None,
+            deployed_bytecode: Some(CompactDeployedBytecode {
+                bytecode: Some(CompactBytecode {
+                    object: BytecodeObject::Bytecode(code),
+                    source_map: None,
+                    link_references: Default::default(),
+                }),
+                immutable_references: Default::default(),
+            }),
+        };
+        ContractsByArtifact::new([(id, artifact)])
+    }
+
+    #[test]
+    fn can_override_code_with_artifact() {
+        let addr = Address::with_last_byte(1);
+        let code = Bytes::from_static(&[0x60, 0x00]);
+        let known_contracts = counter_artifacts(code.clone());
+
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--override-code", &format!("{addr}:@Counter")]);
+        assert!(args.has_artifact_code_overrides());
+        let overrides = args.get_state_overrides(
+            Default::default(),
+            &Default::default(),
+            &Default::default(),
+            Some(&known_contracts),
+        );
+        assert_eq!(overrides.unwrap()[&addr].code, Some(code));
+
+        let err = args.get_state_overrides(
+            Default::default(),
+            &Default::default(),
+            &Default::default(),
+            None,
+        );
+        assert!(err.unwrap_err().to_string().contains("--with-local-artifacts"));
+
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--override-code", &format!("{addr}:@Missing")]);
+        let err = args.get_state_overrides(
+            Default::default(),
+            &Default::default(),
+            &Default::default(),
+            Some(&known_contracts),
+        );
+        assert!(err.unwrap_err().to_string().contains("No artifact found"));
+    }
+
+    #[test]
+    fn identifies_artifact_by_code() {
+        let addr = Address::with_last_byte(1);
+        let code = hex!("6080604052348015600e575f5ffd5b50");
+        let known_contracts = counter_artifacts(code.into());
+
+        let (id, score) = identify_code(&known_contracts, addr, &code).unwrap();
+        assert_eq!(id.name, "Counter");
+        assert_eq!(score, 0.0);
+
+        // Codes that differ slightly still match, with a higher score.
+        let mut modified = code;
+        modified[15] = 0x00;
+        let (id, score) = identify_code(&known_contracts, addr, &modified).unwrap();
+        assert_eq!(id.name, "Counter");
+        assert_eq!(score, 1.0 / 16.0);
+
+        let err = identify_code(&known_contracts, addr, &hex!("5f5ffd")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No local artifact matches the code deployed at 0x0000000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn can_parse_gas_by_opcode() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--gas-by-opcode"]);
+        assert!(args.gas_by_opcode);
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--gas-by-opcode"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_gas_report() {
+        let args = CallArgs::parse_from(["foundry-cli", "--trace", "--gas-report"]);
+        assert!(args.gas_report);
+        assert_eq!(args.sort_by, GasReportSort::Name);
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--gas-report",
+            "--sort-by",
+            "avg",
+            "--top",
+            "5",
+        ]);
+        assert_eq!(args.sort_by, GasReportSort::Avg);
+        assert_eq!(args.top, Some(5));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--gas-report"]).is_err());
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--trace", "--top", "5"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_trace_stack_depth_limit() {
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--trace", "--trace-stack-depth-limit", "3"]);
+        assert_eq!(args.trace_stack_depth_limit, Some(3));
+
+        assert!(
+            CallArgs::try_parse_from(["foundry-cli", "--trace-stack-depth-limit", "3"]).is_err()
+        );
+    }
+
+    #[test]
+    fn can_parse_args_json() {
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000001",
+            "transfer(address to, uint256 amount)",
+            "--args-json",
+            r#"{"to":"0x0000000000000000000000000000000000000002","amount":"100"}"#,
+        ]);
+        assert!(args.args.is_empty());
+        assert!(args.args_json.is_some());
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000001",
+            "transfer(address to, uint256 amount)",
+            "0x0000000000000000000000000000000000000002",
+            "--args-json",
+            "{}",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn can_parse_from_tx() {
+        let tx_hash = B256::with_last_byte(1);
+        let args = CallArgs::parse_from(["foundry-cli", "--from-tx", &tx_hash.to_string()]);
+        assert_eq!(args.from_tx, Some(tx_hash));
+
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--from-tx",
+            &tx_hash.to_string(),
+            "--data",
+            "0x",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_auth_with_code_override() {
+        let addr = Address::with_last_byte(1);
+        let args =
+            CallArgs::parse_from(["foundry-cli", "--override-code", &format!("{addr}:0x1234")]);
+        let state_overrides = args
+            .get_state_overrides(Default::default(), &Default::default(), &Default::default(), None)
+            .unwrap();
+
+        assert!(ensure_no_code_override(&state_overrides, addr).is_err());
+        assert!(ensure_no_code_override(&state_overrides, Address::with_last_byte(2)).is_ok());
+    }
+
+    #[test]
+    fn can_parse_access_list_generation() {
+        let args = CallArgs::parse_from(["foundry-cli", "--access-list"]);
+        assert!(matches!(args.tx.access_list, Some(None)));
+
+        let args = CallArgs::parse_from(["foundry-cli", "--access-list", "[]"]);
+        assert!(matches!(args.tx.access_list, Some(Some(_))));
+    }
+
+    #[test]
+    fn can_parse_state_overrides() {
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-balance",
+            "0x123:0x1234",
+            "--override-nonce",
+            "0x123:1",
+            "--override-code",
+            "0x123:0x1234",
+            "--override-state",
+            "0x123:0x1:0x1234",
+        ]);
+
+        assert_eq!(args.balance_overrides, Some(vec!["0x123:0x1234".to_string()]));
+        assert_eq!(args.nonce_overrides, Some(vec!["0x123:1".to_string()]));
+        assert_eq!(args.code_overrides, Some(vec!["0x123:0x1234".to_string()]));
+        assert_eq!(args.state_overrides, Some(vec!["0x123:0x1:0x1234".to_string()]));
+    }
+
+    #[test]
+    fn can_parse_value_units() {
+        let value = U256::from(500_000_000_000_000_000u64);
+
+        let args = CallArgs::parse_from(["foundry-cli", "--value", "0.5ether"]);
+        assert_eq!(args.tx.value, Some(value));
+
+        let args = CallArgs::parse_from(["foundry-cli", "--create", "0x00", "--value", "0.5ether"]);
+        assert!(
+            matches!(args.command, Some(CallSubcommands::Create { value: Some(v), .. }) if v == value)
+        );
+
+        let args = CallArgs::parse_from(["foundry-cli", "--value", "500000000gwei"]);
+        assert_eq!(args.tx.value, Some(value));
+        let args = CallArgs::parse_from(["foundry-cli", "--value", "500000000000000000"]);
+        assert_eq!(args.tx.value, Some(value));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--value", "0.5foo"]).is_err());
+    }
+
+    #[test]
+    fn can_merge_storage_dump_overrides() {
+        let addr = Address::with_last_byte(1);
+        let slot = |n: u8| B256::with_last_byte(n);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-state",
+            &format!("{addr}:0x1:0x1234"),
+        ]);
+
+        let dump = vec![(slot(1), slot(1)), (slot(2), slot(2))];
+        let dumps = StorageDumps { storage: vec![(addr, dump)], ..Default::default() };
+        let overrides = args
+            .get_state_overrides(dumps, &Default::default(), &Default::default(), None)
+            .unwrap();
+        let state = overrides[&addr].state.as_ref().unwrap();
+        assert_eq!(state[&slot(1)], B256::from(U256::from(0x1234)));
+        assert_eq!(state[&slot(2)], slot(2));
+    }
+
+    #[test]
+    fn can_parse_mapping_slot_overrides() {
+        let owner = address!("0xD0074F4E6490ae3f888d1d4f7E3E43326bD3f0f5");
+        assert_eq!(
+            parse_override_slot(&format!("{owner}@2")).unwrap(),
+            U256::from_be_bytes(
+                b256!("0x9525a448a9000053a4d151336329d6563b7e80b24f8e628e95527f218e8ab5fb").0
+            )
+        );
+        assert_eq!(
+            parse_override_slot("42@0x6").unwrap(),
+            U256::from_be_bytes(
+                b256!("0xfc808b0f31a1e6b9cf25ff6289feae9b51017b392cc8e25620a94a38dcdafcc1").0
+            )
+        );
+
+        // `owner@spender@2` is the slot of `mapping[owner][spender]`, e.g. of the allowance of
+        // `spender` in an ERC20 token whose allowances are stored at slot 2.
+        let spender = Address::with_last_byte(1);
+        let outer = parse_override_slot(&format!("{owner}@2")).unwrap();
+        let allowance = parse_override_slot(&format!("{owner}@{spender}@2")).unwrap();
+        assert_eq!(allowance, parse_override_slot(&format!("{spender}@{outer}")).unwrap());
+        assert_eq!(
+            allowance,
+            U256::from_be_bytes(
+                b256!("0x25d8745dc8c164cc3c5cae2c140894263c7e43485a091245216abfc1a2ca7df5").0
+            )
+        );
+
+        let addr = Address::with_last_byte(2);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-state",
+            &format!("{addr}:{owner}@2:0x1234"),
+        ]);
+        let overrides = args
+            .get_state_overrides(Default::default(), &Default::default(), &Default::default(), None)
+            .unwrap();
+        let state = overrides[&addr].state.as_ref().unwrap();
+        assert_eq!(state[&B256::from(outer)], B256::from(U256::from(0x1234)));
+
+        assert!(parse_override_slot("foo@2").is_err());
+    }
+
+    #[test]
+    fn reports_all_invalid_overrides() {
+        let addr = Address::with_last_byte(1);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--check-overrides",
+            "--override-balance",
+            "0x1234:1ether",
+            "--override-balance",
+            &format!("{addr}:+1ether"),
+            "--override-nonce",
+            &format!("{addr}:auto*2"),
+            "--override-code",
+            &format!("{addr}:@Counter"),
+            "--override-state",
+            &format!("{addr}:0x1:0x1"),
+            "--override-state-diff",
+            &format!("{addr}:0x2:0x1"),
+        ]);
+        assert!(args.check_overrides);
+
+        let err = args.validate_overrides().unwrap_err().to_string();
+        assert!(err.contains("--override-balance 0x1234:1ether"), "{err}");
+        assert!(!err.contains("+1ether"), "{err}");
+        assert!(err.contains("--override-nonce"), "{err}");
+        assert!(err.contains("--with-local-artifacts"), "{err}");
+        assert!(err.contains("--override-state-diff"), "{err}");
+        assert_eq!(err.lines().count(), 5, "{err}");
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-nonce",
+            &format!("{addr}:auto-1"),
+            "--override-state-diff",
+            &format!("{addr}:0x2:0x1"),
+        ]);
+        args.validate_overrides().unwrap();
+    }
+
+    #[test]
+    fn reports_invalid_storage_dumps() {
+        let addr = Address::with_last_byte(1);
+        let dir = tempfile::tempdir().unwrap();
+        let account_dump = dir.path().join("account.json");
+        std::fs::write(&account_dump, r#"{"0x1": "0x2"}"#).unwrap();
+        let state_dump = dir.path().join("state.json");
+        let slot = B256::with_last_byte;
+        let dump = StateDump::from([(
+            addr,
+            AccountDump { storage: BTreeMap::from([(slot(1), slot(2))]), ..Default::default() },
+        )]);
+        write_state_dump(&state_dump, &dump).unwrap();
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-storage-dump",
+            &format!("{addr}:{}", account_dump.display()),
+            "--override-storage-dump",
+            state_dump.to_str().unwrap(),
+            "--override-state",
+            &format!("{addr}:0x1:0x1"),
+        ]);
+        let err = args.validate_overrides().unwrap_err().to_string();
+        assert!(err.contains("Invalid slot 0x1"), "{err}");
+        assert!(
+            err.contains(&format!("--override-storage-dump {}", state_dump.display())),
+            "{err}"
+        );
+        assert!(err.contains(&format!("full storage of {addr}")), "{err}");
+        assert_eq!(err.lines().count(), 3, "{err}");
+
+        // A dump only written with balances doesn't conflict with the state of the account.
+        let dump = StateDump::from([(
+            addr,
+            AccountDump { balance: Some(U256::from(1)), ..Default::default() },
+        )]);
+        write_state_dump(&state_dump, &dump).unwrap();
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-storage-dump",
+            state_dump.to_str().unwrap(),
+            "--override-state",
+            &format!("{addr}:0x1:0x1"),
+        ]);
+        args.validate_overrides().unwrap();
+    }
+
+    #[test]
+    fn can_clear_code_override() {
+        let addr = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-code",
+            &format!("{addr}:0x"),
+            "--override-code",
+            &format!("{other}:"),
+        ]);
+
+        let overrides = args
+            .get_state_overrides(Default::default(), &Default::default(), &Default::default(), None)
+            .unwrap();
+        assert_eq!(overrides[&addr].code, Some(Bytes::new()));
+        assert_eq!(overrides[&other].code, Some(Bytes::new()));
+    }
+
+    #[test]
+    fn rejects_storage_dump_with_state_diff() {
+        let addr = Address::with_last_byte(1);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-state-diff",
+            &format!("{addr}:0x1:0x1234"),
+        ]);
+
+        let dumps = StorageDumps { storage: vec![(addr, vec![])], ..Default::default() };
+        let err = args
+            .get_state_overrides(dumps, &Default::default(), &Default::default(), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("--override-storage-dump"));
+    }
+
+    #[test]
+    fn rejects_state_with_state_diff() {
+        let addr = Address::with_last_byte(1);
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-state",
+            &format!("{addr}:0x1:0x1234"),
+            "--override-state-diff",
+            &format!("{addr}:0x2:0x1234"),
+        ]);
+        let err = args
+            .get_state_overrides(Default::default(), &Default::default(), &Default::default(), None)
+            .unwrap_err();
+        assert!(err.to_string().contains(&addr.to_string()));
+
+        // Different accounts can be overridden with both.
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-state",
+            &format!("{addr}:0x1:0x1234"),
+            "--override-state-diff",
+            &format!("{}:0x2:0x1234", Address::with_last_byte(2)),
+        ]);
+        assert!(args
+            .get_state_overrides(Default::default(), &Default::default(), &Default::default(), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn builds_prestate() {
+        let addr = Address::with_last_byte(1);
+        let mut db = Backend::spawn(None).unwrap();
+        db.insert_account_info(
+            addr,
+            AccountInfo { balance: U256::from(10), nonce: 2, ..Default::default() },
+        );
+        let mut account = Account::default();
+        account
+            .storage
+            .insert(U256::from(1), EvmStorageSlot::new_changed(U256::from(3), U256::from(4)));
+        let changeset = StateChangeset::from_iter([(addr, account)]);
+
+        let prestate = build_prestate(&changeset, &db).unwrap();
+        let state = &prestate.0[&addr];
+        assert_eq!(state.balance, Some(U256::from(10)));
+        assert_eq!(state.nonce, Some(2));
+        assert_eq!(state.code, None);
+        assert_eq!(state.storage[&B256::with_last_byte(1)], B256::with_last_byte(3));
+
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--prestate"]).is_err());
+    }
+
+    #[test]
+    fn can_reload_state_dump() {
+        let addr = Address::with_last_byte(1);
+        let created = Address::with_last_byte(2);
+        let slot = |n: u8| B256::with_last_byte(n);
+
+        // A transfer from `addr` creating a contract.
+        let mut changeset = StateChangeset::default();
+        let mut account =
+            Account::from(AccountInfo { balance: U256::from(90), nonce: 1, ..Default::default() });
+        account
+            .storage
+            .insert(U256::from(1), EvmStorageSlot::new_changed(U256::ZERO, U256::from(2)));
+        account.storage.insert(U256::from(2), EvmStorageSlot::new(U256::from(3)));
+        account.mark_touch();
+        changeset.insert(addr, account);
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let mut account = Account::from(AccountInfo {
+            balance: U256::from(10),
+            code_hash: code.hash_slow(),
+            code: Some(code),
+            ..Default::default()
+        });
+        account.mark_touch();
+        account.mark_created();
+        changeset.insert(created, account);
+        let mut changes = StateDump::new();
+        record_state_changes(&mut changes, &changeset);
+        let dumped = AccountDump {
+            balance: Some(U256::from(90)),
+            nonce: Some(1),
+            code: None,
+            storage: BTreeMap::from([(slot(1), slot(2))]),
+        };
+        assert_eq!(changes[&addr], dumped);
+        assert_eq!(changes[&created].code, Some(Bytes::from_static(&[0x60, 0x00])));
+        assert!(changes[&created].storage.is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write_state_dump(&path, &changes).unwrap();
+        assert_eq!(read_state_dump(&path).unwrap(), changes);
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override-storage-dump",
+            path.to_str().unwrap(),
+            "--override-state-diff",
+            &format!("{addr}:0x3:0x4"),
+            "--override-state",
+            &format!("{created}:0x1:0x1"),
+        ]);
+        let dumps = args.load_storage_dumps().unwrap();
+        assert_eq!(dumps.state, changes);
+
+        let overrides = args
+            .get_state_overrides(dumps, &Default::default(), &Default::default(), None)
+            .unwrap();
+        let account = &overrides[&addr];
+        assert_eq!(account.balance, Some(U256::from(90)));
+        assert_eq!(account.nonce, Some(1));
+        let state_diff = account.state_diff.as_ref().unwrap();
+        assert_eq!(state_diff[&slot(1)], slot(2));
+        assert_eq!(state_diff[&slot(3)], slot(4));
+        assert_eq!(overrides[&created].code, Some(Bytes::from_static(&[0x60, 0x00])));
+        assert!(overrides[&created].state_diff.is_none());
+
+        // The state of a continued call is merged into the previous one.
+        let changes = StateDump::from([(
+            addr,
+            AccountDump {
+                balance: Some(U256::from(80)),
+                storage: BTreeMap::from([(slot(1), slot(5)), (slot(3), slot(6))]),
+                ..Default::default()
+            },
+        )]);
+        write_continued_state(&path, changes).unwrap();
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--continue-state",
+            path.to_str().unwrap(),
+        ]);
+        let dumps = args.load_storage_dumps().unwrap();
+        let continued = &dumps.state[&addr];
+        assert_eq!(continued.balance, Some(U256::from(80)));
+        assert_eq!(continued.nonce, Some(1));
+        assert_eq!(continued.storage, BTreeMap::from([(slot(1), slot(5)), (slot(3), slot(6))]));
+        assert!(dumps.state[&created].code.is_some());
+
+        let lock = StateLock::acquire(&path).unwrap();
+        assert!(StateLock::acquire(&path).is_err());
+        drop(lock);
+        assert!(StateLock::acquire(&path).is_ok());
+    }
+
+    #[test]
+    fn can_load_fork_state() {
+        let addr = Address::with_last_byte(1);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anvil-state.json");
+        let state = serde_json::json!({
+            "block": {
+                "number": "0x2",
+                "coinbase": "0x0000000000000000000000000000000000000000",
+                "timestamp": "0x66cdcc2b",
+                "gas_limit": "0x1c9c380",
+                "basefee": "0x342a1c58",
+                "difficulty": "0x0",
+                "prevrandao": "0xdb639d7f8af4f0ff2aa9cc49861820e72f5f8bfeeed677d1e3569f6b1625df4a",
+                "blob_excess_gas_and_price": { "excess_blob_gas": 0, "blob_gasprice": 1 }
+            },
+            "accounts": {
+                "0x0000000000000000000000000000000000000001": {
+                    "nonce": 3,
+                    "balance": "0x64",
+                    "code": "0x6000",
+                    "storage": { "0x1": "0x2" }
+                }
+            },
+            "best_block_number": "0x2",
+            "blocks": [],
+            "transactions": []
+        });
+        std::fs::write(&path, state.to_string()).unwrap();
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--fork-state-file",
+            path.to_str().unwrap(),
+        ]);
+        let state = ForkState::load(args.fork_state_file.as_deref().unwrap()).unwrap();
+        assert_eq!(state.nonce(addr), 3);
+        assert_eq!(state.nonce(Address::ZERO), 0);
+
+        let (env, fork, chain, _) = state.evm_material(EvmOpts::default());
+        assert!(fork.is_none());
+        assert_eq!(chain, Some(DEV_CHAIN_ID.into()));
+        assert_eq!(env.block.number, U256::from(2));
+        assert_eq!(env.block.timestamp, U256::from(0x66cdcc2b));
+
+        let overrides = state.state_overrides();
+        let account = &overrides[&addr];
+        assert_eq!(account.balance, Some(U256::from(100)));
+        assert_eq!(account.code, Some(Bytes::from_static(&[0x60, 0x00])));
+        let storage = account.state.as_ref().unwrap();
+        assert_eq!(storage[&B256::with_last_byte(1)], B256::with_last_byte(2));
+
+        // The state is loaded instead of the fork of the RPC, so the call can't be made against it.
+        assert!(CallArgs::try_parse_from([
+            "foundry-cli",
+            "--trace",
+            "--fork-state-file",
+            path.to_str().unwrap(),
+            "--block",
+            "1",
+        ])
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn resolves_overrides_against_fork_state() {
+        let addr = Address::with_last_byte(1);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anvil-state.json");
+        let state = serde_json::json!({
+            "accounts": {
+                "0x0000000000000000000000000000000000000001": {
+                    "nonce": 3,
+                    "balance": "0x64",
+                    "code": "0x",
+                    "storage": {}
+                }
+            }
+        });
+        std::fs::write(&path, state.to_string()).unwrap();
+
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--trace",
+            "--fork-state-file",
+            path.to_str().unwrap(),
+            "--override-balance",
+            &format!("{addr}:+1ether"),
+            "--override-nonce",
+            &format!("{addr}:auto+1"),
+        ]);
+        // Nothing listens on the RPC, so that the call fails if anything is fetched from it.
+        let config =
+            Config { eth_rpc_url: Some("http://127.0.0.1:1".to_string()), ..Default::default() };
+        let ctx = CallContext {
+            provider: utils::get_provider(&config).unwrap(),
+            config,
+            evm_opts: EvmOpts::default(),
+            block: None,
+            fork_state: Some(ForkState::load(&path).unwrap()),
+        };
+        let overrides = args.resolve_state_overrides(&ctx, None).await.unwrap();
+        assert_eq!(overrides[&addr].balance, Some(U256::from(1_000_000_000_000_000_100u64)));
+        assert_eq!(overrides[&addr].nonce, Some(4));
+    }
+
+    /// Writes a fork state with the given code deployed at `to` and the `funded` accounts holding
+    /// 1 ether, and returns the arguments tracing a call to `to` against it.
+    ///
+    /// Nothing listens on the RPC, so that the call fails if anything is fetched from it.
+    fn fork_state_call(dir: &Path, to: Address, code: &str, funded: &[Address]) -> Vec<String> {
+        let account = |code: &str, balance: &str| serde_json::json!({ "nonce": 0, "balance": balance, "code": code, "storage": {} });
+        let mut accounts = serde_json::Map::new();
+        accounts.insert(to.to_string(), account(code, "0x0"));
+        for address in funded {
+            accounts.insert(address.to_string(), account("0x", "0xde0b6b3a7640000"));
+        }
+        let state = serde_json::json!({
+            "block": {
+                "number": "0x2",
+                "coinbase": "0x0000000000000000000000000000000000000000",
+                "timestamp": "0x66cdcc2b",
+                "gas_limit": "0x1c9c380",
+                "basefee": "0x342a1c58",
+                "difficulty": "0x0",
+                "prevrandao": null
+            },
+            "accounts": accounts
+        });
+        let path = dir.join("anvil-state.json");
+        std::fs::write(&path, state.to_string()).unwrap();
+
+        let to = to.to_string();
+        let path = path.to_str().unwrap();
+        [
+            "foundry-cli",
+            &to,
+            "--trace",
+            "--fork-state-file",
+            path,
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    /// Returns the output of the top-level call traced with the given arguments.
+    async fn traced_output(args: &[String]) -> Bytes {
+        let result = CallArgs::parse_from(args).run_traced().await.unwrap();
+        let traces = result.traces.unwrap();
+        traces[0].1.arena.nodes()[0].trace.output.clone()
+    }
+
+    #[tokio::test]
+    async fn applies_block_overrides() {
+        use alloy_sol_types::SolValue;
+
+        let to = Address::repeat_byte(1);
+        let dir = tempfile::tempdir().unwrap();
+        // Returns `block.timestamp` and `block.number`.
+        let mut args = fork_state_call(dir.path(), to, "0x425f524360205260405ff3", &[]);
+        let output = traced_output(&args).await;
+        assert_eq!(
+            output,
+            Bytes::from((U256::from(0x66cdcc2b), U256::from(2)).abi_encode_params())
+        );
+
+        args.extend(
+            ["--block-timestamp", "1000", "--block-number-override", "7"].map(String::from),
+        );
+        let output = traced_output(&args).await;
+        assert_eq!(output, Bytes::from((U256::from(1000), U256::from(7)).abi_encode_params()));
+    }
+
+    #[tokio::test]
+    async fn traces_impersonated_call() {
+        let to = Address::repeat_byte(1);
+        let sender = Address::repeat_byte(2);
+        let dir = tempfile::tempdir().unwrap();
+        // Returns `msg.sender`.
+        let mut args = fork_state_call(dir.path(), to, "0x335f5260205ff3", &[]);
+        args.extend(["--impersonate".to_string(), sender.to_string()]);
+        assert_eq!(traced_output(&args).await, Bytes::from(sender.into_word()));
+    }
+
+    #[tokio::test]
+    async fn funds_sender() {
+        let to = Address::repeat_byte(1);
+        let sender = Address::repeat_byte(2);
+        let dir = tempfile::tempdir().unwrap();
+        // Returns the balance of `msg.sender`, which replaces the one of the fork state.
+        let mut args = fork_state_call(dir.path(), to, "0x33315f5260205ff3", &[sender]);
+        args.extend(
+            ["--impersonate", &sender.to_string(), "--fund-sender", "1gwei"].map(String::from),
+        );
+        let balance = B256::from(U256::from(1_000_000_000));
+        assert_eq!(traced_output(&args).await, Bytes::from(balance));
+
+        args.extend(["--override-balance".to_string(), format!("{sender}:1")]);
+        let err = CallArgs::parse_from(&args).run_traced().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Cannot use both --fund-sender and --override-balance for {sender}")
+        );
+    }
+
+    #[tokio::test]
+    async fn overrides_precompile_output() {
+        let to = Address::repeat_byte(1);
+        let dir = tempfile::tempdir().unwrap();
+        // Returns the output of a `STATICCALL` to `ecrecover` without input.
+        let mut args = fork_state_call(dir.path(), to, "0x60205f5f5f60015afa5060205ff3", &[]);
+        assert_eq!(traced_output(&args).await, Bytes::from(B256::ZERO));
+
+        let output = B256::with_last_byte(0x42);
+        args.extend(["--override-precompile".to_string(), format!("{EC_RECOVER}:{output}")]);
+        assert_eq!(traced_output(&args).await, Bytes::from(output));
+    }
+
+    #[tokio::test]
+    async fn overrides_call_return() {
+        let to = Address::repeat_byte(1);
+        let callee = Address::repeat_byte(2);
+        let dir = tempfile::tempdir().unwrap();
+        // Returns the output of a `STATICCALL` to `callee` with the selector `0x12345678`.
+        let code = format!("0x631234567860e01b5f5260205f60045f73{callee:x}5afa5060205ff3");
+        let args = fork_state_call(dir.path(), to, &code, &[]);
+        let output = B256::with_last_byte(0x42);
+        let stubbed = |selector: &str| {
+            let mut args = args.clone();
+            args.extend([
+                "--call-override-return".to_string(),
+                format!("{callee}:{selector}:{output}"),
+            ]);
+            args
+        };
+        assert_eq!(traced_output(&stubbed("0x12345678")).await, Bytes::from(output));
+        // Calls with other selectors are not stubbed.
+        assert_eq!(traced_output(&stubbed("foo()")).await, Bytes::from(B256::ZERO));
+    }
+
+    #[tokio::test]
+    async fn traces_call_with_blobs() {
+        use alloy_sol_types::SolValue;
+
+        let to = Address::repeat_byte(1);
+        let sender = Address::repeat_byte(2);
+        let dir = tempfile::tempdir().unwrap();
+        // Returns `blobhash(0)` and `block.blobbasefee`.
+        let mut args = fork_state_call(dir.path(), to, "0x5f495f524a60205260405ff3", &[sender]);
+        let hash = b256!("0x0100000000000000000000000000000000000000000000000000000000000001");
+        args.extend(
+            [
+                "--impersonate",
+                &sender.to_string(),
+                "--blob-versioned-hash",
+                &hash.to_string(),
+                "--blob-base-fee",
+                "3",
+            ]
+            .map(String::from),
+        );
+        assert_eq!(
+            traced_output(&args).await,
+            Bytes::from((hash, U256::from(3)).abi_encode_params())
+        );
+    }
+
+    #[test]
+    fn can_parse_storage_dump() {
+        let path = Path::new("dump.json");
+        let dump = BTreeMap::from([(B256::with_last_byte(1).to_string(), "0x2".to_string())]);
+        let storage = parse_storage_dump(path, dump).unwrap();
+        assert_eq!(storage, vec![(B256::with_last_byte(1), B256::with_last_byte(2))]);
+
+        let dump = BTreeMap::from([("0x1".to_string(), "0x2".to_string())]);
+        assert!(parse_storage_dump(path, dump).is_err());
     }
 
     #[test]
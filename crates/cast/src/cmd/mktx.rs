@@ -93,7 +93,7 @@ pub async fn run(self) -> Result<()> {
 
         let tx_builder = CastTxBuilder::new(provider, tx, &config)
             .await?
-            .with_to(to)
+            .with_to(to, None)
             .await?
             .with_code_sig_and_args(code, sig, args)
             .await?
@@ -21,5 +21,6 @@
 pub mod run;
 pub mod send;
 pub mod storage;
+pub mod trace_render;
 pub mod txpool;
 pub mod wallet;
@@ -10,7 +10,7 @@
 use eyre::{Result, WrapErr};
 use foundry_cli::{
     opts::{EtherscanOpts, RpcOpts},
-    utils::{handle_traces, init_progress, TraceResult},
+    utils::{handle_traces, init_progress, TraceOptions, TraceResult},
 };
 use foundry_common::{is_known_system_sender, shell, SYSTEM_TRANSACTION_TYPE};
 use foundry_compilers::artifacts::EvmVersion;
@@ -171,13 +171,11 @@ pub async fn run(self) -> Result<()> {
             apply_chain_and_block_specific_env_changes::<AnyNetwork>(&mut env, block);
         }
 
+        let decode_internal =
+            if self.decode_internal { InternalTraceMode::Full } else { InternalTraceMode::None };
         let trace_mode = TraceMode::Call
             .with_debug(self.debug)
-            .with_decode_internal(if self.decode_internal {
-                InternalTraceMode::Full
-            } else {
-                InternalTraceMode::None
-            })
+            .with_decode_internal(decode_internal)
             .with_state_changes(shell::verbosity() > 4);
         let mut executor = TracingExecutor::new(
             env.clone(),
@@ -268,16 +266,15 @@ pub async fn run(self) -> Result<()> {
             }
         };
 
-        handle_traces(
-            result,
-            &config,
-            chain,
-            self.label,
-            self.with_local_artifacts,
-            self.debug,
-            self.decode_internal,
-        )
-        .await?;
+        let options = TraceOptions {
+            labels: self.label,
+            with_local_artifacts: self.with_local_artifacts,
+            with_etherscan: true,
+            debug: self.debug,
+            decode_internal,
+            ..Default::default()
+        };
+        handle_traces(result, &config, chain, options).await?;
 
         Ok(())
     }
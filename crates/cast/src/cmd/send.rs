@@ -30,6 +30,8 @@ pub struct SendTxArgs {
     sig: Option<String>,
 
     /// The arguments of the function to call.
+    ///
+    /// One argument can be `-` to read it from stdin, e.g. for large `bytes` arguments.
     args: Vec<String>,
 
     /// Only print the transaction hash and exit immediately.
@@ -121,7 +123,7 @@ pub async fn run(self) -> eyre::Result<()> {
 
         let builder = CastTxBuilder::new(&provider, tx, &config)
             .await?
-            .with_to(to)
+            .with_to(to, None)
             .await?
             .with_code_sig_and_args(code, sig, args)
             .await?
@@ -0,0 +1,205 @@
+use crate::cmd::call::{ensure_etherscan_key, load_abi};
+use alloy_primitives::Address;
+use clap::{Parser, ValueHint};
+use eyre::Result;
+use foundry_cli::{
+    opts::EtherscanOpts,
+    utils::{handle_traces, GasReportSort, LoadConfig, TraceFormat, TraceOptions, TraceResult},
+};
+use foundry_common::{fs, shell};
+use foundry_config::Chain;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The traces of the calls executed by `cast call --trace`, as written with `--trace-export`.
+///
+/// The traces are saved before being identified and decoded, so that they can be rendered again
+/// with different labels and identifiers, without network access unless the ABIs are fetched from
+/// Etherscan.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceExport {
+    /// The chain the calls were executed on.
+    pub chain: Option<Chain>,
+    /// The traces of the calls, in order of execution.
+    pub traces: Vec<TraceResult>,
+    /// The destinations of the calls, when executed against each address of `--to-list`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<Address>>,
+}
+
+impl TraceExport {
+    /// Writes the traces to the given path.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write_json_file(path, self)?;
+        if !shell::is_json() {
+            sh_eprintln!("Traces written to {}", path.display())?;
+        }
+        Ok(())
+    }
+
+    /// Reads the traces written to the given path.
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(fs::read_json_file(path)?)
+    }
+}
+
+/// CLI arguments for `cast trace-render`.
+#[derive(Clone, Debug, Parser)]
+pub struct TraceRenderArgs {
+    /// The file the traces were written to with `cast call --trace --trace-export <PATH>`.
+    #[arg(value_hint = ValueHint::FilePath, value_name = "PATH")]
+    path: PathBuf,
+
+    /// Labels to apply to the traces; format: `address:label`.
+    #[arg(long)]
+    labels: Vec<String>,
+
+    /// Use current project artifacts for trace decoding.
+    #[arg(long, visible_alias = "la")]
+    with_local_artifacts: bool,
+
+    /// Fetch the ABIs of the traced contracts that could not be identified locally from
+    /// Etherscan.
+    ///
+    /// Requires an Etherscan API key.
+    #[arg(long)]
+    abi_from_etherscan: bool,
+
+    /// Additional ABIs to decode the traces with.
+    ///
+    /// Either a path to a JSON ABI or artifact file, or an inline JSON ABI. Can be repeated.
+    #[arg(long = "abi", value_name = "PATH_OR_JSON")]
+    abis: Vec<String>,
+
+    /// The format of the printed trace.
+    #[arg(long, value_enum, default_value_t)]
+    trace_format: TraceFormat,
+
+    /// Print a gas report of the trace, grouped by contract and function.
+    #[arg(long)]
+    gas_report: bool,
+
+    /// The order of the contracts and functions of the gas report.
+    #[arg(long, value_enum, default_value_t, requires = "gas_report")]
+    sort_by: GasReportSort,
+
+    /// Only print the first functions of each contract of the gas report, in the order of
+    /// `--sort-by`.
+    #[arg(long, value_name = "N", requires = "gas_report")]
+    top: Option<usize>,
+
+    /// Print the events emitted during the call, decoded and grouped by emitting contract.
+    #[arg(long)]
+    decode_events: bool,
+
+    /// Narrate the calls in plain language, with one sentence per call.
+    #[arg(long)]
+    explain: bool,
+
+    #[command(flatten)]
+    etherscan: EtherscanOpts,
+}
+
+impl TraceRenderArgs {
+    pub async fn run(self) -> Result<()> {
+        let Self {
+            path,
+            labels,
+            with_local_artifacts,
+            abi_from_etherscan,
+            abis,
+            trace_format,
+            gas_report,
+            sort_by,
+            top,
+            decode_events,
+            explain,
+            etherscan,
+        } = self;
+
+        let TraceExport { chain, traces, targets } = TraceExport::read(&path)?;
+        let config = etherscan.load_config()?;
+        // The chain of the command line takes precedence over the one the calls were executed on.
+        let chain = etherscan.chain.or(chain);
+        if abi_from_etherscan {
+            ensure_etherscan_key(&config, chain)?;
+        }
+        let abis = abis.iter().map(|abi| load_abi(abi)).collect::<Result<Vec<_>>>()?;
+
+        let batch = traces.len() > 1;
+        for (i, trace) in traces.into_iter().enumerate() {
+            if batch && !shell::is_json() {
+                if i > 0 {
+                    sh_println!()?;
+                }
+                match targets.as_ref().and_then(|targets| targets.get(i)) {
+                    Some(target) => sh_println!("{target}:")?,
+                    None => sh_println!("Call #{}:", i + 1)?,
+                }
+            }
+            let options = TraceOptions {
+                labels: labels.clone(),
+                with_local_artifacts,
+                with_etherscan: abi_from_etherscan,
+                abis: &abis,
+                trace_format,
+                gas_report,
+                gas_report_sort: sort_by,
+                gas_report_top: top,
+                decode_events,
+                explain,
+                ..Default::default()
+            };
+            handle_traces(trace, &config, chain, options).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_evm::traces::{SparsedTraceArena, TraceKind};
+
+    #[test]
+    fn can_reload_trace_export() {
+        let mut arena =
+            SparsedTraceArena { arena: Default::default(), ignored: Default::default() };
+        arena.nodes_mut()[0].trace.address = Address::with_last_byte(1);
+        let export = TraceExport {
+            chain: Some(Chain::mainnet()),
+            traces: vec![TraceResult {
+                success: true,
+                traces: Some(vec![(TraceKind::Execution, arena)]),
+                gas_used: 21000,
+                out_of_gas: false,
+                gas_limit: None,
+            }],
+            targets: None,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        export.write(&path).unwrap();
+
+        let TraceExport { chain, traces, targets } = TraceExport::read(&path).unwrap();
+        assert_eq!(chain, Some(Chain::mainnet()));
+        assert!(targets.is_none());
+        let [trace] = &traces[..] else { panic!("expected a single trace") };
+        assert!(trace.success);
+        assert_eq!(trace.gas_used, 21000);
+        let [(kind, arena)] = &trace.traces.as_deref().unwrap()[..] else { panic!("no arena") };
+        assert_eq!(*kind, TraceKind::Execution);
+        assert_eq!(arena.nodes()[0].trace.address, Address::with_last_byte(1));
+    }
+
+    #[test]
+    fn can_parse_trace_render() {
+        let args =
+            TraceRenderArgs::parse_from(["foundry-cli", "trace.json", "--la", "--labels", "0x1:A"]);
+        assert_eq!(args.path, PathBuf::from("trace.json"));
+        assert!(args.with_local_artifacts);
+        assert_eq!(args.labels, vec!["0x1:A".to_string()]);
+    }
+}
@@ -32,3 +32,23 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 }
 
 impl std::error::Error for FunctionSignatureError {}
+
+/// An error thrown by `cast call --revert-on-failure` when the simulated call reverts.
+#[derive(Clone, Debug)]
+pub struct CallRevertedError {
+    /// The decoded revert reason.
+    pub reason: String,
+}
+
+impl CallRevertedError {
+    /// The exit code of the process when the call reverted, distinct from other failures.
+    pub const EXIT_CODE: i32 = 2;
+}
+
+impl fmt::Display for CallRevertedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "execution reverted: {}", self.reason)
+    }
+}
+
+impl std::error::Error for CallRevertedError {}
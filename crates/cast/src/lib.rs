@@ -4,12 +4,12 @@
 
 use alloy_consensus::TxEnvelope;
 use alloy_dyn_abi::{DynSolType, DynSolValue, FunctionExt};
-use alloy_json_abi::Function;
+use alloy_json_abi::{Function, Param};
 use alloy_network::{AnyNetwork, AnyRpcTransaction};
 use alloy_primitives::{
     hex,
     utils::{keccak256, ParseUnits, Unit},
-    Address, Keccak256, Selector, TxHash, TxKind, B256, I256, U256, U64,
+    Address, Bytes, Keccak256, Selector, TxHash, TxKind, B256, I256, U256, U64,
 };
 use alloy_provider::{
     network::eip2718::{Decodable2718, Encodable2718},
@@ -17,10 +17,11 @@
 };
 use alloy_rlp::Decodable;
 use alloy_rpc_types::{
-    state::StateOverride, BlockId, BlockNumberOrTag, Filter, TransactionRequest,
+    state::StateOverride, BlockId, BlockNumberOrTag, BlockOverrides, Filter, TransactionRequest,
 };
 use alloy_serde::WithOtherFields;
 use alloy_sol_types::sol;
+use alloy_transport::TransportResult;
 use base::{Base, NumberWithBase, ToBase};
 use chrono::DateTime;
 use eyre::{Context, ContextCompat, OptionExt, Result};
@@ -57,10 +58,13 @@
 pub mod opts;
 
 pub mod base;
+pub mod call_result;
 pub mod errors;
 mod rlp_converter;
 pub mod tx;
 
+use call_result::{CallResult, CallRevertReason};
+use foundry_evm::decode::RevertDecoder;
 use rlp_converter::Item;
 
 #[macro_use]
@@ -81,6 +85,10 @@
 
 pub struct Cast<P> {
     provider: P,
+    /// Whether decoded tuples and arrays are formatted over multiple indented lines.
+    pretty_output: bool,
+    /// Whether the values of decoded call outputs are separated by tabs instead of new lines.
+    tsv_output: bool,
 }
 
 impl<P: Provider<AnyNetwork>> Cast<P> {
@@ -100,7 +108,25 @@ impl<P: Provider<AnyNetwork>> Cast<P> {
     /// # }
     /// ```
     pub fn new(provider: P) -> Self {
-        Self { provider }
+        Self { provider, pretty_output: false, tsv_output: false }
+    }
+
+    /// Sets whether decoded tuples and arrays are formatted over multiple indented lines, with the
+    /// fields of tuples labeled with the names of the function's return values.
+    ///
+    /// By default, each return value is formatted on a single line.
+    pub fn with_pretty_output(mut self, yes: bool) -> Self {
+        self.pretty_output = yes;
+        self
+    }
+
+    /// Sets whether decoded call outputs are formatted on a single line, with the return values
+    /// separated by tabs, e.g. to split them in shell scripts.
+    ///
+    /// Takes precedence over [`with_pretty_output`](Self::with_pretty_output).
+    pub fn with_tsv_output(mut self, yes: bool) -> Self {
+        self.tsv_output = yes;
+        self
     }
 
     /// Makes a read-only call to the specified address
@@ -149,46 +175,121 @@ pub async fn call(
         block: Option<BlockId>,
         state_override: StateOverride,
     ) -> Result<String> {
-        let res = self
-            .provider
-            .call(req.clone())
+        self.call_with_overrides(req, func, block, state_override, None).await
+    }
+
+    /// Same as [`call`](Self::call), but also overrides the fields of the block the call is
+    /// executed in, e.g. its timestamp or number.
+    pub async fn call_with_overrides(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        func: Option<&Function>,
+        block: Option<BlockId>,
+        state_override: StateOverride,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<String> {
+        let res = self.eth_call(req, block, state_override, block_overrides).await?;
+
+        self.format_call_output(req, func, block, res).await
+    }
+
+    /// Makes a read-only call to the specified address, returning the output along with the gas
+    /// estimated for the call.
+    ///
+    /// The state overrides are applied to both the call and the gas estimation.
+    pub async fn call_with_gas(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        func: Option<&Function>,
+        block: Option<BlockId>,
+        state_override: StateOverride,
+    ) -> Result<(String, u64)> {
+        let (res, gas) = futures::try_join!(
+            self.eth_call(req, block, state_override.clone(), None),
+            self.estimate_gas(req, block, state_override),
+        )?;
+        let output = self.format_call_output(req, func, block, res).await?;
+        Ok((output, gas))
+    }
+
+    /// Estimates the gas used by a transaction, with the given state overrides applied.
+    pub async fn estimate_gas(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        block: Option<BlockId>,
+        state_override: StateOverride,
+    ) -> TransportResult<u64> {
+        self.provider
+            .estimate_gas(req.clone())
             .block(block.unwrap_or_default())
             .overrides(state_override)
-            .await?;
+            .await
+    }
 
-        let mut decoded = vec![];
-
-        if let Some(func) = func {
-            // decode args into tokens
-            decoded = match func.abi_decode_output(res.as_ref(), false) {
-                Ok(decoded) => decoded,
-                Err(err) => {
-                    // ensure the address is a contract
-                    if res.is_empty() {
-                        // check that the recipient is a contract that can be called
-                        if let Some(TxKind::Call(addr)) = req.to {
-                            if let Ok(code) = self
-                                .provider
-                                .get_code_at(addr)
-                                .block_id(block.unwrap_or_default())
-                                .await
-                            {
-                                if code.is_empty() {
-                                    eyre::bail!("contract {addr:?} does not have any code")
-                                }
-                            }
-                        } else if Some(TxKind::Create) == req.to {
-                            eyre::bail!("tx req is a contract deployment");
-                        } else {
-                            eyre::bail!("recipient is None");
-                        }
-                    }
-                    return Err(err).wrap_err(
-                        "could not decode output; did you specify the wrong function return data type?"
-                    );
-                }
-            };
+    /// Makes a read-only call to the specified address, returning a structured result that
+    /// distinguishes reverts from other failures.
+    ///
+    /// The revert data is decoded as `Error(string)`, `Panic(uint256)`, or as one of the custom
+    /// errors of the given decoder. Errors that are not reverts, e.g. RPC failures, are returned as
+    /// errors.
+    pub async fn call_result(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        block: Option<BlockId>,
+        state_override: StateOverride,
+        block_overrides: Option<BlockOverrides>,
+        revert_decoder: &RevertDecoder,
+    ) -> Result<CallResult> {
+        let res = self.eth_call(req, block, state_override, block_overrides).await;
+
+        match res {
+            Ok(output) => Ok(CallResult::Success { output }),
+            Err(err) => {
+                let Some(data) = err.as_error_resp().and_then(|payload| payload.as_revert_data())
+                else {
+                    return Err(err.into());
+                };
+                let reason = CallRevertReason::decode(&data, revert_decoder);
+                Ok(CallResult::Revert { data, reason })
+            }
         }
+    }
+
+    /// Sends an `eth_call`, with the block overrides as the fourth parameter if any.
+    async fn eth_call(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        block: Option<BlockId>,
+        state_override: StateOverride,
+        block_overrides: Option<BlockOverrides>,
+    ) -> TransportResult<Bytes> {
+        let Some(block_overrides) = block_overrides else {
+            return self
+                .provider
+                .call(req.clone())
+                .block(block.unwrap_or_default())
+                .overrides(state_override)
+                .await;
+        };
+        self.provider
+            .client()
+            .request("eth_call", (req, block.unwrap_or_default(), state_override, block_overrides))
+            .await
+    }
+
+    /// Formats the raw output of a call, decoding it with the function's return types if given.
+    pub async fn format_call_output(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        func: Option<&Function>,
+        block: Option<BlockId>,
+        res: Bytes,
+    ) -> Result<String> {
+        let decoded = match func {
+            Some(func) => self.decode_call_output(req, func, block, &res).await?,
+            None => vec![],
+        };
+        let outputs = func.map(|func| func.outputs.as_slice()).unwrap_or_default();
 
         // handle case when return type is not specified
         Ok(if decoded.is_empty() {
@@ -196,12 +297,64 @@ pub async fn call(
         } else if shell::is_json() {
             let tokens = decoded.iter().map(format_token_raw).collect::<Vec<_>>();
             serde_json::to_string_pretty(&tokens).unwrap()
-        } else {
+        } else if !self.pretty_output || self.tsv_output {
             // seth compatible user-friendly return type conversions
-            decoded.iter().map(format_token).collect::<Vec<_>>().join("\n")
+            let separator = if self.tsv_output { "\t" } else { "\n" };
+            decoded.iter().map(format_token).collect::<Vec<_>>().join(separator)
+        } else {
+            decoded
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let components = outputs.get(i).map_or(&[][..], |param| &param.components);
+                    format_token_pretty(&name_components(value, components))
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
         })
     }
 
+    /// Decodes the raw output of a call with the function's return types.
+    ///
+    /// If the output can't be decoded because it is empty, checks whether the recipient is a
+    /// contract to report a more helpful error.
+    pub async fn decode_call_output(
+        &self,
+        req: &WithOtherFields<TransactionRequest>,
+        func: &Function,
+        block: Option<BlockId>,
+        res: &Bytes,
+    ) -> Result<Vec<DynSolValue>> {
+        match func.abi_decode_output(res.as_ref(), false) {
+            Ok(decoded) => Ok(decoded),
+            Err(err) => {
+                // ensure the address is a contract
+                if res.is_empty() {
+                    // check that the recipient is a contract that can be called
+                    if let Some(TxKind::Call(addr)) = req.to {
+                        if let Ok(code) = self
+                            .provider
+                            .get_code_at(addr)
+                            .block_id(block.unwrap_or_default())
+                            .await
+                        {
+                            if code.is_empty() {
+                                eyre::bail!("contract {addr:?} does not have any code")
+                            }
+                        }
+                    } else if Some(TxKind::Create) == req.to {
+                        eyre::bail!("tx req is a contract deployment");
+                    } else {
+                        eyre::bail!("recipient is None");
+                    }
+                }
+                Err(err).wrap_err(
+                    "could not decode output; did you specify the wrong function return data type?",
+                )
+            }
+        }
+    }
+
     /// Generates an access list for the specified transaction
     ///
     /// # Example
@@ -2236,6 +2389,35 @@ pub fn decode_eof(eof: &str) -> Result<String> {
     }
 }
 
+/// Attaches the names of the given components to the tuples of a decoded value, recursing into
+/// arrays and nested tuples, so that their fields are labeled when formatted.
+fn name_components(value: DynSolValue, components: &[Param]) -> DynSolValue {
+    match value {
+        DynSolValue::Tuple(values) if values.len() == components.len() => {
+            let tuple = values
+                .into_iter()
+                .zip(components)
+                .map(|(value, param)| name_components(value, &param.components))
+                .collect();
+            if components.iter().all(|param| param.name.is_empty()) {
+                return DynSolValue::Tuple(tuple);
+            }
+            DynSolValue::CustomStruct {
+                name: String::new(),
+                prop_names: components.iter().map(|param| param.name.clone()).collect(),
+                tuple,
+            }
+        }
+        DynSolValue::Array(values) => DynSolValue::Array(
+            values.into_iter().map(|value| name_components(value, components)).collect(),
+        ),
+        DynSolValue::FixedArray(values) => DynSolValue::FixedArray(
+            values.into_iter().map(|value| name_components(value, components)).collect(),
+        ),
+        value => value,
+    }
+}
+
 fn strip_0x(s: &str) -> &str {
     s.strip_prefix("0x").unwrap_or(s)
 }
@@ -2272,6 +2454,60 @@ mod tests {
     use super::SimpleCast as Cast;
     use alloy_primitives::hex;
 
+    #[test]
+    fn names_output_components() {
+        use alloy_dyn_abi::{DynSolValue, FunctionExt};
+        use alloy_json_abi::Function;
+        use alloy_primitives::U256;
+        use foundry_common::fmt::format_token_pretty;
+
+        let func: Function = serde_json::from_str(
+            r#"{
+                "type": "function",
+                "name": "f",
+                "inputs": [],
+                "outputs": [{
+                    "name": "position",
+                    "type": "tuple",
+                    "components": [
+                        { "name": "id", "type": "uint256" },
+                        {
+                            "name": "items",
+                            "type": "tuple[]",
+                            "components": [
+                                { "name": "active", "type": "bool" },
+                                { "name": "kind", "type": "uint8" }
+                            ]
+                        }
+                    ]
+                }],
+                "stateMutability": "view"
+            }"#,
+        )
+        .unwrap();
+        let output = DynSolValue::Tuple(vec![DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(1), 256),
+            DynSolValue::Array(vec![DynSolValue::Tuple(vec![
+                DynSolValue::Bool(true),
+                DynSolValue::Uint(U256::from(2), 8),
+            ])]),
+        ])]);
+        let decoded = func.abi_decode_output(&output.abi_encode_params(), true).unwrap();
+        let named = super::name_components(decoded[0].clone(), &func.outputs[0].components);
+        assert_eq!(
+            format_token_pretty(&named),
+            "(
+    id: 1,
+    items: [
+        [0]: (
+            active: true,
+            kind: 2,
+        ),
+    ],
+)"
+        );
+    }
+
     #[test]
     fn simple_selector() {
         assert_eq!("0xc2985578", Cast::get_selector("foo()", 0).unwrap().0.as_str())
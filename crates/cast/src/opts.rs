@@ -3,7 +3,7 @@
     constructor_args::ConstructorArgsArgs, create2::Create2Args, creation_code::CreationCodeArgs,
     estimate::EstimateArgs, find_block::FindBlockArgs, interface::InterfaceArgs, logs::LogsArgs,
     mktx::MakeTxArgs, rpc::RpcArgs, run::RunArgs, send::SendTxArgs, storage::StorageArgs,
-    txpool::TxPoolSubcommands, wallet::WalletSubcommands,
+    trace_render::TraceRenderArgs, txpool::TxPoolSubcommands, wallet::WalletSubcommands,
 };
 use alloy_primitives::{Address, Selector, B256, U256};
 use alloy_rpc_types::BlockId;
@@ -1018,6 +1018,11 @@ pub enum CastSubcommand {
     #[command(visible_alias = "r")]
     Run(RunArgs),
 
+    /// Renders the traces written by `cast call --trace --trace-export`, without executing the
+    /// calls again.
+    #[command(name = "trace-render")]
+    TraceRender(TraceRenderArgs),
+
     /// Perform a raw JSON-RPC request.
     #[command(visible_alias = "rp")]
     Rpc(RpcArgs),
@@ -1,6 +1,6 @@
-use crate::traces::identifier::SignaturesIdentifier;
+use crate::traces::identifier::{SignaturesCache, SignaturesIdentifier};
 use alloy_consensus::{SidecarBuilder, SignableTransaction, SimpleCoder};
-use alloy_dyn_abi::ErrorExt;
+use alloy_dyn_abi::{DynSolType, ErrorExt};
 use alloy_json_abi::Function;
 use alloy_network::{
     AnyNetwork, AnyTypedTransaction, TransactionBuilder, TransactionBuilder4844,
@@ -8,16 +8,21 @@
 };
 use alloy_primitives::{hex, Address, Bytes, TxKind, U256};
 use alloy_provider::Provider;
-use alloy_rpc_types::{AccessList, Authorization, TransactionInput, TransactionRequest};
+use alloy_rpc_types::{AccessList, Authorization, BlockId, TransactionInput, TransactionRequest};
 use alloy_serde::WithOtherFields;
 use alloy_signer::Signer;
 use alloy_transport::TransportError;
 use eyre::Result;
 use foundry_cli::{
     opts::{CliAuthorizationList, TransactionOpts},
-    utils::{self, parse_function_args},
+    utils::{self, parse_function_args, parse_json_function_args},
+};
+use foundry_common::{
+    abi::{encode_function_args, get_func},
+    ens::{NameOrAddress, ProviderEnsExt},
+    fmt::format_tokens,
+    stdin,
 };
-use foundry_common::{ens::NameOrAddress, fmt::format_tokens};
 use foundry_config::{Chain, Config};
 use foundry_wallets::{WalletOpts, WalletSigner};
 use itertools::Itertools;
@@ -117,7 +122,9 @@ pub fn validate_from_address(
 /// State with known [TxKind].
 #[derive(Debug)]
 pub struct ToState {
-    to: Option<Address>,
+    to: Option<NameOrAddress>,
+    /// The block to resolve the ENS names at.
+    block: Option<BlockId>,
 }
 
 /// State with known input for the transaction.
@@ -128,6 +135,116 @@ pub struct InputState {
     func: Option<Function>,
 }
 
+/// Resolves the ENS names of the recipient and of the arguments at the given block, in a single
+/// batch.
+///
+/// Arguments are treated as names if they contain a dot, and are left as is if they can't be
+/// resolved.
+async fn resolve_names<P: Provider<AnyNetwork>>(
+    provider: &P,
+    to: Option<NameOrAddress>,
+    mut args: Vec<String>,
+    block: Option<BlockId>,
+) -> Result<(Option<Address>, Vec<String>)> {
+    let arg_names = (0..args.len()).filter(|&idx| args[idx].contains('.')).collect::<Vec<_>>();
+    let mut names = arg_names.iter().map(|&idx| args[idx].clone()).collect::<Vec<_>>();
+    if let Some(NameOrAddress::Name(name)) = &to {
+        names.push(name.clone());
+    }
+
+    let mut resolved = provider.resolve_names_at(&names, block.unwrap_or_default()).await;
+    let to = match to {
+        Some(NameOrAddress::Name(_)) => Some(resolved.pop().expect("missing resolved recipient")?),
+        Some(NameOrAddress::Address(address)) => Some(address),
+        None => None,
+    };
+    for (idx, address) in arg_names.into_iter().zip(resolved) {
+        if let Ok(address) = address {
+            args[idx] = address.to_string();
+        }
+    }
+    Ok((to, args))
+}
+
+/// Replaces the argument given as `-` with the contents of stdin, for arguments too large for the
+/// command line. Only one argument can be read from stdin.
+///
+/// See [`stdin_arg`] for how the contents are interpreted with the parameter type of the signature.
+fn read_stdin_arg(sig: Option<&str>, args: &mut [String]) -> Result<()> {
+    let mut positions = args.iter().positions(|arg| arg == "-");
+    let Some(idx) = positions.next() else { return Ok(()) };
+    if let Some(other) = positions.next() {
+        eyre::bail!(
+            "Only one argument can be read from stdin, but arguments {} and {} are both `-`",
+            idx + 1,
+            other + 1
+        );
+    }
+
+    // The parameter type is unknown if the function is looked up on Etherscan.
+    let func = sig.filter(|sig| sig.contains('(')).map(get_func).transpose()?;
+    let ty = func.as_ref().and_then(|func| Some(func.inputs.get(idx)?.ty.as_str()));
+    args[idx] = stdin_arg(ty, stdin::read_bytes(false)?)?;
+    Ok(())
+}
+
+/// Converts the contents of stdin to an argument of the given type.
+///
+/// For `bytes` parameters, hex is used as is, and anything else is taken as the raw bytes of the
+/// argument. Other arguments are read as text, without trailing newlines.
+fn stdin_arg(ty: Option<&str>, input: Vec<u8>) -> Result<String> {
+    let is_bytes = ty
+        .and_then(|ty| DynSolType::parse(ty).ok())
+        .is_some_and(|ty| matches!(ty, DynSolType::Bytes | DynSolType::FixedBytes(_)));
+    if let Ok(text) = std::str::from_utf8(&input) {
+        let text = text.trim_end_matches(['\n', '\r']);
+        if !is_bytes || hex::decode(text).is_ok() {
+            return Ok(text.to_string());
+        }
+    }
+    if let Some(ty) = ty.filter(|_| !is_bytes) {
+        eyre::bail!(
+            "stdin is not valid UTF-8, and can only be read as raw bytes for a `bytes` argument, \
+             not `{ty}`"
+        );
+    }
+    Ok(hex::encode_prefixed(input))
+}
+
+/// Returns `true` if the signature is a plain function name, without parameters.
+fn is_function_name(sig: &str) -> bool {
+    !sig.is_empty() &&
+        hex::decode(sig).is_err() &&
+        sig.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Resolves a function name to its full signature using the local signatures cache.
+///
+/// Overloaded functions are disambiguated by the given arguments. Returns `None` if no cached
+/// signature matches the name, and an error listing the candidates if it is still ambiguous.
+fn resolve_function_name(name: &str, args: &[String]) -> Result<Option<String>> {
+    let Some(cache_dir) = Config::foundry_cache_dir() else { return Ok(None) };
+    let mut candidates =
+        SignaturesCache::load(&cache_dir.join("signatures")).function_signatures(name);
+    if candidates.len() > 1 {
+        candidates.retain(|sig| {
+            get_func(sig).is_ok_and(|func| encode_function_args(&func, args).is_ok())
+        });
+    }
+
+    match &candidates[..] {
+        [] => Ok(None),
+        [sig] => {
+            trace!(name, %sig, "resolved function name from signatures cache");
+            Ok(Some(sig.clone()))
+        }
+        _ => eyre::bail!(
+            "Function name `{name}` is ambiguous, please provide one of the signatures:\n{}",
+            candidates.iter().map(|sig| format!("  {sig}")).join("\n")
+        ),
+    }
+}
+
 /// Builder type constructing [TransactionRequest] from cast send/mktx inputs.
 ///
 /// It is implemented as a stateful builder with expected state transition of [InitState] ->
@@ -199,8 +316,15 @@ pub async fn new(provider: P, tx_opts: TransactionOpts, config: &Config) -> Resu
     }
 
     /// Sets [TxKind] for this builder and changes state to [ToState].
-    pub async fn with_to(self, to: Option<NameOrAddress>) -> Result<CastTxBuilder<P, ToState>> {
-        let to = if let Some(to) = to { Some(to.resolve(&self.provider).await?) } else { None };
+    ///
+    /// ENS names are resolved at the given block, or at the latest block if `None`, together with
+    /// the ones of the arguments, see
+    /// [`with_code_sig_and_args`](CastTxBuilder::with_code_sig_and_args).
+    pub async fn with_to(
+        self,
+        to: Option<NameOrAddress>,
+        block: Option<BlockId>,
+    ) -> Result<CastTxBuilder<P, ToState>> {
         Ok(CastTxBuilder {
             provider: self.provider,
             tx: self.tx,
@@ -210,7 +334,7 @@ pub async fn with_to(self, to: Option<NameOrAddress>) -> Result<CastTxBuilder<P,
             etherscan_api_key: self.etherscan_api_key,
             auth: self.auth,
             access_list: self.access_list,
-            state: ToState { to },
+            state: ToState { to, block },
         })
     }
 }
@@ -225,16 +349,22 @@ pub async fn with_code_sig_and_args(
         sig: Option<String>,
         args: Vec<String>,
     ) -> Result<CastTxBuilder<P, InputState>> {
+        // Resolve a plain function name to its full signature from the local signatures cache,
+        // falling back to Etherscan if it is not cached.
+        let sig = match sig {
+            Some(sig) if code.is_none() && is_function_name(&sig) => {
+                Some(resolve_function_name(&sig, &args)?.unwrap_or(sig))
+            }
+            sig => sig,
+        };
+
+        let (to, mut args) =
+            resolve_names(&self.provider, self.state.to, args, self.state.block).await?;
+        read_stdin_arg(sig.as_deref(), &mut args)?;
+
         let (mut args, func) = if let Some(sig) = sig {
-            parse_function_args(
-                &sig,
-                args,
-                self.state.to,
-                self.chain,
-                &self.provider,
-                self.etherscan_api_key.as_deref(),
-            )
-            .await?
+            parse_function_args(&sig, args, to, self.chain, self.etherscan_api_key.as_deref())
+                .await?
         } else {
             (Vec::new(), None)
         };
@@ -247,7 +377,7 @@ pub async fn with_code_sig_and_args(
             args
         };
 
-        if self.state.to.is_none() && code.is_none() {
+        if to.is_none() && code.is_none() {
             let has_value = self.tx.value.is_some_and(|v| !v.is_zero());
             let has_auth = self.auth.is_some();
             // We only allow user to omit the recipient address if transaction is an EIP-7702 tx
@@ -266,9 +396,24 @@ pub async fn with_code_sig_and_args(
             etherscan_api_key: self.etherscan_api_key,
             auth: self.auth,
             access_list: self.access_list,
-            state: InputState { kind: self.state.to.into(), input, func },
+            state: InputState { kind: to.into(), input, func },
         })
     }
+
+    /// Same as [`Self::with_code_sig_and_args`], but with the arguments given as a JSON object
+    /// mapping the parameter names of the signature to their values.
+    pub async fn with_code_sig_and_json_args(
+        self,
+        code: Option<String>,
+        sig: Option<String>,
+        args_json: &str,
+    ) -> Result<CastTxBuilder<P, InputState>> {
+        let sig = sig.ok_or_else(|| {
+            eyre::eyre!("A function signature with named parameters must be provided")
+        })?;
+        let args = parse_json_function_args(&get_func(&sig)?, args_json)?;
+        self.with_code_sig_and_args(code, Some(sig), args).await
+    }
 }
 
 impl<P: Provider<AnyNetwork>> CastTxBuilder<P, InputState> {
@@ -474,3 +619,31 @@ async fn decode_execution_revert(data: &RawValue) -> Result<Option<String>> {
     }
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_stdin_args() {
+        assert_eq!(stdin_arg(Some("bytes"), b"0x1234\n".to_vec()).unwrap(), "0x1234");
+        assert_eq!(stdin_arg(Some("bytes"), b"1234".to_vec()).unwrap(), "1234");
+        assert_eq!(stdin_arg(Some("bytes"), vec![0xff, 0x00, 0x0a]).unwrap(), "0xff000a");
+        assert_eq!(stdin_arg(Some("bytes"), b"proof".to_vec()).unwrap(), "0x70726f6f66");
+        assert_eq!(stdin_arg(Some("string"), b"hello\r\n".to_vec()).unwrap(), "hello");
+        assert_eq!(stdin_arg(None, b"42\n".to_vec()).unwrap(), "42");
+        assert_eq!(stdin_arg(None, vec![0xff]).unwrap(), "0xff");
+        assert!(stdin_arg(Some("string"), vec![0xff]).is_err());
+    }
+
+    #[test]
+    fn reads_one_stdin_arg() {
+        let mut args = vec!["1".to_string()];
+        read_stdin_arg(Some("f(uint256)"), &mut args).unwrap();
+        assert_eq!(args, ["1"]);
+
+        let mut args = vec!["-".to_string(), "1".to_string(), "-".to_string()];
+        let err = read_stdin_arg(Some("f(bytes,uint256,bytes)"), &mut args).unwrap_err();
+        assert!(err.to_string().contains("arguments 1 and 3"), "{err}");
+    }
+}
@@ -2,7 +2,7 @@
 
 use alloy_chains::NamedChain;
 use alloy_network::{TransactionBuilder, TransactionResponse};
-use alloy_primitives::{address, b256, Bytes, B256};
+use alloy_primitives::{address, b256, Bytes, B256, U256};
 use alloy_provider::{Provider, ProviderBuilder};
 use alloy_rpc_types::{BlockNumberOrTag, Index, TransactionRequest};
 use anvil::{EthereumHardfork, NodeConfig};
@@ -2257,6 +2257,46 @@ struct Info {
         .assert_success();
 });
 
+// tests that `cast call --diff-against` fails if the outcome differs between the two nodes
+forgetest_async!(cast_call_diff_against, |_prj, cmd| {
+    let (primary_api, primary) = anvil::spawn(NodeConfig::test()).await;
+    let (secondary_api, secondary) = anvil::spawn(NodeConfig::test()).await;
+
+    // Returns `address(this).balance`.
+    let target = address!("0x0000000000000000000000000000000000001234");
+    let code = Bytes::from_str("0x475f5260205ff3").unwrap();
+    primary_api.anvil_set_code(target, code.clone()).await.unwrap();
+    secondary_api.anvil_set_code(target, code).await.unwrap();
+
+    let args = [
+        "call",
+        &target.to_string(),
+        "balance()(uint256)",
+        "--rpc-url",
+        &primary.http_endpoint(),
+        "--diff-against",
+        &secondary.http_endpoint(),
+    ];
+    cmd.cast_fuse().args(args).assert_success().stdout_eq(str![[r#"
+0
+
+"#]]);
+
+    secondary_api.anvil_set_balance(target, U256::from(1)).await.unwrap();
+    cmd.cast_fuse()
+        .args(args)
+        .assert_failure()
+        .stdout_eq(str![[r#"
+- [..]: 0
++ [..]: 1
+
+"#]])
+        .stderr_eq(str![[r#"
+Error: The outcome of the call differs between the two nodes
+
+"#]]);
+});
+
 // https://github.com/foundry-rs/foundry/issues/10189
 forgetest_async!(cast_call_custom_override, |prj, cmd| {
     let (_, handle) = anvil::spawn(NodeConfig::test()).await;
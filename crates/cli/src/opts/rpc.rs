@@ -2,6 +2,7 @@
 use alloy_chains::ChainKind;
 use clap::Parser;
 use eyre::Result;
+use foundry_common::provider::runtime_transport::parse_header;
 use foundry_config::{
     figment::{
         self,
@@ -19,6 +20,8 @@
 #[derive(Clone, Debug, Default, Parser)]
 pub struct RpcOpts {
     /// The RPC endpoint, default value is http://localhost:8545.
+    ///
+    /// Can also be the path to an IPC socket, e.g. `/path/to/geth.ipc`.
     #[arg(short = 'r', long = "rpc-url", env = "ETH_RPC_URL")]
     pub url: Option<String>,
 
@@ -50,8 +53,23 @@ pub struct RpcOpts {
     #[arg(long, env = "ETH_RPC_TIMEOUT")]
     pub rpc_timeout: Option<u64>,
 
-    /// Specify custom headers for RPC requests.
-    #[arg(long, alias = "headers", env = "ETH_RPC_HEADERS", value_delimiter(','))]
+    /// Number of times to retry RPC requests that timed out or failed to reach the node.
+    ///
+    /// Retries are made with an exponential backoff, and the timeout is enforced on every attempt.
+    #[arg(long, env = "ETH_RPC_RETRIES")]
+    pub rpc_retries: Option<u32>,
+
+    /// Specify custom headers for RPC requests, in the form `Key: Value`.
+    ///
+    /// Can be repeated, and applies to all RPC requests including forking.
+    #[arg(
+        long,
+        visible_alias = "rpc-header",
+        alias = "headers",
+        env = "ETH_RPC_HEADERS",
+        value_delimiter(','),
+        value_parser = parse_rpc_header
+    )]
     pub rpc_headers: Option<Vec<String>>,
 }
 
@@ -100,6 +118,9 @@ pub fn dict(&self) -> Dict {
         if let Some(rpc_timeout) = self.rpc_timeout {
             dict.insert("eth_rpc_timeout".into(), rpc_timeout.into());
         }
+        if let Some(rpc_retries) = self.rpc_retries {
+            dict.insert("eth_rpc_retries".into(), rpc_retries.into());
+        }
         if let Some(headers) = &self.rpc_headers {
             dict.insert("eth_rpc_headers".into(), headers.clone().into());
         }
@@ -107,6 +128,12 @@ pub fn dict(&self) -> Dict {
     }
 }
 
+/// Validates that `header` is a valid HTTP header of the form `Key: Value`.
+fn parse_rpc_header(header: &str) -> Result<String> {
+    parse_header(header)?;
+    Ok(header.to_string())
+}
+
 #[derive(Clone, Debug, Default, Serialize, Parser)]
 pub struct EtherscanOpts {
     /// The Etherscan (or equivalent) API key.
@@ -212,4 +239,21 @@ fn parse_etherscan_opts() {
             EtherscanOpts::parse_from(["foundry-cli", "--etherscan-api-key", ""]);
         assert!(!args.has_key());
     }
+
+    #[test]
+    fn parse_rpc_headers() {
+        let args = RpcOpts::parse_from([
+            "foundry-cli",
+            "--rpc-header",
+            "X-Api-Key: secret",
+            "--rpc-headers",
+            "Origin: foundry",
+        ]);
+        assert_eq!(
+            args.rpc_headers,
+            Some(vec!["X-Api-Key: secret".to_string(), "Origin: foundry".to_string()])
+        );
+
+        assert!(RpcOpts::try_parse_from(["foundry-cli", "--rpc-header", "X-Api-Key"]).is_err());
+    }
 }
@@ -1,43 +1,25 @@
 use alloy_chains::Chain;
-use alloy_json_abi::Function;
+use alloy_json_abi::{Function, Param};
 use alloy_primitives::{hex, Address};
-use alloy_provider::{network::AnyNetwork, Provider};
 use eyre::{OptionExt, Result};
-use foundry_common::{
-    abi::{encode_function_args, get_func, get_func_etherscan},
-    ens::NameOrAddress,
-};
-use futures::future::join_all;
-
-async fn resolve_name_args<P: Provider<AnyNetwork>>(args: &[String], provider: &P) -> Vec<String> {
-    join_all(args.iter().map(|arg| async {
-        if arg.contains('.') {
-            let addr = NameOrAddress::Name(arg.to_string()).resolve(provider).await;
-            match addr {
-                Ok(addr) => addr.to_string(),
-                Err(_) => arg.to_string(),
-            }
-        } else {
-            arg.to_string()
-        }
-    }))
-    .await
-}
+use foundry_common::abi::{encode_function_args, get_func, get_func_etherscan};
+use serde_json::Value;
 
-pub async fn parse_function_args<P: Provider<AnyNetwork>>(
+/// Encodes the arguments of the function with the given signature, returning the encoded calldata
+/// along with the function.
+///
+/// ENS names among the arguments are expected to be resolved already.
+pub async fn parse_function_args(
     sig: &str,
     args: Vec<String>,
     to: Option<Address>,
     chain: Chain,
-    provider: &P,
     etherscan_api_key: Option<&str>,
 ) -> Result<(Vec<u8>, Option<Function>)> {
     if sig.trim().is_empty() {
         eyre::bail!("Function signature or calldata must be provided.")
     }
 
-    let args = resolve_name_args(&args, provider).await;
-
     if let Ok(data) = hex::decode(sig) {
         return Ok((data, None))
     }
@@ -55,3 +37,116 @@ pub async fn parse_function_args<P: Provider<AnyNetwork>>(
 
     Ok((encode_function_args(&func, &args)?, Some(func)))
 }
+
+/// Parses function arguments given as a JSON object mapping the parameter names to their values,
+/// and returns them ordered by the function parameters.
+///
+/// Struct values can be given either as JSON objects keyed by the component names, or as strings.
+pub fn parse_json_function_args(func: &Function, args_json: &str) -> Result<Vec<String>> {
+    let Value::Object(mut args) = serde_json::from_str(args_json)? else {
+        eyre::bail!("Named arguments must be a JSON object")
+    };
+
+    let ordered_args = func
+        .inputs
+        .iter()
+        .map(|param| {
+            if param.name.is_empty() {
+                eyre::bail!("Parameters of `{}` must be named to use named arguments", func.name)
+            }
+            let value = args
+                .remove(&param.name)
+                .ok_or_else(|| eyre::eyre!("Missing argument for parameter `{}`", param.name))?;
+            json_value_to_arg(param, &param.ty, value)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(name) = args.keys().next() {
+        eyre::bail!("Unknown argument `{name}` for `{}`", func.signature())
+    }
+    Ok(ordered_args)
+}
+
+/// Converts a JSON value to its string representation for the given parameter type.
+fn json_value_to_arg(param: &Param, ty: &str, value: Value) -> Result<String> {
+    Ok(match value {
+        Value::String(s) => s,
+        Value::Array(values) => {
+            // Strip the outermost array dimension to get the element type.
+            let elem_ty = ty.rsplit_once('[').map(|(elem_ty, _)| elem_ty).unwrap_or(ty);
+            let values = values
+                .into_iter()
+                .map(|value| json_value_to_arg(param, elem_ty, value))
+                .collect::<Result<Vec<_>>>()?;
+            format!("[{}]", values.join(","))
+        }
+        Value::Object(mut fields) => {
+            let values = param
+                .components
+                .iter()
+                .map(|component| {
+                    let value = fields.remove(&component.name).ok_or_else(|| {
+                        eyre::eyre!("Missing field `{}` in `{}`", component.name, param.name)
+                    })?;
+                    json_value_to_arg(component, &component.ty, value)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(name) = fields.keys().next() {
+                eyre::bail!("Unknown field `{name}` in `{}`", param.name)
+            }
+            format!("({})", values.join(","))
+        }
+        value => value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_json_function_args() {
+        let func = get_func("transfer(address to, uint256 amount)").unwrap();
+        let args = parse_json_function_args(
+            &func,
+            r#"{"amount": 100, "to": "0x0000000000000000000000000000000000000001"}"#,
+        )
+        .unwrap();
+        assert_eq!(args, ["0x0000000000000000000000000000000000000001", "100"]);
+
+        let err = parse_json_function_args(&func, r#"{"to": "0x01"}"#).unwrap_err();
+        assert!(err.to_string().contains("Missing argument for parameter `amount`"));
+
+        let err = parse_json_function_args(&func, r#"{"to": "0x01", "amount": 1, "foo": 2}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown argument `foo`"));
+    }
+
+    #[test]
+    fn can_parse_json_struct_args() {
+        let func: Function = serde_json::from_str(
+            r#"{
+                "type": "function",
+                "name": "f",
+                "inputs": [
+                    {
+                        "name": "s",
+                        "type": "tuple[]",
+                        "components": [
+                            { "name": "a", "type": "uint256" },
+                            { "name": "b", "type": "bool[]" }
+                        ]
+                    },
+                    { "name": "c", "type": "uint8" }
+                ],
+                "outputs": [],
+                "stateMutability": "nonpayable"
+            }"#,
+        )
+        .unwrap();
+        let args =
+            parse_json_function_args(&func, r#"{"c": 1, "s": [{"b": [true, false], "a": "2"}]}"#)
+                .unwrap();
+        assert_eq!(args, ["[(2,[true,false])]", "1"]);
+    }
+}
@@ -1,9 +1,16 @@
+use crate::utils::{get_provider, GasReportSort, OpcodeGasReport, TraceGasReport};
+use alloy_eips::BlockId;
 use alloy_json_abi::JsonAbi;
-use alloy_primitives::Address;
+use alloy_primitives::{
+    map::{AddressHashMap, AddressHashSet},
+    utils::format_ether,
+    Address, Selector,
+};
+use alloy_provider::Provider;
 use eyre::{Result, WrapErr};
 use foundry_common::{
-    compile::ProjectCompiler, fs, selectors::SelectorKind, shell, ContractsByArtifact,
-    TestFunctionExt,
+    compile::ProjectCompiler, ens::ProviderEnsExt, fs, provider::RetryProvider,
+    selectors::SelectorKind, shell, ContractsByArtifact, Shell, TestFunctionExt,
 };
 use foundry_compilers::{
     artifacts::{CompactBytecode, Settings},
@@ -16,17 +23,26 @@
 use foundry_evm::{
     executors::{DeployResult, EvmError, RawCallResult},
     opts::EvmOpts,
+    revm::interpreter::InstructionResult,
     traces::{
         debug::{ContractSources, DebugTraceIdentifier},
-        decode_trace_arena,
+        decode_trace_arena, filter_trace_addresses,
         identifier::{SignaturesCache, SignaturesIdentifier, TraceIdentifiers},
-        render_trace_arena_inner, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
+        json_trace, label_constructor_args, label_selfdestructs, label_storage_changes,
+        label_storage_reads, limit_trace_depth, write_trace_arena, CallKind, CallLog,
+        CallTraceDecoder, CallTraceDecoderBuilder, CallTraceNode, InternalTraceMode, TraceKind,
+        Traces,
     },
 };
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fmt::Write,
+    io::{self, BufWriter, Write as _},
     path::{Path, PathBuf},
     str::FromStr,
+    sync::{LazyLock, Mutex},
 };
 use yansi::Paint;
 
@@ -278,18 +294,41 @@ pub fn read_constructor_args_file(constructor_args_path: PathBuf) -> Result<Vec<
 }
 
 /// A slimmed down return from the executor used for returning minimal trace + gas metering info
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TraceResult {
     pub success: bool,
     pub traces: Option<Traces>,
     pub gas_used: u64,
+    /// Whether the execution ran out of gas.
+    pub out_of_gas: bool,
+    /// The gas limit of the execution, if it was capped.
+    pub gas_limit: Option<u64>,
 }
 
 impl TraceResult {
     /// Create a new [`TraceResult`] from a [`RawCallResult`].
     pub fn from_raw(raw: RawCallResult, trace_kind: TraceKind) -> Self {
-        let RawCallResult { gas_used, traces, reverted, .. } = raw;
-        Self { success: !reverted, traces: traces.map(|arena| vec![(trace_kind, arena)]), gas_used }
+        let RawCallResult { gas_used, traces, reverted, exit_reason, .. } = raw;
+        Self {
+            success: !reverted,
+            traces: traces.map(|arena| vec![(trace_kind, arena)]),
+            gas_used,
+            out_of_gas: matches!(
+                exit_reason,
+                InstructionResult::OutOfGas |
+                    InstructionResult::MemoryOOG |
+                    InstructionResult::MemoryLimitOOG |
+                    InstructionResult::PrecompileOOG |
+                    InstructionResult::InvalidOperandOOG
+            ),
+            gas_limit: None,
+        }
+    }
+
+    /// Sets the gas limit the execution was capped at, to report it alongside the gas used.
+    pub fn with_gas_limit(mut self, gas_limit: Option<u64>) -> Self {
+        self.gas_limit = gas_limit;
+        self
     }
 }
 
@@ -328,47 +367,159 @@ fn try_from(value: Result<RawCallResult>) -> Result<Self, Self::Error> {
     }
 }
 
+/// The output format of printed traces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TraceFormat {
+    /// Human-readable trace tree.
+    #[default]
+    Pretty,
+    /// Structured JSON call tree, see [`json_trace::JsonCallTrace`].
+    Json,
+}
+
+/// Parses trace labels in the format `address:label`.
+///
+/// If an address is labeled multiple times, the last label wins.
+pub fn parse_labels(labels: &[String]) -> Result<AddressHashMap<String>> {
+    let mut parsed = AddressHashMap::default();
+    for label_str in labels {
+        let (address, label) = label_str
+            .split_once(':')
+            .and_then(|(addr, label)| Some((Address::from_str(addr).ok()?, label)))
+            .filter(|(_, label)| !label.is_empty())
+            .ok_or_else(|| {
+                eyre::eyre!("Invalid label `{label_str}`. Expected <address>:<label>")
+            })?;
+        parsed.insert(address, label.to_string());
+    }
+    Ok(parsed)
+}
+
+/// Options of [`handle_traces`], controlling how the traces are identified, decoded and printed.
+#[derive(Clone, Debug, Default)]
+pub struct TraceOptions<'a> {
+    /// Labels to apply to the traces, in the format `address:label`.
+    pub labels: Vec<String>,
+    /// Identify the traces with the artifacts of the current project.
+    pub with_local_artifacts: bool,
+    /// Don't identify the addresses of the traces from their code, which can be slow for large
+    /// traces. Function selectors are still decoded from their signatures.
+    pub skip_identification: bool,
+    /// Identify the traces with Etherscan, if an API key is configured.
+    pub with_etherscan: bool,
+    /// Label the addresses that have a reverse ENS record.
+    pub ens_names: bool,
+    /// The block to look up the ENS names at. Defaults to the latest block.
+    pub block: Option<BlockId>,
+    /// Additional ABIs to decode the traces with.
+    pub abis: &'a [JsonAbi],
+    /// Open the debugger instead of printing the traces.
+    pub debug: bool,
+    /// How to decode internal function calls.
+    pub decode_internal: InternalTraceMode,
+    /// Print the storage slots read by each call.
+    pub storage_reads: bool,
+    /// The format of the printed traces.
+    pub trace_format: TraceFormat,
+    /// Print a gas report of the traces, grouped by contract and function.
+    pub gas_report: bool,
+    /// The order of the gas report.
+    pub gas_report_sort: GasReportSort,
+    /// Only print the first functions of each contract of the gas report.
+    pub gas_report_top: Option<usize>,
+    /// Print a gas report of the traces, grouped by opcode.
+    pub gas_by_opcode: bool,
+    /// Only print the calls up to this depth.
+    pub depth_limit: Option<usize>,
+    /// Only print the calls to these addresses, along with their parents.
+    pub trace_filter: &'a [Address],
+    /// Print the decoded events, grouped by emitting contract.
+    pub decode_events: bool,
+    /// Print the called function selectors.
+    pub inspect_selectors: bool,
+    /// Decode the constructor arguments of the created contracts.
+    pub identify_creation: bool,
+    /// Narrate the traces in plain language.
+    pub explain: bool,
+}
+
 /// labels the traces, conditionally prints them or opens the debugger
 pub async fn handle_traces(
     mut result: TraceResult,
     config: &Config,
     chain: Option<Chain>,
-    labels: Vec<String>,
-    with_local_artifacts: bool,
-    debug: bool,
-    decode_internal: bool,
+    options: TraceOptions<'_>,
 ) -> Result<()> {
-    let (known_contracts, mut sources) = if with_local_artifacts {
+    let TraceOptions {
+        labels,
+        with_local_artifacts,
+        skip_identification,
+        with_etherscan,
+        ens_names,
+        block,
+        abis,
+        debug,
+        decode_internal,
+        storage_reads,
+        trace_format,
+        gas_report,
+        gas_report_sort,
+        gas_report_top,
+        gas_by_opcode,
+        depth_limit,
+        trace_filter,
+        decode_events,
+        inspect_selectors,
+        identify_creation,
+        explain,
+    } = options;
+    let state_changes = shell::verbosity() > 4;
+    let (known_contracts, storage_layouts, mut sources) = if with_local_artifacts {
         let _ = sh_println!("Compiling project to generate artifacts");
-        let project = config.project()?;
+        let mut project = config.project()?;
+        if state_changes {
+            // Storage layouts are used to label the storage changes.
+            project.artifacts.additional_values.storage_layout = true;
+            project.update_output_selection(|selection| {
+                for contract_selection in selection.0.values_mut() {
+                    for selection in contract_selection.values_mut() {
+                        selection.push("storageLayout".to_string());
+                    }
+                }
+            });
+        }
         let compiler = ProjectCompiler::new();
         let output = compiler.compile(&project)?;
         (
             Some(ContractsByArtifact::new(
                 output.artifact_ids().map(|(id, artifact)| (id, artifact.clone().into())),
             )),
+            output
+                .artifact_ids()
+                .filter_map(|(id, artifact)| {
+                    Some((id.identifier(), artifact.storage_layout.clone()?))
+                })
+                .collect::<Vec<_>>(),
             ContractSources::from_project_output(&output, project.root(), None)?,
         )
     } else {
-        (None, ContractSources::default())
+        (None, Vec::new(), ContractSources::default())
     };
 
-    let labels = labels.iter().filter_map(|label_str| {
-        let mut iter = label_str.split(':');
-
-        if let Some(addr) = iter.next() {
-            if let (Ok(address), Some(label)) = (Address::from_str(addr), iter.next()) {
-                return Some((address, label.to_string()));
-            }
-        }
-        None
-    });
-    let config_labels = config.labels.clone().into_iter();
+    let mut labels = parse_labels(&labels)?;
+    labels.extend(config.labels.clone());
 
     let mut builder = CallTraceDecoderBuilder::new()
-        .with_labels(labels.chain(config_labels))
-        .with_signature_identifier(SignaturesIdentifier::from_config(config)?);
-    let mut identifier = TraceIdentifiers::new().with_etherscan(config, chain)?;
+        .with_labels(labels.clone())
+        .with_signature_identifier(SignaturesIdentifier::from_config(config)?)
+        .with_storage_layouts(storage_layouts);
+    for abi in abis {
+        builder = builder.with_abi(abi);
+    }
+    let mut identifier = TraceIdentifiers::new();
+    if with_etherscan {
+        identifier = identifier.with_etherscan(config, chain)?;
+    }
     if let Some(contracts) = &known_contracts {
         builder = builder.with_known_contracts(contracts);
         identifier = identifier.with_local(contracts);
@@ -376,11 +527,21 @@ pub async fn handle_traces(
 
     let mut decoder = builder.build();
 
-    for (_, trace) in result.traces.as_deref_mut().unwrap_or_default() {
-        decoder.identify(trace, &mut identifier);
+    identify_traces(&result, &mut decoder, &mut identifier, skip_identification);
+
+    if identify_creation {
+        if let Some(local) = &identifier.local {
+            for (_, trace) in result.traces.as_deref().unwrap_or_default() {
+                decoder.identify_constructor_args(trace, local);
+            }
+        }
+    }
+
+    if ens_names {
+        label_ens_names(&result, &mut decoder, config, chain, block, &labels).await?;
     }
 
-    if decode_internal || debug {
+    if !decode_internal.is_none() || debug {
         if let Some(ref etherscan_identifier) = identifier.etherscan {
             sources.merge(etherscan_identifier.get_compiled_contracts().await?);
         }
@@ -395,19 +556,164 @@ pub async fn handle_traces(
             return Ok(())
         }
 
-        decoder.debug_identifier = Some(DebugTraceIdentifier::new(sources));
+        decoder.debug_identifier = Some(
+            DebugTraceIdentifier::new(sources)
+                .with_boundaries_only(decode_internal == InternalTraceMode::Boundaries),
+        );
+    }
+
+    if trace_format == TraceFormat::Json {
+        print_json_traces(&mut result, &decoder).await?;
+    } else {
+        print_traces(
+            &mut result,
+            &decoder,
+            shell::verbosity() > 0,
+            state_changes,
+            storage_reads,
+            depth_limit,
+            trace_filter,
+        )
+        .await?;
+    }
+
+    if gas_report {
+        let nodes = result.traces.iter().flatten().flat_map(|(_, arena)| arena.nodes());
+        let report =
+            TraceGasReport::new(nodes, &decoder).with_sort(gas_report_sort, gas_report_top);
+        if shell::is_json() || trace_format == TraceFormat::Json {
+            sh_println!("{}", serde_json::to_string(&report)?)?;
+        } else {
+            // Rendered before printing, as rendering the tables queries the shell.
+            let report = report.to_string();
+            sh_println!("{report}")?;
+        }
+    }
+
+    if gas_by_opcode {
+        let nodes = result.traces.iter().flatten().map(|(_, arena)| arena.nodes());
+        let report = OpcodeGasReport::new(nodes);
+        if shell::is_json() || trace_format == TraceFormat::Json {
+            sh_println!("{}", serde_json::to_string(&report)?)?;
+        } else {
+            // Rendered before printing, as rendering the tables queries the shell.
+            let report = report.to_string();
+            sh_println!("{report}")?;
+        }
+    }
+
+    if decode_events {
+        print_events(&result, &decoder)?;
     }
 
-    print_traces(&mut result, &decoder, shell::verbosity() > 0, shell::verbosity() > 4).await?;
+    if inspect_selectors {
+        print_selectors(&result)?;
+    }
+
+    if explain {
+        print_explanation(&result, &decoder)?;
+    }
 
     Ok(())
 }
 
+/// Reverse ENS names looked up with `--ens-names` by chain ID, cached across the traces of a
+/// command.
+static ENS_NAMES: LazyLock<Mutex<HashMap<(u64, Address), Option<String>>>> =
+    LazyLock::new(Default::default);
+
+/// The maximum number of concurrent reverse ENS lookups.
+const ENS_LOOKUP_CONCURRENCY: usize = 10;
+
+/// Labels the addresses of the traces that have a reverse ENS record as `name.eth (0x...)`.
+///
+/// The names are looked up at the given block, and only used if they resolve back to the address.
+/// The names are resolved in a single `Multicall3` call. Addresses in `labels` keep their label.
+async fn label_ens_names(
+    result: &TraceResult,
+    decoder: &mut CallTraceDecoder,
+    config: &Config,
+    chain: Option<Chain>,
+    block: Option<BlockId>,
+    labels: &AddressHashMap<String>,
+) -> Result<()> {
+    let addresses = result
+        .traces
+        .iter()
+        .flatten()
+        .flat_map(|(_, arena)| arena.nodes())
+        .flat_map(|node| [node.trace.address, node.trace.caller])
+        .filter(|address| !labels.contains_key(address))
+        .collect::<AddressHashSet>();
+    if addresses.is_empty() {
+        return Ok(());
+    }
+
+    let provider = get_provider(config)?;
+    let chain_id = match chain {
+        Some(chain) => chain.id(),
+        None => provider.get_chain_id().await?,
+    };
+    let missing = {
+        let names = ENS_NAMES.lock().unwrap();
+        addresses
+            .iter()
+            .filter(|address| !names.contains_key(&(chain_id, **address)))
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    if !missing.is_empty() {
+        let block = block.unwrap_or_default();
+        let found = reverse_ens_names(&provider, &missing, block).await;
+        let mut names = ENS_NAMES.lock().unwrap();
+        names.extend(missing.into_iter().map(|address| ((chain_id, address), None)));
+        names.extend(found.into_iter().map(|(address, name)| ((chain_id, address), Some(name))));
+    }
+
+    let names = ENS_NAMES.lock().unwrap();
+    for address in addresses {
+        if let Some(Some(name)) = names.get(&(chain_id, address)) {
+            decoder.labels.insert(address, format!("{name} ({address})"));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the reverse ENS names of the addresses that resolve back to them.
+async fn reverse_ens_names(
+    provider: &RetryProvider,
+    addresses: &[Address],
+    block: BlockId,
+) -> Vec<(Address, String)> {
+    let (addresses, names): (Vec<_>, Vec<_>) = stream::iter(addresses.iter().copied())
+        .map(|address| async move {
+            let name = provider.lookup_address_at(&address, block).await.ok();
+            (address, name.filter(|name| !name.is_empty()))
+        })
+        .buffer_unordered(ENS_LOOKUP_CONCURRENCY)
+        .filter_map(|(address, name)| async move { Some((address, name?)) })
+        .unzip()
+        .await;
+    let resolved = provider.resolve_names_at(&names, block).await;
+    addresses
+        .into_iter()
+        .zip(names)
+        .zip(resolved)
+        .filter(|((address, _), resolved)| {
+            resolved.as_ref().is_ok_and(|resolved| resolved == address)
+        })
+        .map(|(found, _)| found)
+        .collect()
+}
+
 pub async fn print_traces(
     result: &mut TraceResult,
     decoder: &CallTraceDecoder,
     verbose: bool,
     state_changes: bool,
+    storage_reads: bool,
+    depth_limit: Option<usize>,
+    trace_filter: &[Address],
 ) -> Result<()> {
     let traces = result.traces.as_mut().expect("No traces found");
 
@@ -417,7 +723,27 @@ pub async fn print_traces(
 
     for (_, arena) in traces {
         decode_trace_arena(arena, decoder).await;
-        sh_println!("{}", render_trace_arena_inner(arena, verbose, state_changes))?;
+        label_selfdestructs(arena, decoder);
+        label_constructor_args(arena, decoder);
+        if !trace_filter.is_empty() {
+            filter_trace_addresses(&mut arena.arena, &trace_filter.iter().copied().collect());
+        }
+        if let Some(depth_limit) = depth_limit {
+            limit_trace_depth(&mut arena.arena, depth_limit);
+        }
+        if storage_reads {
+            label_storage_reads(&mut arena.arena, decoder);
+        }
+        if state_changes {
+            label_storage_changes(&mut arena.arena, decoder);
+        }
+        // Streamed to stdout as rendered, so that large traces are not buffered in memory.
+        if !shell::is_quiet() {
+            let mut out = BufWriter::new(ShellStdout);
+            write_trace_arena(arena, verbose, state_changes, &mut out)?;
+            out.write_all(b"\n")?;
+            out.flush()?;
+        }
     }
 
     if shell::is_json() {
@@ -427,14 +753,261 @@ pub async fn print_traces(
     sh_println!()?;
     if result.success {
         sh_println!("{}", "Transaction successfully executed.".green())?;
+    } else if result.out_of_gas {
+        sh_err!("Transaction ran out of gas.")?;
     } else {
         sh_err!("Transaction failed.")?;
     }
-    sh_println!("Gas used: {}", result.gas_used)?;
+    match result.gas_limit {
+        Some(gas_limit) => sh_println!("Gas used: {} / {gas_limit}", result.gas_used)?,
+        None => sh_println!("Gas used: {}", result.gas_used)?,
+    }
+
+    Ok(())
+}
+
+/// Writes to the stdout of the shell, locking it only for each write.
+struct ShellStdout;
+
+impl io::Write for ShellStdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Shell::get().out().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Shell::get().out().flush()
+    }
+}
+
+/// Decodes the traces and prints them as a structured JSON call tree.
+pub async fn print_json_traces(result: &mut TraceResult, decoder: &CallTraceDecoder) -> Result<()> {
+    let traces = result.traces.as_mut().expect("No traces found");
+
+    let mut calls = Vec::with_capacity(traces.len());
+    for (_, arena) in traces {
+        decode_trace_arena(arena, decoder).await;
+        calls.push(json_trace::build(arena));
+    }
+
+    sh_println!(
+        "{}",
+        serde_json::json!({
+            "success": result.success,
+            "outOfGas": result.out_of_gas,
+            "gasUsed": result.gas_used,
+            "gasLimit": result.gas_limit,
+            "traces": calls,
+        })
+    )?;
 
     Ok(())
 }
 
+/// Prints the logs emitted in the traces, grouped by emitting contract.
+///
+/// The traces must have been decoded beforehand, e.g. by [`print_traces`]. Logs that could not be
+/// decoded are printed with their raw topics and data.
+pub fn print_events(result: &TraceResult, decoder: &CallTraceDecoder) -> Result<()> {
+    let groups = group_events(result);
+
+    if shell::is_json() {
+        let groups = groups
+            .into_iter()
+            .map(|(address, logs)| {
+                serde_json::json!({
+                    "address": address,
+                    "label": decoder.labels.get(&address),
+                    "events": logs.into_iter().map(json_trace::JsonCallLog::from).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+        sh_println!("{}", serde_json::to_string(&groups)?)?;
+        return Ok(());
+    }
+
+    sh_println!("\nEvents:")?;
+    for (address, logs) in groups {
+        match decoder.labels.get(&address) {
+            Some(label) => sh_println!("{label} ({address}):")?,
+            None => sh_println!("{address}:")?,
+        }
+        for log in logs {
+            sh_println!("  {}", format_event(log))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Identifies the contracts called in the traces, unless skipped with `--quick`, in which case
+/// they are only decoded with the known ABIs and signatures.
+fn identify_traces(
+    result: &TraceResult,
+    decoder: &mut CallTraceDecoder,
+    identifier: &mut TraceIdentifiers<'_>,
+    skip_identification: bool,
+) {
+    if skip_identification {
+        return;
+    }
+    for (_, trace) in result.traces.as_deref().unwrap_or_default() {
+        decoder.identify(trace, identifier);
+    }
+}
+
+/// Groups the logs emitted in the traces by emitting contract, in order of first emission.
+fn group_events(result: &TraceResult) -> Vec<(Address, Vec<&CallLog>)> {
+    let mut groups: Vec<(Address, Vec<&CallLog>)> = Vec::new();
+    for node in result.traces.iter().flatten().flat_map(|(_, arena)| arena.nodes()) {
+        if node.logs.is_empty() {
+            continue;
+        }
+        let address = node.trace.address;
+        match groups.iter_mut().find(|(group_address, _)| *group_address == address) {
+            Some((_, logs)) => logs.extend(&node.logs),
+            None => groups.push((address, node.logs.iter().collect())),
+        }
+    }
+    groups
+}
+
+/// Formats a decoded log as `Name(param: value, ...)`, or with its raw topics and data if it could
+/// not be decoded.
+fn format_event(log: &CallLog) -> String {
+    if let Some(name) = &log.decoded.name {
+        let params = log.decoded.params.iter().flatten();
+        let params = params.map(|(name, value)| format!("{name}: {value}"));
+        format!("{name}({})", params.collect::<Vec<_>>().join(", "))
+    } else {
+        let topics = log.raw_log.topics().iter().map(ToString::to_string);
+        format!("topics: [{}], data: {}", topics.collect::<Vec<_>>().join(", "), log.raw_log.data)
+    }
+}
+
+/// Prints the function selectors called in the traces, in order of first call, along with their
+/// signature if it was identified.
+///
+/// The traces must have been decoded beforehand, e.g. by [`print_traces`].
+pub fn print_selectors(result: &TraceResult) -> Result<()> {
+    let selectors = collect_selectors(result);
+
+    if shell::is_json() {
+        let selectors = selectors
+            .into_iter()
+            .map(|(selector, signature)| {
+                serde_json::json!({ "selector": selector, "signature": signature })
+            })
+            .collect::<Vec<_>>();
+        sh_println!("{}", serde_json::to_string(&selectors)?)?;
+        return Ok(());
+    }
+
+    sh_println!("\nSelectors:")?;
+    for (selector, signature) in selectors {
+        match signature {
+            Some(signature) => sh_println!("  {selector}: {signature}")?,
+            None => sh_println!("  {selector}")?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the function selectors called in the traces, in order of first call, along with their
+/// signature if any of the calls was decoded. Creations and precompile calls are skipped.
+fn collect_selectors(result: &TraceResult) -> Vec<(Selector, Option<&str>)> {
+    let mut selectors: Vec<(Selector, Option<&str>)> = Vec::new();
+    let nodes = result.traces.iter().flatten().flat_map(|(_, arena)| arena.nodes());
+    for node in nodes.filter(|node| !node.trace.kind.is_any_create() && !node.is_precompile()) {
+        let Some(selector) = node.selector() else { continue };
+        let signature = node.trace.decoded.call_data.as_ref().map(|data| data.signature.as_str());
+        match selectors.iter_mut().find(|(known, _)| *known == selector) {
+            Some((_, known)) => *known = known.or(signature),
+            None => selectors.push((selector, signature)),
+        }
+    }
+    selectors
+}
+
+/// Prints a plain language narrative of the traces, with one sentence per call, indented by depth.
+///
+/// The traces must have been decoded beforehand, e.g. by [`print_traces`].
+pub fn print_explanation(result: &TraceResult, decoder: &CallTraceDecoder) -> Result<()> {
+    let nodes = result.traces.iter().flatten().flat_map(|(_, arena)| arena.nodes());
+
+    if shell::is_json() {
+        let sentences = nodes
+            .map(|node| {
+                serde_json::json!({
+                    "depth": node.trace.depth,
+                    "sentence": explain_call(node, decoder),
+                })
+            })
+            .collect::<Vec<_>>();
+        sh_println!("{}", serde_json::to_string(&sentences)?)?;
+        return Ok(());
+    }
+
+    sh_println!("\nExplanation:")?;
+    for node in nodes {
+        sh_println!("{}{}", "  ".repeat(node.trace.depth + 1), explain_call(node, decoder))?;
+    }
+
+    Ok(())
+}
+
+/// Describes a decoded call in a sentence, e.g. `Alice called transfer(Bob, 100) on Token.`.
+///
+/// Addresses are named by their label if they have one.
+fn explain_call(node: &CallTraceNode, decoder: &CallTraceDecoder) -> String {
+    let trace = &node.trace;
+    let name = |address: &Address| {
+        decoder.labels.get(address).cloned().unwrap_or_else(|| address.to_string())
+    };
+    let (caller, callee) = (name(&trace.caller), name(&trace.address));
+    let value = (!trace.value.is_zero()).then(|| {
+        let ether = format_ether(trace.value);
+        format!("{} ETH", ether.trim_end_matches('0').trim_end_matches('.'))
+    });
+
+    let function = trace.decoded.call_data.as_ref().map(|data| {
+        let name = data.signature.split('(').next().unwrap_or_default();
+        format!("{name}({})", data.args.join(", "))
+    });
+    let mut sentence = if trace.kind.is_any_create() {
+        format!("{caller} deployed {callee}")
+    } else if node.is_precompile() {
+        format!("{caller} called the precompile {callee}")
+    } else if let Some(function) = function.or_else(|| node.selector().map(|s| s.to_string())) {
+        match trace.kind {
+            CallKind::StaticCall => format!("{caller} queried {function} on {callee}"),
+            CallKind::DelegateCall | CallKind::CallCode => {
+                format!("{caller} ran {function} of {callee} in its own context")
+            }
+            _ => format!("{caller} called {function} on {callee}"),
+        }
+    } else if let Some(value) = &value {
+        format!("{caller} sent {value} to {callee}")
+    } else {
+        format!("{caller} called {callee}")
+    };
+
+    if let Some(value) = value.filter(|_| trace.kind.is_any_create() || !trace.data.is_empty()) {
+        write!(sentence, " with {value}").unwrap();
+    }
+    let output = trace.decoded.return_data.as_deref().filter(|output| !output.is_empty());
+    if !trace.success {
+        sentence.push_str(", which reverted");
+        if let Some(reason) = output {
+            write!(sentence, ": {reason}").unwrap();
+        }
+    } else if let Some(output) = output.filter(|_| !trace.kind.is_any_create()) {
+        write!(sentence, ", which returned {output}").unwrap();
+    }
+    sentence.push('.');
+    sentence
+}
+
 /// Traverse the artifacts in the project to generate local signatures and merge them into the cache
 /// file.
 pub fn cache_local_signatures(output: &ProjectCompileOutput, cache_dir: &Path) -> Result<()> {
@@ -455,3 +1028,242 @@ pub fn cache_local_signatures(output: &ProjectCompileOutput, cache_dir: &Path) -
     signatures.save(&path);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{hex, LogData, B256, U256};
+    use foundry_evm::traces::{
+        CallTraceArena, DecodedCallData, DecodedCallLog, SparsedTraceArena, TraceMemberOrder,
+    };
+
+    #[test]
+    fn can_parse_labels() {
+        let addr = Address::with_last_byte(1);
+        let labels = parse_labels(&[format!("{addr}:first"), format!("{addr}:second")]).unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[&addr], "second");
+
+        let err = parse_labels(&["0x1:label".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("`0x1:label`"));
+        assert!(parse_labels(&[format!("{addr}:")]).is_err());
+        assert!(parse_labels(&[addr.to_string()]).is_err());
+    }
+
+    #[test]
+    fn detects_out_of_gas() {
+        let raw = RawCallResult {
+            reverted: true,
+            exit_reason: InstructionResult::OutOfGas,
+            ..Default::default()
+        };
+        let result = TraceResult::from_raw(raw, TraceKind::Execution).with_gas_limit(Some(50_000));
+        assert!(result.out_of_gas);
+        assert_eq!(result.gas_limit, Some(50_000));
+
+        let raw = RawCallResult {
+            reverted: true,
+            exit_reason: InstructionResult::Revert,
+            ..Default::default()
+        };
+        assert!(!TraceResult::from_raw(raw, TraceKind::Execution).out_of_gas);
+    }
+
+    fn trace_result(arena: CallTraceArena) -> TraceResult {
+        TraceResult {
+            success: true,
+            traces: Some(vec![(
+                TraceKind::Execution,
+                SparsedTraceArena { arena, ignored: Default::default() },
+            )]),
+            gas_used: 0,
+            out_of_gas: false,
+            gas_limit: None,
+        }
+    }
+
+    #[test]
+    fn groups_events_by_emitter() {
+        let [token, vault] = [1, 2].map(Address::with_last_byte);
+        let transfer = CallLog {
+            raw_log: LogData::new_unchecked(vec![B256::with_last_byte(1)], Default::default()),
+            decoded: DecodedCallLog {
+                name: Some("Transfer".to_string()),
+                params: Some(vec![
+                    ("to".to_string(), vault.to_string()),
+                    ("value".to_string(), "100".to_string()),
+                ]),
+            },
+            ..Default::default()
+        };
+        let raw = CallLog {
+            raw_log: LogData::new_unchecked(
+                vec![B256::with_last_byte(2), B256::with_last_byte(3)],
+                hex!("1234").into(),
+            ),
+            ..Default::default()
+        };
+
+        // token -> vault -> token, with each call emitting a log.
+        let mut arena = CallTraceArena::default();
+        for (idx, address) in [token, vault, token].into_iter().enumerate() {
+            if idx > 0 {
+                let parent = Some(idx - 1);
+                arena.nodes_mut().push(CallTraceNode { idx, parent, ..Default::default() });
+                let parent = &mut arena.nodes_mut()[idx - 1];
+                parent.ordering.push(TraceMemberOrder::Call(0));
+                parent.children.push(idx);
+            }
+            let node = &mut arena.nodes_mut()[idx];
+            node.trace.address = address;
+            node.logs.push(if address == token { transfer.clone() } else { raw.clone() });
+        }
+        let result = trace_result(arena);
+
+        let groups = group_events(&result);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0], (token, vec![&transfer, &transfer]));
+        assert_eq!(groups[1], (vault, vec![&raw]));
+
+        assert_eq!(format_event(&transfer), format!("Transfer(to: {vault}, value: 100)"));
+        assert_eq!(
+            format_event(&raw),
+            format!(
+                "topics: [{}, {}], data: 0x1234",
+                B256::with_last_byte(2),
+                B256::with_last_byte(3)
+            )
+        );
+    }
+
+    #[test]
+    fn skips_identification() {
+        use foundry_compilers::artifacts::{
+            BytecodeObject, CompactContractBytecode, CompactDeployedBytecode,
+        };
+
+        let code = hex!("6080604052348015600e575f5ffd5b50");
+        let id = ArtifactId {
+            path: PathBuf::from("out/Counter.sol/Counter.json"),
+            name: "Counter".to_string(),
+            source: PathBuf::from("src/Counter.sol"),
+            version: "0.8.28".parse().unwrap(),
+            build_id: String::new(),
+            profile: "default".to_string(),
+        };
+        let artifact = CompactContractBytecode {
+            abi: Some(Default::default()),
+            bytecode: None,
+            deployed_bytecode: Some(CompactDeployedBytecode {
+                bytecode: Some(CompactBytecode {
+                    object: BytecodeObject::Bytecode(code.into()),
+                    source_map: None,
+                    link_references: Default::default(),
+                }),
+                immutable_references: Default::default(),
+            }),
+        };
+        let known_contracts = ContractsByArtifact::new([(id, artifact)]);
+
+        // The creation of a contract whose runtime code matches the artifact.
+        let counter = Address::with_last_byte(1);
+        let mut arena = CallTraceArena::default();
+        let root = &mut arena.nodes_mut()[0].trace;
+        root.kind = CallKind::Create;
+        root.address = counter;
+        root.output = code.into();
+        let result = trace_result(arena);
+
+        for skip_identification in [true, false] {
+            let mut decoder = CallTraceDecoderBuilder::new().build();
+            let mut identifier = TraceIdentifiers::new().with_local(&known_contracts);
+            identify_traces(&result, &mut decoder, &mut identifier, skip_identification);
+            let identified = decoder.contracts.get(&counter).map(String::as_str);
+            assert_eq!(identified, (!skip_identification).then_some("src/Counter.sol:Counter"));
+        }
+    }
+
+    #[test]
+    fn collects_selectors() {
+        let transfer =
+            DecodedCallData { signature: "transfer(address,uint256)".to_string(), args: vec![] };
+        let approve =
+            DecodedCallData { signature: "approve(address,uint256)".to_string(), args: vec![] };
+        // The calls made by the root call, which calls `transfer` without decoding it.
+        let calls = [
+            (CallKind::Call, false, hex!("095ea7b3").to_vec(), Some(approve)),
+            (CallKind::Create, false, hex!("60806040").to_vec(), None),
+            (CallKind::StaticCall, true, hex!("00000001").to_vec(), None),
+            (CallKind::Call, false, hex!("a9059cbb").to_vec(), Some(transfer)),
+            (CallKind::Call, false, hex!("095ea7b3").to_vec(), None),
+            (CallKind::Call, false, vec![], None),
+        ];
+
+        let mut arena = CallTraceArena::default();
+        arena.nodes_mut()[0].trace.data = hex!("a9059cbb00").to_vec().into();
+        for (idx, (kind, precompile, data, decoded)) in calls.into_iter().enumerate() {
+            let mut node = CallTraceNode { idx: idx + 1, parent: Some(0), ..Default::default() };
+            node.trace.kind = kind;
+            node.trace.maybe_precompile = Some(precompile);
+            node.trace.data = data.into();
+            node.trace.decoded.call_data = decoded;
+            arena.nodes_mut().push(node);
+            let root = &mut arena.nodes_mut()[0];
+            root.ordering.push(TraceMemberOrder::Call(idx));
+            root.children.push(idx + 1);
+        }
+        let result = trace_result(arena);
+
+        assert_eq!(
+            collect_selectors(&result),
+            [
+                (Selector::from(hex!("a9059cbb")), Some("transfer(address,uint256)")),
+                (Selector::from(hex!("095ea7b3")), Some("approve(address,uint256)")),
+            ]
+        );
+    }
+
+    #[test]
+    fn explains_calls() {
+        let [alice, bob, token] = [1, 2, 3].map(Address::with_last_byte);
+        let decoder = CallTraceDecoderBuilder::new()
+            .with_labels([(alice, "Alice".to_string()), (token, "Token".to_string())])
+            .build();
+
+        let mut node = CallTraceNode::default();
+        node.trace.caller = alice;
+        node.trace.address = token;
+        node.trace.success = true;
+        node.trace.data = hex!("a9059cbb").to_vec().into();
+        node.trace.decoded.call_data = Some(DecodedCallData {
+            signature: "transfer(address,uint256)".to_string(),
+            args: vec![bob.to_string(), "100".to_string()],
+        });
+        node.trace.decoded.return_data = Some("true".to_string());
+        assert_eq!(
+            explain_call(&node, &decoder),
+            format!("Alice called transfer({bob}, 100) on Token, which returned true.")
+        );
+
+        node.trace.success = false;
+        node.trace.decoded.return_data = Some("insufficient balance".to_string());
+        assert_eq!(
+            explain_call(&node, &decoder),
+            format!(
+                "Alice called transfer({bob}, 100) on Token, which reverted: insufficient balance."
+            )
+        );
+
+        let mut node = CallTraceNode::default();
+        node.trace.caller = alice;
+        node.trace.address = bob;
+        node.trace.success = true;
+        node.trace.value = U256::from(1_500_000_000_000_000_000u128);
+        assert_eq!(explain_call(&node, &decoder), format!("Alice sent 1.5 ETH to {bob}."));
+
+        node.trace.kind = CallKind::Create;
+        node.trace.address = token;
+        node.trace.data = hex!("6080").to_vec().into();
+        assert_eq!(explain_call(&node, &decoder), "Alice deployed Token with 1.5 ETH.");
+    }
+}
@@ -0,0 +1,528 @@
+use alloy_primitives::{hex::ToHexExt, Selector};
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, Cell, Color, Table};
+use foundry_common::shell;
+use foundry_evm::{
+    revm::interpreter::{opcode, OpCode},
+    traces::{CallTraceDecoder, CallTraceNode, TraceMemberOrder},
+};
+use serde::Serialize;
+use std::{cmp::Reverse, collections::BTreeMap, fmt};
+
+/// Gas usage of a single transaction trace, grouped by contract and function.
+///
+/// The same report is rendered as tables, and serialized as JSON with `--json`. The JSON field
+/// names are stable, so that the reports of different commits can be diffed:
+///
+/// ```json
+/// {
+///   "contracts": {
+///     "<contract name or address>": {
+///       "gas": 0,
+///       "calls": 0,
+///       "functions": {
+///         "<function signature, selector, `constructor` or `fallback`>": {
+///           "selector": "0x00000000",
+///           "calls": 0,
+///           "min": 0,
+///           "avg": 0,
+///           "max": 0,
+///           "total": 0
+///         }
+///       }
+///     }
+///   }
+/// }
+/// ```
+///
+/// Contracts and functions are sorted by name, and `selector` is `null` for constructors and
+/// fallbacks. The [sort order](Self::with_sort) of the tables does not apply to the JSON.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TraceGasReport {
+    /// Contract name, or address if unidentified -> gas info.
+    pub contracts: BTreeMap<String, ContractGasInfo>,
+    /// The order of the rendered contracts and functions.
+    #[serde(skip)]
+    sort_by: GasReportSort,
+    /// The maximum number of rendered functions per contract.
+    #[serde(skip)]
+    top: Option<usize>,
+}
+
+/// The order of the contracts and functions of a rendered [`TraceGasReport`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GasReportSort {
+    /// By name.
+    #[default]
+    Name,
+    /// By total gas used, descending.
+    Total,
+    /// By number of calls, descending.
+    Calls,
+    /// By average gas used per call, descending.
+    Avg,
+}
+
+impl GasReportSort {
+    /// Returns the key to sort the rendered items by, in ascending order.
+    fn key(self, calls: u64, total: u64) -> Reverse<u64> {
+        Reverse(match self {
+            Self::Name => 0,
+            Self::Total => total,
+            Self::Calls => calls,
+            Self::Avg => total / calls.max(1),
+        })
+    }
+}
+
+impl TraceGasReport {
+    /// Builds the gas report from the nodes of decoded trace arenas.
+    pub fn new<'a>(
+        nodes: impl IntoIterator<Item = &'a CallTraceNode>,
+        decoder: &CallTraceDecoder,
+    ) -> Self {
+        let mut report = Self::default();
+        for node in nodes {
+            report.analyze_node(node, decoder);
+        }
+        report.finalize()
+    }
+
+    /// Sets the order of the rendered contracts and functions, and optionally only renders the
+    /// `top` first functions of each contract in this order.
+    pub fn with_sort(mut self, sort_by: GasReportSort, top: Option<usize>) -> Self {
+        self.sort_by = sort_by;
+        self.top = top;
+        self
+    }
+
+    fn analyze_node(&mut self, node: &CallTraceNode, decoder: &CallTraceDecoder) {
+        let trace = &node.trace;
+
+        let contract = decoder
+            .contracts
+            .get(&trace.address)
+            .map(|id| id.rsplit(':').next().unwrap_or(id).to_string())
+            .or_else(|| trace.decoded.label.clone())
+            .unwrap_or_else(|| trace.address.to_string());
+
+        let function = if trace.kind.is_any_create() {
+            "constructor".to_string()
+        } else if let Some(call_data) = &trace.decoded.call_data {
+            call_data.signature.clone()
+        } else if let Some(selector) = node.selector() {
+            selector.encode_hex_with_prefix()
+        } else {
+            "fallback".to_string()
+        };
+
+        let selector = if trace.kind.is_any_create() { None } else { node.selector() };
+
+        let contract_info = self.contracts.entry(contract).or_default();
+        contract_info.gas += trace.gas_used;
+        contract_info.calls += 1;
+        let function_info = contract_info.functions.entry(function).or_default();
+        function_info.selector = selector;
+        function_info.frames.push(trace.gas_used);
+    }
+
+    fn finalize(mut self) -> Self {
+        for contract in self.contracts.values_mut() {
+            for func in contract.functions.values_mut() {
+                func.calls = func.frames.len() as u64;
+                func.min = func.frames.iter().copied().min().unwrap_or_default();
+                func.max = func.frames.iter().copied().max().unwrap_or_default();
+                func.total = func.frames.iter().sum();
+                func.avg = func.total / func.calls.max(1);
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for TraceGasReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Sorting is stable, so items with the same key stay sorted by name.
+        let mut contracts = self.contracts.iter().collect::<Vec<_>>();
+        contracts.sort_by_key(|(_, contract)| self.sort_by.key(contract.calls, contract.gas));
+        for (name, contract) in contracts {
+            let mut table = Table::new();
+            table.apply_modifier(UTF8_ROUND_CORNERS);
+            if shell::out_supports_color() {
+                table.enforce_styling();
+            } else {
+                table.force_no_tty();
+            }
+
+            table.set_header(vec![Cell::new(format!("{name} Contract")).fg(Color::Magenta)]);
+
+            table.add_row(vec![
+                Cell::new("Total Gas").fg(Color::Cyan),
+                Cell::new("# Calls").fg(Color::Cyan),
+            ]);
+            table.add_row(vec![
+                Cell::new(contract.gas.to_string()),
+                Cell::new(contract.calls.to_string()),
+            ]);
+
+            // Add a blank row to separate contract info from function info.
+            table.add_row(vec![Cell::new("")]);
+
+            table.add_row(vec![
+                Cell::new("Function Name"),
+                Cell::new("Min").fg(Color::Green),
+                Cell::new("Avg").fg(Color::Yellow),
+                Cell::new("Max").fg(Color::Red),
+                Cell::new("# Calls").fg(Color::Cyan),
+            ]);
+            let mut functions = contract.functions.iter().collect::<Vec<_>>();
+            functions.sort_by_key(|(_, func)| self.sort_by.key(func.calls, func.total));
+            functions.truncate(self.top.unwrap_or(usize::MAX));
+            for (function, gas_info) in functions {
+                table.add_row(vec![
+                    Cell::new(function),
+                    Cell::new(gas_info.min.to_string()).fg(Color::Green),
+                    Cell::new(gas_info.avg.to_string()).fg(Color::Yellow),
+                    Cell::new(gas_info.max.to_string()).fg(Color::Red),
+                    Cell::new(gas_info.calls.to_string()),
+                ]);
+            }
+
+            writeln!(f, "\n{table}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gas usage of the calls to a contract.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ContractGasInfo {
+    /// Total gas used by the calls to the contract, including their subcalls.
+    pub gas: u64,
+    /// Number of calls to the contract.
+    pub calls: u64,
+    /// Function signature, or selector if unidentified -> gas info.
+    pub functions: BTreeMap<String, FunctionGasInfo>,
+}
+
+/// Gas usage of the calls to a function.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FunctionGasInfo {
+    /// Selector of the function, if it was called with one.
+    pub selector: Option<Selector>,
+    pub calls: u64,
+    pub min: u64,
+    pub avg: u64,
+    pub max: u64,
+    /// Total gas used by the calls to the function.
+    pub total: u64,
+
+    #[serde(skip)]
+    pub frames: Vec<u64>,
+}
+
+/// The category of an opcode in an [`OpcodeGasReport`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpcodeCategory {
+    /// Storage accesses: `SLOAD`, `SSTORE`, `TLOAD` and `TSTORE`.
+    Storage,
+    /// Memory accesses and copies to memory, including the memory expansion costs: `MLOAD`,
+    /// `MSTORE`, `MSTORE8`, `MCOPY`, `MSIZE`, `CALLDATACOPY`, `CODECOPY` and `RETURNDATACOPY`.
+    Memory,
+    /// Accesses to the state of other accounts: `BALANCE`, `EXTCODESIZE`, `EXTCODECOPY`,
+    /// `EXTCODEHASH` and `BLOCKHASH`.
+    State,
+    /// Calls and contract creations, excluding the gas forwarded to the called contract: `CALL`,
+    /// `CALLCODE`, `DELEGATECALL`, `STATICCALL`, `CREATE`, `CREATE2`, `SELFDESTRUCT` and their EOF
+    /// equivalents.
+    Calls,
+    /// Events: `LOG0` to `LOG4`.
+    Logs,
+    /// Everything else: arithmetic, stack, control flow, hashing and environment opcodes.
+    Compute,
+}
+
+impl OpcodeCategory {
+    /// Returns the category of `op`.
+    pub fn of(op: OpCode) -> Self {
+        match op.get() {
+            opcode::SLOAD | opcode::SSTORE | opcode::TLOAD | opcode::TSTORE => Self::Storage,
+            opcode::MLOAD |
+            opcode::MSTORE |
+            opcode::MSTORE8 |
+            opcode::MCOPY |
+            opcode::MSIZE |
+            opcode::CALLDATACOPY |
+            opcode::CODECOPY |
+            opcode::RETURNDATACOPY => Self::Memory,
+            opcode::BALANCE |
+            opcode::EXTCODESIZE |
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::BLOCKHASH => Self::State,
+            opcode::CALL |
+            opcode::CALLCODE |
+            opcode::DELEGATECALL |
+            opcode::STATICCALL |
+            opcode::CREATE |
+            opcode::CREATE2 |
+            opcode::SELFDESTRUCT |
+            opcode::EXTCALL |
+            opcode::EXTDELEGATECALL |
+            opcode::EXTSTATICCALL |
+            opcode::EOFCREATE => Self::Calls,
+            opcode::LOG0..=opcode::LOG4 => Self::Logs,
+            _ => Self::Compute,
+        }
+    }
+}
+
+impl fmt::Display for OpcodeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Storage => "Storage",
+            Self::Memory => "Memory",
+            Self::State => "State",
+            Self::Calls => "Calls",
+            Self::Logs => "Logs",
+            Self::Compute => "Compute",
+        })
+    }
+}
+
+/// Gas used by the executed opcodes of a trace, grouped by [`OpcodeCategory`].
+///
+/// Requires the steps of the trace to be recorded. The gas of each call only includes its own
+/// opcodes, and the gas forwarded by call opcodes is attributed to the opcodes of the called
+/// contract instead.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OpcodeGasReport {
+    /// Total gas used by the executed opcodes.
+    pub gas: u64,
+    /// Category -> gas info.
+    pub categories: BTreeMap<OpcodeCategory, OpcodeGasInfo>,
+    /// Opcode name -> gas info.
+    pub opcodes: BTreeMap<String, OpcodeGasInfo>,
+}
+
+/// Gas used by a category of opcodes, or a single opcode.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OpcodeGasInfo {
+    /// Total gas used.
+    pub gas: u64,
+    /// Number of executions.
+    pub count: u64,
+}
+
+impl OpcodeGasReport {
+    /// Builds the report from the recorded steps of the nodes of trace arenas.
+    pub fn new<'a>(nodes: impl IntoIterator<Item = &'a [CallTraceNode]>) -> Self {
+        let mut report = Self::default();
+        for nodes in nodes {
+            for node in nodes {
+                report.analyze_node(node, nodes);
+            }
+        }
+        report
+    }
+
+    fn analyze_node(&mut self, node: &CallTraceNode, nodes: &[CallTraceNode]) {
+        let mut pending = None;
+        for member in &node.ordering {
+            match *member {
+                TraceMemberOrder::Step(idx) => {
+                    if let Some((op, gas)) = pending.take() {
+                        self.record(op, gas);
+                    }
+                    let step = &node.trace.steps[idx];
+                    pending = Some((step.op, step.gas_cost));
+                }
+                // The gas forwarded to a call is deducted by the step that made it.
+                TraceMemberOrder::Call(child) => {
+                    if let Some((_, gas)) = &mut pending {
+                        *gas = gas.saturating_sub(nodes[node.children[child]].trace.gas_limit);
+                    }
+                }
+                TraceMemberOrder::Log(_) => {}
+            }
+        }
+        if let Some((op, gas)) = pending {
+            self.record(op, gas);
+        }
+    }
+
+    fn record(&mut self, op: OpCode, gas: u64) {
+        self.gas += gas;
+        for info in [
+            self.categories.entry(OpcodeCategory::of(op)).or_default(),
+            self.opcodes.entry(op.as_str().to_string()).or_default(),
+        ] {
+            info.gas += gas;
+            info.count += 1;
+        }
+    }
+}
+
+impl fmt::Display for OpcodeGasReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.apply_modifier(UTF8_ROUND_CORNERS);
+        if shell::out_supports_color() {
+            table.enforce_styling();
+        } else {
+            table.force_no_tty();
+        }
+
+        table.set_header(vec![
+            Cell::new("Category").fg(Color::Magenta),
+            Cell::new("Gas").fg(Color::Cyan),
+            Cell::new("%").fg(Color::Cyan),
+            Cell::new("# Opcodes").fg(Color::Cyan),
+        ]);
+        let mut categories = self.categories.iter().collect::<Vec<_>>();
+        categories.sort_by(|(_, a), (_, b)| b.gas.cmp(&a.gas));
+        for (category, info) in categories {
+            let share = info.gas as f64 * 100.0 / self.gas.max(1) as f64;
+            table.add_row(vec![
+                Cell::new(category),
+                Cell::new(info.gas.to_string()),
+                Cell::new(format!("{share:.1}")),
+                Cell::new(info.count.to_string()),
+            ]);
+        }
+        table.add_row(vec![Cell::new("Total").fg(Color::Cyan), Cell::new(self.gas.to_string())]);
+
+        writeln!(f, "\n{table}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes};
+    use foundry_evm::{
+        revm::interpreter::InstructionResult,
+        traces::{CallKind, CallTraceStep, DecodedCallData},
+    };
+
+    #[test]
+    fn serializes_stable_schema() {
+        let token = Address::repeat_byte(0xaa);
+        let node = |kind, data: &[u8], signature: Option<&str>, gas_used| {
+            let mut node = CallTraceNode::default();
+            node.trace.kind = kind;
+            node.trace.address = token;
+            node.trace.data = Bytes::copy_from_slice(data);
+            node.trace.gas_used = gas_used;
+            node.trace.decoded.call_data = signature
+                .map(|signature| DecodedCallData { signature: signature.into(), args: vec![] });
+            node
+        };
+        let transfer = [0xa9, 0x05, 0x9c, 0xbb];
+        let nodes = [
+            node(CallKind::Create, &[0x60, 0x80], None, 100_000),
+            node(CallKind::Call, &transfer, Some("transfer(address,uint256)"), 30_000),
+            node(CallKind::Call, &transfer, Some("transfer(address,uint256)"), 10_000),
+            node(CallKind::Call, &[0x12, 0x34, 0x56, 0x78], None, 5_000),
+            node(CallKind::Call, &[], None, 2_100),
+        ];
+
+        let mut decoder = CallTraceDecoder::default();
+        decoder.contracts.insert(token, "src/Token.sol:Token".to_string());
+
+        let report = TraceGasReport::new(&nodes, &decoder);
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        assert_eq!(json, include_str!("../../test-data/gas_report.json").trim_end());
+    }
+
+    #[test]
+    fn sorts_rendered_functions() {
+        let node = |address, data: &[u8], gas_used| {
+            let mut node = CallTraceNode::default();
+            node.trace.address = address;
+            node.trace.data = Bytes::copy_from_slice(data);
+            node.trace.gas_used = gas_used;
+            node
+        };
+        let (token, vault) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+        let nodes = [
+            node(token, &[0x11; 4], 10_000),
+            node(token, &[0x22; 4], 3_000),
+            node(token, &[0x22; 4], 3_000),
+            node(vault, &[0x33; 4], 50_000),
+        ];
+        let report = TraceGasReport::new(&nodes, &CallTraceDecoder::default());
+        let position = |rendered: &str, item: &str| rendered.find(item).unwrap();
+
+        let rendered = report.clone().to_string();
+        assert!(position(&rendered, &token.to_string()) < position(&rendered, &vault.to_string()));
+        assert!(position(&rendered, "0x11111111") < position(&rendered, "0x22222222"));
+
+        let rendered = report.clone().with_sort(GasReportSort::Total, None).to_string();
+        assert!(position(&rendered, &vault.to_string()) < position(&rendered, &token.to_string()));
+        assert!(position(&rendered, "0x11111111") < position(&rendered, "0x22222222"));
+
+        let rendered = report.with_sort(GasReportSort::Calls, Some(1)).to_string();
+        assert!(rendered.contains("0x22222222"));
+        assert!(!rendered.contains("0x11111111"));
+        assert!(rendered.contains("0x33333333"));
+    }
+
+    #[test]
+    fn groups_opcode_gas_by_category() {
+        let step = |op, gas_cost| CallTraceStep {
+            depth: 0,
+            pc: 0,
+            code_section_idx: 0,
+            op: OpCode::new(op).unwrap(),
+            contract: Address::ZERO,
+            stack: None,
+            push_stack: None,
+            memory: None,
+            returndata: Default::default(),
+            gas_remaining: 0,
+            gas_refund_counter: 0,
+            gas_used: 0,
+            gas_cost,
+            storage_change: None,
+            status: InstructionResult::Continue,
+            immediate_bytes: None,
+            decoded: None,
+        };
+
+        let mut caller = CallTraceNode::default();
+        caller.children = vec![1];
+        caller.trace.steps = vec![
+            step(opcode::PUSH1, 3),
+            step(opcode::SLOAD, 2100),
+            step(opcode::CALL, 2600 + 5000),
+            step(opcode::STOP, 0),
+        ];
+        caller.ordering = vec![
+            TraceMemberOrder::Step(0),
+            TraceMemberOrder::Step(1),
+            TraceMemberOrder::Step(2),
+            TraceMemberOrder::Call(0),
+            TraceMemberOrder::Step(3),
+        ];
+        let mut callee = CallTraceNode { idx: 1, parent: Some(0), ..Default::default() };
+        callee.trace.gas_limit = 5000;
+        callee.trace.steps = vec![step(opcode::MSTORE, 6), step(opcode::LOG1, 750)];
+        callee.ordering = vec![TraceMemberOrder::Step(0), TraceMemberOrder::Step(1)];
+        let nodes = [caller, callee];
+
+        let report = OpcodeGasReport::new([&nodes[..]]);
+        assert_eq!(report.gas, 3 + 2100 + 2600 + 6 + 750);
+        let category = |category| {
+            report.categories.get(&category).map(|info| (info.gas, info.count)).unwrap_or_default()
+        };
+        assert_eq!(category(OpcodeCategory::Storage), (2100, 1));
+        assert_eq!(category(OpcodeCategory::Calls), (2600, 1));
+        assert_eq!(category(OpcodeCategory::Memory), (6, 1));
+        assert_eq!(category(OpcodeCategory::Logs), (750, 1));
+        assert_eq!(category(OpcodeCategory::Compute), (3, 2));
+        assert_eq!(category(OpcodeCategory::State), (0, 0));
+        assert_eq!(report.opcodes["SLOAD"].gas, 2100);
+    }
+}
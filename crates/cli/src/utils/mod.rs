@@ -26,6 +26,9 @@
 mod abi;
 pub use abi::*;
 
+mod gas_report;
+pub use gas_report::*;
+
 // reexport all `foundry_config::utils`
 #[doc(hidden)]
 pub use foundry_config::utils::*;
@@ -108,6 +111,8 @@ pub fn get_provider_builder(config: &Config) -> Result<ProviderBuilder> {
         builder = builder.timeout(Duration::from_secs(rpc_timeout));
     }
 
+    builder = builder.maybe_transient_retries(config.eth_rpc_retries);
+
     if let Some(rpc_headers) = config.eth_rpc_headers.clone() {
         builder = builder.headers(rpc_headers);
     }
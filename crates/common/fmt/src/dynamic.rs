@@ -92,6 +92,77 @@ fn tuple(&self, values: &[DynSolValue], f: &mut fmt::Formatter<'_>) -> fmt::Resu
     }
 }
 
+/// Multi-line [`DynSolValue`] formatter.
+struct PrettyDynValueFormatter {
+    /// The formatter of the values that fit on a single line.
+    inline: DynValueFormatter,
+}
+
+impl PrettyDynValueFormatter {
+    /// The indentation of each nesting level.
+    const INDENT: &'static str = "    ";
+
+    /// Recursively formats a [`DynSolValue`], with one line per element of tuples and arrays.
+    fn value(&self, value: &DynSolValue, indent: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match value {
+            DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+                if values.is_empty() {
+                    return f.write_str("[]");
+                }
+                f.write_str("[\n")?;
+                for (i, value) in values.iter().enumerate() {
+                    write!(f, "{}[{i}]: ", Self::INDENT.repeat(indent + 1))?;
+                    self.value(value, indent + 1, f)?;
+                    f.write_str(",\n")?;
+                }
+                write!(f, "{}]", Self::INDENT.repeat(indent))
+            }
+            DynSolValue::Tuple(values) => self.fields(values, None, indent, f),
+            DynSolValue::CustomStruct { name, prop_names, tuple } => {
+                f.write_str(name)?;
+                let names = (prop_names.len() == tuple.len()).then_some(prop_names.as_slice());
+                self.fields(tuple, names, indent, f)
+            }
+            _ => self.inline.value(value, f),
+        }
+    }
+
+    /// Formats the fields of a tuple, labeled with their names if any.
+    fn fields(
+        &self,
+        values: &[DynSolValue],
+        names: Option<&[String]>,
+        indent: usize,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if values.is_empty() {
+            return f.write_str("()");
+        }
+        f.write_str("(\n")?;
+        for (i, value) in values.iter().enumerate() {
+            f.write_str(&Self::INDENT.repeat(indent + 1))?;
+            if let Some(name) = names.and_then(|names| names.get(i)).filter(|name| !name.is_empty())
+            {
+                write!(f, "{name}: ")?;
+            }
+            self.value(value, indent + 1, f)?;
+            f.write_str(",\n")?;
+        }
+        write!(f, "{})", Self::INDENT.repeat(indent))
+    }
+}
+
+/// Wrapper that implements [`Display`](fmt::Display) for a [`DynSolValue`] formatted over
+/// multiple lines.
+struct PrettyDynValueDisplay<'a>(&'a DynSolValue);
+
+impl fmt::Display for PrettyDynValueDisplay<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        PrettyDynValueFormatter { inline: DynValueFormatter { raw: false } }.value(self.0, 0, f)
+    }
+}
+
 /// Wrapper that implements [`Display`](fmt::Display) for a [`DynSolValue`].
 struct DynValueDisplay<'a> {
     /// The value to display.
@@ -137,6 +208,15 @@ pub fn format_token(value: &DynSolValue) -> String {
     DynValueDisplay::new(value, false).to_string()
 }
 
+/// Pretty-prints the given value like [`format_token`], but over multiple indented lines for
+/// tuples and arrays.
+///
+/// The fields of structs are labeled with their names, and the elements of arrays with their
+/// indices. Other values are formatted on a single line, as with [`format_token`].
+pub fn format_token_pretty(value: &DynSolValue) -> String {
+    PrettyDynValueDisplay(value).to_string()
+}
+
 /// Pretty-prints the given value into a string suitable for re-parsing as values later.
 ///
 /// This means:
@@ -164,6 +244,37 @@ fn parse_hex_uint() {
         assert_eq!(values, [DynSolValue::Uint(U256::from(100), 256)]);
     }
 
+    #[test]
+    fn format_pretty() {
+        let value = DynSolValue::CustomStruct {
+            name: String::new(),
+            prop_names: vec!["owner".to_string(), "amounts".to_string(), "inner".to_string()],
+            tuple: vec![
+                DynSolValue::Address(address!("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")),
+                DynSolValue::Array(vec![
+                    DynSolValue::Uint(U256::from(1), 256),
+                    DynSolValue::Uint(U256::from(2), 256),
+                ]),
+                DynSolValue::Tuple(vec![DynSolValue::Bool(true), DynSolValue::Array(vec![])]),
+            ],
+        };
+        assert_eq!(
+            format_token_pretty(&value),
+            "(
+    owner: 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed,
+    amounts: [
+        [0]: 1,
+        [1]: 2,
+    ],
+    inner: (
+        true,
+        [],
+    ),
+)"
+        );
+        assert_eq!(format_token_pretty(&DynSolValue::Uint(U256::from(7), 256)), "7");
+    }
+
     #[test]
     fn format_addr() {
         // copied from testcases in https://github.com/ethereum/EIPs/blob/master/EIPS/eip-55.md
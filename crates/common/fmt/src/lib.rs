@@ -4,7 +4,10 @@
 pub use console::{console_format, ConsoleFmt, FormatSpec};
 
 mod dynamic;
-pub use dynamic::{format_token, format_token_raw, format_tokens, format_tokens_raw, parse_tokens};
+pub use dynamic::{
+    format_token, format_token_pretty, format_token_raw, format_tokens, format_tokens_raw,
+    parse_tokens,
+};
 
 mod exp;
 pub use exp::{format_int_exp, format_uint_exp, to_exp_notation};
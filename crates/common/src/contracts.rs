@@ -15,7 +15,7 @@
 };
 use std::{
     collections::BTreeMap,
-    ops::Deref,
+    ops::{Deref, Range},
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
@@ -41,6 +41,18 @@ impl BytecodeData {
     fn bytes(&self) -> Option<&Bytes> {
         self.object.as_ref().and_then(|b| b.as_bytes())
     }
+
+    /// Returns the byte ranges of the immutable variables in the bytecode.
+    pub fn immutable_ranges(&self) -> Vec<Range<usize>> {
+        self.immutable_references
+            .values()
+            .flatten()
+            .map(|offsets| {
+                let start = offsets.start as usize;
+                start..start + offsets.length as usize
+            })
+            .collect()
+    }
 }
 
 impl From<CompactBytecode> for BytecodeData {
@@ -376,6 +388,60 @@ pub fn bytecode_diff_score<'a>(mut a: &'a [u8], mut b: &'a [u8]) -> f64 {
     n_different_bytes as f64 / a.len() as f64
 }
 
+/// Same as [`bytecode_diff_score`], but ignores the given immutable variable ranges and the
+/// trailing CBOR metadata of both bytecodes.
+///
+/// This allows matching the same contract deployed with different immutables, or compiled with a
+/// different metadata hash. As code without metadata can end with bytes that look like it, the
+/// bytecodes are also compared as is, and the lower score is returned.
+pub fn bytecode_diff_score_masked(a: &[u8], b: &[u8], immutable_ranges: &[Range<usize>]) -> f64 {
+    let score = |a: &[u8], b: &[u8]| {
+        if immutable_ranges.is_empty() {
+            return bytecode_diff_score(a, b);
+        }
+
+        let mask = |code: &[u8]| {
+            let mut code = code.to_vec();
+            for range in immutable_ranges {
+                let end = range.end.min(code.len());
+                if range.start < end {
+                    code[range.start..end].fill(0);
+                }
+            }
+            code
+        };
+        bytecode_diff_score(&mask(a), &mask(b))
+    };
+
+    let stripped = score(strip_metadata(a), strip_metadata(b));
+    if stripped == 0.0 || (metadata_range(a).is_none() && metadata_range(b).is_none()) {
+        return stripped;
+    }
+    stripped.min(score(a, b))
+}
+
+/// Returns the range of the CBOR-encoded metadata appended by the compiler, including its length
+/// suffix, if any.
+///
+/// The metadata is followed by its length as a big-endian `u16`.
+pub fn metadata_range(code: &[u8]) -> Option<Range<usize>> {
+    let (rest, len) = code.split_last_chunk::<2>()?;
+    let len = u16::from_be_bytes(*len) as usize;
+    let start = rest.len().checked_sub(len)?;
+    // The metadata is a CBOR map.
+    (len > 0 && matches!(rest[start], 0xa0..=0xbf)).then_some(start..code.len())
+}
+
+/// Strips the CBOR-encoded metadata appended by the compiler, if any.
+///
+/// See [`metadata_range`].
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    match metadata_range(code) {
+        Some(range) => &code[..range.start],
+        None => code,
+    }
+}
+
 /// Returns the amount of different bytes between two slices.
 ///
 /// # Safety
@@ -524,6 +590,44 @@ fn bytecode_diffing() {
         assert!(bytecode_diff_score(a_100, a_99) <= 0.01);
     }
 
+    #[test]
+    fn masked_bytecode_diffing() {
+        // `PUSH32 <immutable>`, followed by metadata `{"a": 1}` and its length.
+        let code = |immutable: u8, metadata_value: u8| {
+            let mut code = vec![0x7f];
+            code.extend([immutable; 32]);
+            code.extend([0x00; 8]);
+            code.extend([0xa1, 0x61, b'a', metadata_value, 0x00, 0x04]);
+            code
+        };
+        let (a, b) = (code(1, 1), code(2, 2));
+        assert!(bytecode_diff_score(&a, &b) > 0.5);
+        assert!(bytecode_diff_score_masked(&a, &b, &[]) > 0.5);
+        assert_eq!(bytecode_diff_score_masked(&a, &b, &[1..17, 17..33]), 0.0);
+        assert_eq!(bytecode_diff_score_masked(&a, &code(1, 2), &[]), 0.0);
+
+        // Trailing bytes that aren't metadata are kept.
+        assert_eq!(strip_metadata(&[0x60, 0x00, 0x00, 0x01]), &[0x60, 0x00, 0x00, 0x01]);
+        assert_eq!(strip_metadata(&[0x00]), &[0x00]);
+        assert_eq!(metadata_range(&a), Some(41..47));
+    }
+
+    #[test]
+    fn masked_bytecode_diffing_without_metadata() {
+        // Code without metadata whose trailing bytes look like a metadata of length 2.
+        let mut a = vec![0x60; 40];
+        a.extend([0xa1, 0x01, 0x00, 0x02]);
+        let mut b = a.clone();
+        *b.last_mut().unwrap() = 0x03;
+        assert_eq!(metadata_range(&a), Some(40..44));
+        assert_eq!(metadata_range(&b), None);
+
+        // Stripping the metadata of one side only would count it as different.
+        let score = bytecode_diff_score_masked(&a, &b, &[]);
+        assert_eq!(score, bytecode_diff_score(&a, &b));
+        assert!(score < bytecode_diff_score(strip_metadata(&a), &b));
+    }
+
     #[test]
     fn find_by_deployed_code_exact_with_empty_deployed() {
         let contracts = ContractsByArtifact::new(vec![]);
@@ -4,7 +4,8 @@
 
 use self::EnsResolver::EnsResolverInstance;
 use alloy_primitives::{address, Address, Keccak256, B256};
-use alloy_provider::{Network, Provider};
+use alloy_provider::{MulticallBuilder, MulticallError, Network, Provider};
+use alloy_rpc_types::BlockId;
 use alloy_sol_types::sol;
 use async_trait::async_trait;
 use std::{borrow::Cow, str::FromStr};
@@ -65,9 +66,19 @@ impl NameOrAddress {
     pub async fn resolve<N: Network, P: Provider<N>>(
         &self,
         provider: &P,
+    ) -> Result<Address, EnsError> {
+        self.resolve_at(provider, None).await
+    }
+
+    /// Resolves the name to an Ethereum Address at the given block, or at the latest block if
+    /// `None`.
+    pub async fn resolve_at<N: Network, P: Provider<N>>(
+        &self,
+        provider: &P,
+        block: Option<BlockId>,
     ) -> Result<Address, EnsError> {
         match self {
-            Self::Name(name) => provider.resolve_name(name).await,
+            Self::Name(name) => provider.resolve_name_at(name, block.unwrap_or_default()).await,
             Self::Address(addr) => Ok(*addr),
         }
     }
@@ -116,15 +127,32 @@ async fn get_resolver(
         &self,
         node: B256,
         error_name: &str,
+    ) -> Result<EnsResolverInstance<(), &P, N>, EnsError> {
+        self.get_resolver_at(node, error_name, BlockId::latest()).await
+    }
+
+    /// Returns the resolver for the specified node at the given block. The `&str` is only used
+    /// for error messages.
+    async fn get_resolver_at(
+        &self,
+        node: B256,
+        error_name: &str,
+        block: BlockId,
     ) -> Result<EnsResolverInstance<(), &P, N>, EnsError>;
 
     /// Performs a forward lookup of an ENS name to an address.
     async fn resolve_name(&self, name: &str) -> Result<Address, EnsError> {
+        self.resolve_name_at(name, BlockId::latest()).await
+    }
+
+    /// Performs a forward lookup of an ENS name to an address at the given block.
+    async fn resolve_name_at(&self, name: &str, block: BlockId) -> Result<Address, EnsError> {
         let node = namehash(name);
-        let resolver = self.get_resolver(node, name).await?;
+        let resolver = self.get_resolver_at(node, name, block).await?;
         let addr = resolver
             .addr(node)
             .call()
+            .block(block)
             .await
             .map_err(EnsError::Resolve)
             .inspect_err(|e| {
@@ -134,12 +162,32 @@ async fn resolve_name(&self, name: &str) -> Result<Address, EnsError> {
         Ok(addr)
     }
 
+    /// Performs a forward lookup of multiple ENS names to addresses at the given block.
+    ///
+    /// The resolvers of the names, and then their addresses, are each fetched in a single
+    /// `Multicall3` call. If batching fails, e.g. on a chain without `Multicall3`, the names are
+    /// resolved one after the other.
+    async fn resolve_names_at(
+        &self,
+        names: &[String],
+        block: BlockId,
+    ) -> Vec<Result<Address, EnsError>>;
+
     /// Performs a reverse lookup of an address to an ENS name.
     async fn lookup_address(&self, address: &Address) -> Result<String, EnsError> {
+        self.lookup_address_at(address, BlockId::latest()).await
+    }
+
+    /// Performs a reverse lookup of an address to an ENS name at the given block.
+    async fn lookup_address_at(
+        &self,
+        address: &Address,
+        block: BlockId,
+    ) -> Result<String, EnsError> {
         let name = reverse_address(address);
         let node = namehash(&name);
-        let resolver = self.get_resolver(node, &name).await?;
-        let name = resolver.name(node).call().await.map_err(EnsError::Lookup)?._0;
+        let resolver = self.get_resolver_at(node, &name, block).await?;
+        let name = resolver.name(node).call().block(block).await.map_err(EnsError::Lookup)?._0;
         Ok(name)
     }
 }
@@ -150,18 +198,84 @@ impl<N, P> ProviderEnsExt<N, P> for P
     P: Provider<N>,
     N: Network,
 {
-    async fn get_resolver(
+    async fn get_resolver_at(
         &self,
         node: B256,
         error_name: &str,
+        block: BlockId,
     ) -> Result<EnsResolverInstance<(), &P, N>, EnsError> {
         let registry = EnsRegistry::new(ENS_ADDRESS, self);
-        let address = registry.resolver(node).call().await.map_err(EnsError::Resolver)?._0;
+        let address =
+            registry.resolver(node).call().block(block).await.map_err(EnsError::Resolver)?._0;
         if address == Address::ZERO {
             return Err(EnsError::ResolverNotFound(error_name.to_string()));
         }
         Ok(EnsResolverInstance::new(address, self))
     }
+
+    async fn resolve_names_at(
+        &self,
+        names: &[String],
+        block: BlockId,
+    ) -> Vec<Result<Address, EnsError>> {
+        if names.is_empty() {
+            return Vec::new();
+        }
+        match batch_resolve_names(self, names, block).await {
+            Ok(addresses) => addresses,
+            Err(err) => {
+                trace!(%err, "batched ENS resolution failed, resolving names one by one");
+                let mut addresses = Vec::with_capacity(names.len());
+                for name in names {
+                    addresses.push(self.resolve_name_at(name, block).await);
+                }
+                addresses
+            }
+        }
+    }
+}
+
+/// Resolves the names with one `Multicall3` call for their resolvers and one for their addresses.
+async fn batch_resolve_names<N: Network, P: Provider<N>>(
+    provider: &P,
+    names: &[String],
+    block: BlockId,
+) -> Result<Vec<Result<Address, EnsError>>, MulticallError> {
+    let nodes = names.iter().map(|name| namehash(name)).collect::<Vec<_>>();
+    let registry = EnsRegistry::new(ENS_ADDRESS, provider);
+    let resolvers = MulticallBuilder::new_dynamic(provider)
+        .extend(nodes.iter().map(|node| registry.resolver(*node)))
+        .block(block)
+        .aggregate()
+        .await?
+        .into_iter()
+        .map(|resolver| resolver._0)
+        .collect::<Vec<_>>();
+
+    let found = nodes
+        .iter()
+        .zip(&resolvers)
+        .filter(|(_, resolver)| !resolver.is_zero())
+        .map(|(node, resolver)| (*node, EnsResolver::new(*resolver, provider)))
+        .collect::<Vec<_>>();
+    let lookups = found.iter().map(|(node, resolver)| resolver.addr(*node)).collect::<Vec<_>>();
+    let mut addresses = if lookups.is_empty() {
+        Vec::new()
+    } else {
+        MulticallBuilder::new_dynamic(provider).extend(lookups).block(block).aggregate().await?
+    }
+    .into_iter();
+
+    Ok(names
+        .iter()
+        .zip(resolvers)
+        .map(|(name, resolver)| {
+            if resolver.is_zero() {
+                return Err(EnsError::ResolverNotFound(name.clone()));
+            }
+            Ok(addresses.next().expect("missing address of resolved name")._0)
+        })
+        .collect())
 }
 
 /// Returns the ENS namehash as specified in [EIP-137](https://eips.ethereum.org/EIPS/eip-137)
@@ -251,4 +365,35 @@ fn test_invalid_address() {
             assert!(NameOrAddress::from_str(addr).is_err());
         }
     }
+
+    #[tokio::test]
+    async fn test_resolve_names_batched() {
+        use alloy_primitives::{Bytes, U256};
+        use alloy_provider::{ProviderBuilder, RootProvider};
+        use alloy_sol_types::SolValue;
+        use alloy_transport::mock::Asserter;
+
+        let aggregate = |addresses: &[Address]| {
+            let data = addresses.iter().map(|a| Bytes::from(a.abi_encode())).collect::<Vec<_>>();
+            Bytes::from((U256::from(1), data).abi_encode_params())
+        };
+        let [resolver, a, c] = [1, 2, 3].map(Address::with_last_byte);
+        let names = ["a.eth", "b.eth", "c.eth"].map(String::from);
+
+        let asserter = Asserter::new();
+        let provider: RootProvider = ProviderBuilder::default().on_mocked_client(asserter.clone());
+        asserter.push_success(&aggregate(&[resolver, Address::ZERO, resolver]));
+        asserter.push_success(&aggregate(&[a, c]));
+        let resolved = provider.resolve_names_at(&names, BlockId::number(1)).await;
+        assert_eq!(resolved[0].as_ref().unwrap(), &a);
+        assert!(matches!(&resolved[1], Err(EnsError::ResolverNotFound(name)) if name == "b.eth"));
+        assert_eq!(resolved[2].as_ref().unwrap(), &c);
+
+        // Falls back to resolving the names one by one if the multicall fails.
+        asserter.push_failure_msg("no multicall");
+        asserter.push_success(&Bytes::from(resolver.abi_encode()));
+        asserter.push_success(&Bytes::from(a.abi_encode()));
+        let resolved = provider.resolve_names_at(&names[..1], BlockId::number(1)).await;
+        assert_eq!(resolved[0].as_ref().unwrap(), &a);
+    }
 }
@@ -23,6 +23,12 @@ pub fn color_choice() -> ColorChoice {
     Shell::get().color_choice()
 }
 
+/// Returns whether `stdout` supports color, taking into account the color choice, whether it is a
+/// terminal, and the `NO_COLOR` and `CLICOLOR` environment variables.
+pub fn out_supports_color() -> bool {
+    Shell::get().out_supports_color()
+}
+
 /// Returns the currently set verbosity level.
 pub fn verbosity() -> Verbosity {
     Shell::get().verbosity()
@@ -1,9 +1,11 @@
 //! Provider-related instantiation and usage utilities.
 
+pub mod retry_layer;
 pub mod runtime_transport;
 
 use crate::{
-    provider::runtime_transport::RuntimeTransportBuilder, ALCHEMY_FREE_TIER_CUPS, REQUEST_TIMEOUT,
+    provider::{retry_layer::TransientRetryLayer, runtime_transport::RuntimeTransportBuilder},
+    ALCHEMY_FREE_TIER_CUPS, REQUEST_TIMEOUT,
 };
 use alloy_provider::{
     fillers::{ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller},
@@ -88,6 +90,8 @@ pub struct ProviderBuilder {
     url: Result<Url>,
     chain: NamedChain,
     max_retry: u32,
+    /// Retries of requests that timed out or failed to reach the node
+    transient_retries: u32,
     initial_backoff: u64,
     timeout: Duration,
     /// available CUPS
@@ -131,13 +135,15 @@ pub fn new(url_str: &str) -> Self {
             })
             .wrap_err_with(|| format!("invalid provider URL: {url_str:?}"));
 
-        // Use the final URL string to guess if it's a local URL.
-        let is_local = url.as_ref().is_ok_and(|url| guess_local_url(url.as_str()));
+        // Use the final URL string to guess if it's a local URL. IPC endpoints are always local.
+        let is_local =
+            url.as_ref().is_ok_and(|url| url.scheme() == "file" || guess_local_url(url.as_str()));
 
         Self {
             url,
             chain: NamedChain::Mainnet,
             max_retry: 8,
+            transient_retries: 0,
             initial_backoff: 800,
             timeout: REQUEST_TIMEOUT,
             // alchemy max cpus <https://docs.alchemy.com/reference/compute-units#what-are-cups-compute-units-per-second>
@@ -177,6 +183,23 @@ pub fn maybe_max_retry(mut self, max_retry: Option<u32>) -> Self {
         self
     }
 
+    /// How often to retry a request that timed out or failed to reach the node.
+    ///
+    /// When set, the request timeout is enforced on every attempt for all transports.
+    ///
+    /// Default is no retries.
+    pub fn transient_retries(mut self, transient_retries: u32) -> Self {
+        self.transient_retries = transient_retries;
+        self
+    }
+
+    /// How often to retry a request that timed out or failed to reach the node. If `None`,
+    /// defaults to the already-set value.
+    pub fn maybe_transient_retries(mut self, transient_retries: Option<u32>) -> Self {
+        self.transient_retries = transient_retries.unwrap_or(self.transient_retries);
+        self
+    }
+
     /// The starting backoff delay to use after the first failed request. If `None`, defaults to
     /// the already-set value.
     pub fn maybe_initial_backoff(mut self, initial_backoff: Option<u64>) -> Self {
@@ -248,6 +271,7 @@ pub fn build(self) -> Result<RetryProvider> {
             url,
             chain,
             max_retry,
+            transient_retries,
             initial_backoff,
             timeout,
             compute_units_per_second,
@@ -256,16 +280,25 @@ pub fn build(self) -> Result<RetryProvider> {
             is_local,
         } = self;
         let url = url?;
+        ensure_ipc_path_exists(&url)?;
 
         let retry_layer =
             RetryBackoffLayer::new(max_retry, initial_backoff, compute_units_per_second);
+        let transient_retry_layer = TransientRetryLayer::new(
+            transient_retries,
+            Duration::from_millis(initial_backoff),
+            timeout,
+        );
 
         let transport = RuntimeTransportBuilder::new(url)
             .with_timeout(timeout)
             .with_headers(headers)
             .with_jwt(jwt)
             .build();
-        let client = ClientBuilder::default().layer(retry_layer).transport(transport, is_local);
+        let client = ClientBuilder::default()
+            .layer(retry_layer)
+            .layer(transient_retry_layer)
+            .transport(transport, is_local);
 
         if !is_local {
             client.set_poll_interval(
@@ -291,6 +324,7 @@ pub fn build_with_wallet(self, wallet: EthereumWallet) -> Result<RetryProviderWi
             url,
             chain,
             max_retry,
+            transient_retries,
             initial_backoff,
             timeout,
             compute_units_per_second,
@@ -299,9 +333,15 @@ pub fn build_with_wallet(self, wallet: EthereumWallet) -> Result<RetryProviderWi
             is_local,
         } = self;
         let url = url?;
+        ensure_ipc_path_exists(&url)?;
 
         let retry_layer =
             RetryBackoffLayer::new(max_retry, initial_backoff, compute_units_per_second);
+        let transient_retry_layer = TransientRetryLayer::new(
+            transient_retries,
+            Duration::from_millis(initial_backoff),
+            timeout,
+        );
 
         let transport = RuntimeTransportBuilder::new(url)
             .with_timeout(timeout)
@@ -309,7 +349,10 @@ pub fn build_with_wallet(self, wallet: EthereumWallet) -> Result<RetryProviderWi
             .with_jwt(jwt)
             .build();
 
-        let client = ClientBuilder::default().layer(retry_layer).transport(transport, is_local);
+        let client = ClientBuilder::default()
+            .layer(retry_layer)
+            .layer(transient_retry_layer)
+            .transport(transport, is_local);
 
         if !is_local {
             client.set_poll_interval(
@@ -329,6 +372,28 @@ pub fn build_with_wallet(self, wallet: EthereumWallet) -> Result<RetryProviderWi
     }
 }
 
+/// Ensures that the IPC socket of a `file://` URL exists, so that connecting to a missing socket
+/// fails with a clear error instead of a generic connection failure.
+#[cfg(not(windows))]
+fn ensure_ipc_path_exists(url: &Url) -> Result<()> {
+    if url.scheme() != "file" {
+        return Ok(());
+    }
+    let path = url.to_file_path().map_err(|()| eyre::eyre!("invalid IPC path: {url}"))?;
+    if !path.exists() {
+        eyre::bail!(
+            "IPC socket not found at {}; is the node running with IPC enabled?",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn ensure_ipc_path_exists(_url: &Url) -> Result<()> {
+    Ok(())
+}
+
 #[cfg(not(windows))]
 fn resolve_path(path: &Path) -> Result<PathBuf, ()> {
     if path.is_absolute() {
@@ -360,4 +425,19 @@ fn can_auto_correct_missing_prefix() {
         let url = builder.url.unwrap();
         assert_eq!(url, Url::parse("http://localhost:8545").unwrap());
     }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn can_detect_ipc_path() {
+        let builder = ProviderBuilder::new("/tmp/geth.ipc");
+        assert_eq!(builder.url.as_ref().unwrap().scheme(), "file");
+        assert!(builder.is_local);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn errors_on_missing_ipc_socket() {
+        let err = ProviderBuilder::new("/does/not/exist/geth.ipc").build().unwrap_err();
+        assert!(err.to_string().contains("IPC socket not found"), "{err}");
+    }
 }
@@ -0,0 +1,190 @@
+//! A transport layer retrying requests that failed due to transient network errors.
+
+use alloy_json_rpc::{RequestPacket, ResponsePacket};
+use alloy_transport::{RpcError, TransportError, TransportErrorKind, TransportFut};
+use std::{
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower::{Layer, Service};
+
+/// A layer that enforces a timeout on every request attempt, and retries requests that timed out
+/// or failed to reach the node with an exponential backoff.
+///
+/// Errors returned by the node itself are never retried by this layer; rate limits are handled
+/// by [`RetryBackoffLayer`](alloy_transport::layers::RetryBackoffLayer).
+///
+/// With `max_retries` set to `0` requests are passed through untouched.
+#[derive(Clone, Debug)]
+pub struct TransientRetryLayer {
+    /// The maximum number of retries.
+    max_retries: u32,
+    /// The backoff before the first retry, doubled after every attempt.
+    initial_backoff: Duration,
+    /// The timeout of a single attempt.
+    timeout: Duration,
+}
+
+impl TransientRetryLayer {
+    /// Creates a new layer with the given number of retries, initial backoff and per-attempt
+    /// timeout.
+    pub fn new(max_retries: u32, initial_backoff: Duration, timeout: Duration) -> Self {
+        Self { max_retries, initial_backoff, timeout }
+    }
+}
+
+impl<S> Layer<S> for TransientRetryLayer {
+    type Service = TransientRetryService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TransientRetryService { inner, layer: self.clone() }
+    }
+}
+
+/// The service created by [`TransientRetryLayer`].
+#[derive(Clone, Debug)]
+pub struct TransientRetryService<S> {
+    inner: S,
+    layer: TransientRetryLayer,
+}
+
+impl<S> Service<RequestPacket> for TransientRetryService<S>
+where
+    S: Service<RequestPacket, Future = TransportFut<'static>, Error = TransportError>
+        + Send
+        + 'static
+        + Clone,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let inner = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, inner);
+        let TransientRetryLayer { max_retries, mut initial_backoff, timeout } = self.layer.clone();
+        Box::pin(async move {
+            if max_retries == 0 {
+                return inner.call(request).await;
+            }
+
+            let mut attempts = 1;
+            loop {
+                let err = match tokio::time::timeout(timeout, inner.call(request.clone())).await {
+                    Ok(Ok(res)) => return Ok(res),
+                    Ok(Err(err)) if is_transient(&err) => err,
+                    Ok(Err(err)) => return Err(err),
+                    Err(_) => TransportErrorKind::custom_str(&format!(
+                        "request timed out after {timeout:?}"
+                    )),
+                };
+
+                if attempts > max_retries {
+                    return Err(TransportErrorKind::custom_str(&format!(
+                        "request failed after {attempts} attempts: {err}"
+                    )));
+                }
+
+                trace!(%err, attempts, "retrying request");
+                tokio::time::sleep(initial_backoff).await;
+                initial_backoff = initial_backoff.saturating_mul(2);
+                attempts += 1;
+            }
+        })
+    }
+}
+
+/// Returns `true` if the request failed before getting a response from the node, e.g. due to a
+/// timeout or a connection error.
+fn is_transient(err: &TransportError) -> bool {
+    let RpcError::Transport(kind) = err else { return false };
+    // Rate limits are already retried by the rate limit retry layer.
+    if kind.is_retry_err() {
+        return false;
+    }
+    match kind {
+        TransportErrorKind::BackendGone => true,
+        TransportErrorKind::HttpError(err) => err.status >= 500,
+        TransportErrorKind::Custom(err) => err
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|err| err.is_timeout() || err.is_connect()),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_rpc::{Id, Request};
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    fn request() -> RequestPacket {
+        Request::new("eth_chainId", Id::Number(1), ()).serialize().unwrap().into()
+    }
+
+    /// Returns a service failing every request with the given error, along with its call count.
+    fn failing_service(
+        err: fn() -> TransportError,
+    ) -> (
+        impl Service<
+                RequestPacket,
+                Response = ResponsePacket,
+                Future = TransportFut<'static>,
+                Error = TransportError,
+            > + Clone,
+        Arc<AtomicU32>,
+    ) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let service = tower::service_fn(move |_: RequestPacket| -> TransportFut<'static> {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Err(err()) })
+        });
+        (service, calls)
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let (service, calls) = failing_service(TransportErrorKind::backend_gone);
+        let layer = TransientRetryLayer::new(2, Duration::ZERO, Duration::from_secs(1));
+        let err = layer.layer(service).call(request()).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            err.to_string(),
+            "request failed after 3 attempts: backend connection task has stopped"
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_node_errors() {
+        let (service, calls) = failing_service(|| TransportErrorKind::custom_str("bad request"));
+        let layer = TransientRetryLayer::new(2, Duration::ZERO, Duration::from_secs(1));
+        let err = layer.layer(service).call(request()).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(err.to_string(), "bad request");
+    }
+
+    #[tokio::test]
+    async fn retries_timed_out_requests() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let service = tower::service_fn(move |_: RequestPacket| -> TransportFut<'static> {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(std::future::pending())
+        });
+        let layer = TransientRetryLayer::new(1, Duration::ZERO, Duration::from_millis(10));
+        let err = layer.layer(service).call(request()).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            err.to_string(),
+            "request failed after 2 attempts: request timed out after 10ms"
+        );
+    }
+}
@@ -166,14 +166,8 @@ pub fn reqwest_client(&self) -> Result<reqwest::Client, RuntimeTransportError> {
 
         // Add any custom headers.
         for header in &self.headers {
-            let make_err = || RuntimeTransportError::BadHeader(header.to_string());
-
-            let (key, val) = header.split_once(':').ok_or_else(make_err)?;
-
-            headers.insert(
-                HeaderName::from_str(key.trim()).map_err(|_| make_err())?,
-                HeaderValue::from_str(val.trim()).map_err(|_| make_err())?,
-            );
+            let (key, val) = parse_header(header)?;
+            headers.insert(key, val);
         }
 
         if !headers.contains_key(reqwest::header::USER_AGENT) {
@@ -296,6 +290,18 @@ fn call(&mut self, req: RequestPacket) -> Self::Future {
     }
 }
 
+/// Parses an HTTP header of the form `Key: Value`.
+pub fn parse_header(header: &str) -> Result<(HeaderName, HeaderValue), RuntimeTransportError> {
+    let make_err = || RuntimeTransportError::BadHeader(header.to_string());
+
+    let (key, val) = header.split_once(':').ok_or_else(make_err)?;
+
+    Ok((
+        HeaderName::from_str(key.trim()).map_err(|_| make_err())?,
+        HeaderValue::from_str(val.trim()).map_err(|_| make_err())?,
+    ))
+}
+
 fn build_auth(jwt: String) -> eyre::Result<Authorization> {
     // Decode jwt from hex, then generate claims (iat with current timestamp)
     let secret = JwtSecret::from_hex(jwt)?;
@@ -364,4 +370,15 @@ async fn test_user_agent_header() {
 
         server_task.abort();
     }
+
+    #[test]
+    fn test_parse_header() {
+        let (key, val) = parse_header("X-Api-Key: secret:1").unwrap();
+        assert_eq!(key, HeaderName::from_static("x-api-key"));
+        assert_eq!(val, HeaderValue::from_static("secret:1"));
+
+        assert!(parse_header("X-Api-Key").is_err());
+        assert!(parse_header("X Api Key: secret").is_err());
+        assert!(parse_header(": secret").is_err());
+    }
 }
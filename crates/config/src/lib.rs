@@ -270,6 +270,8 @@ pub struct Config {
     pub eth_rpc_jwt: Option<String>,
     /// Timeout that should be used for any rpc calls
     pub eth_rpc_timeout: Option<u64>,
+    /// Number of times to retry rpc calls that timed out or failed to reach the node
+    pub eth_rpc_retries: Option<u32>,
     /// Headers that should be used for any rpc calls
     ///
     /// # Example
@@ -413,6 +415,12 @@ pub struct Config {
     /// Disables storage caching entirely. This overrides any settings made in
     /// `rpc_storage_caching`
     pub no_storage_caching: bool,
+    /// The directory to write the RPC storage cache to, instead of
+    /// [`Config::foundry_rpc_cache_dir`].
+    ///
+    /// The cache of each block is stored at `<rpc_cache_path>/<chain>/<block>/storage.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_cache_path: Option<PathBuf>,
     /// Disables rate limiting entirely. This overrides any settings made in
     /// `compute_units_per_second`
     pub no_rpc_rate_limit: bool,
@@ -794,6 +802,10 @@ fn p(root: &Path, rem: &Path) -> PathBuf {
             self.build_info_path = Some(p(&root, &build_info_path));
         }
 
+        if let Some(rpc_cache_path) = self.rpc_cache_path {
+            self.rpc_cache_path = Some(p(&root, &rpc_cache_path));
+        }
+
         self.libs = self.libs.into_iter().map(|lib| p(&root, &lib)).collect();
 
         self.remappings =
@@ -2342,6 +2354,7 @@ fn default() -> Self {
             eth_rpc_url: None,
             eth_rpc_jwt: None,
             eth_rpc_timeout: None,
+            eth_rpc_retries: None,
             eth_rpc_headers: None,
             etherscan_api_key: None,
             verbosity: 0,
@@ -2362,6 +2375,7 @@ fn default() -> Self {
             rpc_endpoints: Default::default(),
             etherscan: Default::default(),
             no_storage_caching: false,
+            rpc_cache_path: None,
             no_rpc_rate_limit: false,
             use_literal_content: false,
             bytecode_hash: BytecodeHash::Ipfs,
@@ -2583,6 +2597,29 @@ fn test_caching() {
         assert!(!config.enable_caching(url, NamedChain::Dev));
     }
 
+    #[test]
+    fn test_rpc_cache_path() {
+        figment::Jail::expect_with(|jail| {
+            let config = Config::load().unwrap();
+            assert_eq!(config.rpc_cache_path, None);
+            jail.create_file(
+                "foundry.toml",
+                r"
+                [profile.default]
+                rpc_cache_path = 'rpc-cache'
+            ",
+            )?;
+
+            let config = Config::load_with_root(jail.directory()).unwrap();
+            assert_eq!(config.rpc_cache_path, Some("rpc-cache".into()));
+
+            let config = config.canonic_at(jail.directory());
+            assert_eq!(config.rpc_cache_path, Some(canonic(jail.directory().join("rpc-cache"))));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_install_dir() {
         figment::Jail::expect_with(|jail| {
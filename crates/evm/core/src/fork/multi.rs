@@ -7,8 +7,7 @@
 use alloy_consensus::BlockHeader;
 use alloy_primitives::{map::HashMap, U256};
 use alloy_provider::network::BlockResponse;
-use foundry_common::provider::{ProviderBuilder, RetryProvider};
-use foundry_config::Config;
+use foundry_common::provider::RetryProvider;
 use foundry_fork_db::{cache::BlockchainDbMeta, BackendHandler, BlockchainDb, SharedBackend};
 use futures::{
     channel::mpsc::{channel, Receiver, Sender},
@@ -508,11 +507,11 @@ fn drop(&mut self) {
 /// This will establish a new `Provider` to the endpoint and return the Fork Backend.
 async fn create_fork(mut fork: CreateFork) -> eyre::Result<(ForkId, CreatedFork, Handler)> {
     let provider = Arc::new(
-        ProviderBuilder::new(fork.url.as_str())
+        fork.evm_opts
+            .fork_provider_builder(fork.url.as_str())
             .maybe_max_retry(fork.evm_opts.fork_retries)
             .maybe_initial_backoff(fork.evm_opts.fork_retry_backoff)
             .maybe_headers(fork.evm_opts.fork_headers.clone())
-            .compute_units_per_second(fork.evm_opts.get_compute_units_per_second())
             .build()?,
     );
 
@@ -527,7 +526,7 @@ async fn create_fork(mut fork: CreateFork) -> eyre::Result<(ForkId, CreatedFork,
 
     // Determine the cache path if caching is enabled.
     let cache_path = if fork.enable_caching {
-        Config::foundry_block_cache_dir(meta.cfg_env.chain_id, number)
+        fork.evm_opts.fork_cache_dir(meta.cfg_env.chain_id, number)
     } else {
         None
     };
@@ -7,7 +7,7 @@
 use foundry_config::{Chain, Config, GasLimit};
 use revm::primitives::{BlockEnv, CfgEnv, TxEnv};
 use serde::{Deserialize, Serialize};
-use std::fmt::Write;
+use std::{fmt::Write, path::PathBuf, time::Duration};
 use url::Url;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,7 +29,16 @@ pub struct EvmOpts {
     /// Initial retry backoff.
     pub fork_retry_backoff: Option<u64>,
 
+    /// The timeout of requests to `fork_url`, in seconds.
+    #[serde(rename = "eth_rpc_timeout")]
+    pub fork_timeout: Option<u64>,
+
+    /// The number of retries of requests to `fork_url` that timed out or failed to reach the node.
+    #[serde(rename = "eth_rpc_retries")]
+    pub fork_transient_retries: Option<u32>,
+
     /// Headers to use with `fork_url`
+    #[serde(rename = "eth_rpc_headers")]
     pub fork_headers: Option<Vec<String>>,
 
     /// The available compute units per second.
@@ -43,6 +52,10 @@ pub struct EvmOpts {
     /// Disables storage caching entirely.
     pub no_storage_caching: bool,
 
+    /// The directory the RPC storage cache is written to, instead of
+    /// `<Config::foundry_rpc_cache_dir()>`.
+    pub rpc_cache_path: Option<PathBuf>,
+
     /// The initial balance of each deployed test contract.
     pub initial_balance: U256,
 
@@ -83,10 +96,13 @@ fn default() -> Self {
             fork_block_number: None,
             fork_retries: None,
             fork_retry_backoff: None,
+            fork_timeout: None,
+            fork_transient_retries: None,
             fork_headers: None,
             compute_units_per_second: None,
             no_rpc_rate_limit: false,
             no_storage_caching: false,
+            rpc_cache_path: None,
             initial_balance: U256::default(),
             sender: Address::default(),
             ffi: false,
@@ -120,9 +136,7 @@ pub async fn fork_evm_env(
         &self,
         fork_url: &str,
     ) -> eyre::Result<(revm::primitives::Env, AnyRpcBlock)> {
-        let provider = ProviderBuilder::new(fork_url)
-            .compute_units_per_second(self.get_compute_units_per_second())
-            .build()?;
+        let provider = self.fork_provider_builder(fork_url).build()?;
         environment(
             &provider,
             self.memory_limit,
@@ -144,6 +158,18 @@ pub async fn fork_evm_env(
         })
     }
 
+    /// Returns a [`ProviderBuilder`] for the given fork URL, configured with the fork's compute
+    /// units per second, timeout and retries.
+    pub fn fork_provider_builder(&self, fork_url: &str) -> ProviderBuilder {
+        let mut builder = ProviderBuilder::new(fork_url)
+            .compute_units_per_second(self.get_compute_units_per_second())
+            .maybe_transient_retries(self.fork_transient_retries);
+        if let Some(timeout) = self.fork_timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        builder
+    }
+
     /// Returns the `revm::Env` configured with only local settings
     pub fn local_evm_env(&self) -> revm::primitives::Env {
         let mut cfg = CfgEnv::default();
@@ -186,14 +212,27 @@ pub fn local_evm_env(&self) -> revm::primitives::Env {
     ///   - storage is allowed (`no_storage_caching = false`)
     ///
     /// If all these criteria are met, then storage caching is enabled and storage info will be
-    /// written to `<Config::foundry_cache_dir()>/<str(chainid)>/<block>/storage.json`.
+    /// written to `<Config::foundry_cache_dir()>/<str(chainid)>/<block>/storage.json`, or to
+    /// `<rpc_cache_path>/<str(chainid)>/<block>/storage.json` if `rpc_cache_path` is configured.
     ///
     /// for `mainnet` and `--fork-block-number 14435000` on mac the corresponding storage cache will
     /// be at `~/.foundry/cache/mainnet/14435000/storage.json`.
     pub fn get_fork(&self, config: &Config, env: revm::primitives::Env) -> Option<CreateFork> {
         let url = self.fork_url.clone()?;
         let enable_caching = config.enable_caching(&url, env.cfg.chain_id);
-        Some(CreateFork { url, enable_caching, env, evm_opts: self.clone() })
+        let evm_opts = Self { rpc_cache_path: config.rpc_cache_path.clone(), ..self.clone() };
+        Some(CreateFork { url, enable_caching, env, evm_opts })
+    }
+
+    /// Returns the directory of the storage cache of the `block` on the `chain`.
+    ///
+    /// This is `<rpc_cache_path>/<chain>/<block>` if `rpc_cache_path` is set, and
+    /// [`Config::foundry_block_cache_dir`] otherwise.
+    pub fn fork_cache_dir(&self, chain_id: impl Into<Chain>, block: u64) -> Option<PathBuf> {
+        match &self.rpc_cache_path {
+            Some(dir) => Some(dir.join(chain_id.into().to_string()).join(block.to_string())),
+            None => Config::foundry_block_cache_dir(chain_id, block),
+        }
     }
 
     /// Returns the gas limit to use
@@ -231,8 +270,8 @@ pub fn get_compute_units_per_second(&self) -> u64 {
     pub async fn get_remote_chain_id(&self) -> Option<Chain> {
         if let Some(ref url) = self.fork_url {
             trace!(?url, "retrieving chain via eth_chainId");
-            let provider = ProviderBuilder::new(url.as_str())
-                .compute_units_per_second(self.get_compute_units_per_second())
+            let provider = self
+                .fork_provider_builder(url)
                 .build()
                 .ok()
                 .unwrap_or_else(|| panic!("Failed to establish provider to {url}"));
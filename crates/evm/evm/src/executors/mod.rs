@@ -7,7 +7,8 @@
 // the concrete `Executor` type.
 
 use crate::inspectors::{
-    cheatcodes::BroadcastableTransactions, Cheatcodes, InspectorData, InspectorStack,
+    cheatcodes::BroadcastableTransactions, AccessListInspector, Cheatcodes, InspectorData,
+    InspectorStack, OpcodePrinter,
 };
 use alloy_dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt};
 use alloy_json_abi::Function;
@@ -32,8 +33,8 @@
     db::{DatabaseCommit, DatabaseRef},
     interpreter::{return_ok, InstructionResult},
     primitives::{
-        AuthorizationList, BlockEnv, Bytecode, Env, EnvWithHandlerCfg, ExecutionResult, Output,
-        ResultAndState, SignedAuthorization, SpecId, TxEnv, TxKind,
+        AccessList, AuthorizationList, BlockEnv, Bytecode, Env, EnvWithHandlerCfg, ExecutionResult,
+        Output, ResultAndState, SignedAuthorization, SpecId, TxEnv, TxKind,
     },
 };
 use std::{
@@ -246,6 +247,38 @@ pub fn get_nonce(&self, address: Address) -> BackendResult<u64> {
         Ok(self.backend().basic_ref(address)?.map(|acc| acc.nonce).unwrap_or_default())
     }
 
+    /// Set the code of an account.
+    pub fn set_code(&mut self, address: Address, code: Bytecode) -> BackendResult<()> {
+        let mut account = self.backend().basic_ref(address)?.unwrap_or_default();
+        account.code_hash = code.hash_slow();
+        account.code = Some(code);
+        self.backend_mut().insert_account_info(address, account);
+        Ok(())
+    }
+
+    /// Replaces the whole storage of an account.
+    ///
+    /// When forking, all slots that are not set are assumed to be `0`.
+    pub fn set_storage(
+        &mut self,
+        address: Address,
+        storage: HashMap<U256, U256>,
+    ) -> BackendResult<()> {
+        self.backend_mut().replace_account_storage(address, storage)?;
+        Ok(())
+    }
+
+    /// Sets a single storage slot of an account.
+    pub fn set_storage_slot(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> BackendResult<()> {
+        self.backend_mut().insert_account_storage(address, slot, value)?;
+        Ok(())
+    }
+
     /// Returns `true` if the account has no code.
     pub fn is_empty_code(&self, address: Address) -> BackendResult<bool> {
         Ok(self.backend().basic_ref(address)?.map(|acc| acc.is_empty_code_hash()).unwrap_or(true))
@@ -268,6 +301,12 @@ pub fn set_trace_printer(&mut self, trace_printer: bool) -> &mut Self {
         self
     }
 
+    #[inline]
+    pub fn set_opcode_printer(&mut self, printer: Option<OpcodePrinter>) -> &mut Self {
+        self.inspector_mut().print_opcodes(printer);
+        self
+    }
+
     #[inline]
     pub fn create2_deployer(&self) -> Address {
         self.inspector().create2_deployer()
@@ -441,6 +480,37 @@ pub fn transact_raw(
         self.transact_with_env(env)
     }
 
+    /// Generates the EIP-2930 access list of a transaction, mirroring `eth_createAccessList`.
+    ///
+    /// The transaction is executed to collect the touched accounts and storage slots, and then
+    /// re-executed with the collected access list applied until the list no longer changes. The
+    /// sender, the recipient (or the created address) and precompiles are excluded.
+    ///
+    /// Returns the access list and the result of the last execution. The state after the call is
+    /// **not** persisted.
+    pub fn create_access_list(
+        &self,
+        from: Address,
+        to: TxKind,
+        data: Bytes,
+        value: U256,
+    ) -> eyre::Result<(AccessList, ExecutionResult)> {
+        let mut env = self.build_test_env(from, to, data, value);
+        loop {
+            let mut inspector = AccessListInspector::new(AccessList(env.tx.access_list.clone()));
+            let mut backend = CowBackend::new_borrowed(self.backend());
+            let ResultAndState { result, .. } =
+                backend.inspect(&mut env.clone(), &mut inspector)?;
+
+            let mut access_list = inspector.into_access_list();
+            access_list.0.sort_unstable_by_key(|item| item.address);
+            if access_list.0 == env.tx.access_list {
+                return Ok((access_list, result));
+            }
+            env.tx.access_list = access_list.0;
+        }
+    }
+
     /// Execute the transaction configured in `env.tx`.
     ///
     /// The state after the call is **not** persisted.
@@ -13,8 +13,14 @@
 mod logs;
 pub use logs::LogCollector;
 
+mod opcodes;
+pub use opcodes::OpcodePrinter;
+
 mod script;
 pub use script::ScriptExecutionInspector;
 
 mod stack;
 pub use stack::{InspectorData, InspectorStack, InspectorStackBuilder};
+
+mod trace_size;
+pub use trace_size::{estimate_trace_size, TraceSizeLimit};
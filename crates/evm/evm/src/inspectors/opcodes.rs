@@ -0,0 +1,147 @@
+use alloy_primitives::{hex, U256};
+use foundry_common::{sh_println, shell};
+use revm::{
+    interpreter::{opcode::OpCode, Interpreter},
+    Database, EvmContext, Inspector,
+};
+use serde::Serialize;
+
+/// An inspector that prints every executed opcode as soon as it is executed.
+///
+/// Unlike the recorded debug steps of the tracer, nothing is buffered apart from the state of the
+/// current step, so arbitrarily large executions can be printed.
+#[derive(Clone, Debug, Default)]
+pub struct OpcodePrinter {
+    /// Whether to print the stack before each opcode.
+    stack: bool,
+    /// Whether to print the memory words written by each opcode.
+    memory: bool,
+    /// The state before the step being executed.
+    step: Option<PendingStep>,
+}
+
+/// The state of the interpreter before executing an opcode.
+#[derive(Clone, Debug)]
+struct PendingStep {
+    depth: u64,
+    pc: usize,
+    op: u8,
+    gas_remaining: u64,
+    stack: Option<Vec<U256>>,
+    memory: Option<Vec<u8>>,
+}
+
+/// A printed opcode, in JSON mode.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpcodeStep<'a> {
+    depth: u64,
+    pc: usize,
+    op: &'a str,
+    gas: u64,
+    gas_cost: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stack: Option<&'a [U256]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory: Option<Vec<(usize, String)>>,
+}
+
+impl OpcodePrinter {
+    /// Creates a new printer, optionally printing the stack and the memory writes of each opcode.
+    pub fn new(stack: bool, memory: bool) -> Self {
+        Self { stack, memory, step: None }
+    }
+
+    fn print(&self, step: &PendingStep, gas_remaining: u64, memory: &[u8]) -> eyre::Result<()> {
+        let op = OpCode::name_by_op(step.op);
+        let gas_cost = step.gas_remaining.saturating_sub(gas_remaining);
+        let memory_writes = step.memory.as_deref().map(|old| memory_writes(old, memory));
+
+        if shell::is_json() {
+            let step = OpcodeStep {
+                depth: step.depth,
+                pc: step.pc,
+                op,
+                gas: step.gas_remaining,
+                gas_cost,
+                stack: step.stack.as_deref(),
+                memory: memory_writes.map(|writes| {
+                    writes
+                        .into_iter()
+                        .map(|(offset, word)| (offset, hex::encode_prefixed(word)))
+                        .collect()
+                }),
+            };
+            sh_println!("{}", serde_json::to_string(&step)?)
+        } else {
+            self.print_pretty(step, op, gas_cost, memory_writes)
+        }
+    }
+
+    fn print_pretty(
+        &self,
+        step: &PendingStep,
+        op: &str,
+        gas_cost: u64,
+        memory_writes: Option<Vec<(usize, &[u8])>>,
+    ) -> eyre::Result<()> {
+        let indent = "  ".repeat(step.depth.saturating_sub(1) as usize);
+        let (pc, gas) = (step.pc, step.gas_remaining);
+        sh_println!("{indent}[{pc}] {op} gas: {gas} cost: {gas_cost}")?;
+        if let Some(stack) = &step.stack {
+            let stack = stack.iter().rev().map(|item| format!("{item:#x}")).collect::<Vec<_>>();
+            sh_println!("{indent}    stack: [{}]", stack.join(", "))?;
+        }
+        for (offset, word) in memory_writes.into_iter().flatten() {
+            sh_println!("{indent}    memory[{offset:#x}]: {}", hex::encode_prefixed(word))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the 32-byte memory words that differ between the old and new memory, along with their
+/// offset.
+fn memory_writes<'a>(old: &[u8], new: &'a [u8]) -> Vec<(usize, &'a [u8])> {
+    new.chunks(32)
+        .enumerate()
+        .map(|(i, word)| (i * 32, word))
+        .filter(|&(offset, word)| old.get(offset..offset + word.len()) != Some(word))
+        .collect()
+}
+
+impl<DB: Database> Inspector<DB> for OpcodePrinter {
+    fn step(&mut self, interp: &mut Interpreter, ecx: &mut EvmContext<DB>) {
+        self.step = Some(PendingStep {
+            depth: ecx.journaled_state.depth(),
+            pc: interp.program_counter(),
+            op: interp.current_opcode(),
+            gas_remaining: interp.gas.remaining(),
+            stack: self.stack.then(|| interp.stack.data().clone()),
+            memory: self.memory.then(|| interp.shared_memory.context_memory().to_vec()),
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _ecx: &mut EvmContext<DB>) {
+        if let Some(step) = self.step.take() {
+            let memory = interp.shared_memory.context_memory();
+            let _ = self.print(&step, interp.gas.remaining(), memory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_memory_writes() {
+        let old = [0u8; 64];
+        let mut new = [0u8; 96];
+        new[33] = 1;
+        new[64] = 2;
+
+        let writes = memory_writes(&old, &new);
+        assert_eq!(writes, vec![(32, &new[32..64]), (64, &new[64..96])]);
+        assert!(memory_writes(&new, &new).is_empty());
+    }
+}
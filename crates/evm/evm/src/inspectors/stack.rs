@@ -1,8 +1,8 @@
 use super::{
-    Cheatcodes, CheatsConfig, ChiselState, CoverageCollector, Fuzzer, LogCollector,
-    ScriptExecutionInspector, TracingInspector,
+    Cheatcodes, CheatsConfig, ChiselState, CoverageCollector, Fuzzer, LogCollector, OpcodePrinter,
+    ScriptExecutionInspector, TraceSizeLimit, TracingInspector,
 };
-use alloy_primitives::{map::AddressHashMap, Address, Bytes, Log, TxKind, U256};
+use alloy_primitives::{map::AddressHashMap, Address, Bytes, Log, Selector, TxKind, U256};
 use foundry_cheatcodes::{CheatcodesExecutor, Wallets};
 use foundry_evm_core::{backend::DatabaseExt, InspectorExt};
 use foundry_evm_coverage::HitMaps;
@@ -14,8 +14,8 @@
         EOFCreateKind, Gas, InstructionResult, Interpreter, InterpreterResult,
     },
     primitives::{
-        Account, AccountStatus, BlockEnv, CreateScheme, Env, EnvWithHandlerCfg, ExecutionResult,
-        HashMap, Output, TransactTo,
+        Account, AccountStatus, BlockEnv, CreateScheme, EVMError, Env, EnvWithHandlerCfg,
+        ExecutionResult, HashMap, Output, TransactTo,
     },
     EvmContext, Inspector, JournaledState,
 };
@@ -290,7 +290,13 @@ pub struct InspectorStackInner {
     pub fuzzer: Option<Fuzzer>,
     pub log_collector: Option<LogCollector>,
     pub printer: Option<CustomPrintTracer>,
+    pub opcode_printer: Option<OpcodePrinter>,
     pub tracer: Option<TracingInspector>,
+    pub trace_size_limit: Option<TraceSizeLimit>,
+    /// The fixed outputs of the stubbed precompiles, by address.
+    pub precompile_overrides: AddressHashMap<Bytes>,
+    /// The fixed outputs of the stubbed calls, by called address and selector.
+    pub call_return_overrides: HashMap<(Address, Selector), Bytes>,
     pub script_execution_inspector: Option<ScriptExecutionInspector>,
     pub enable_isolation: bool,
     pub odyssey: bool,
@@ -344,7 +350,16 @@ macro_rules! push {
                     )*
                 };
             }
-            push!(cheatcodes, chisel_state, coverage, fuzzer, log_collector, printer, tracer);
+            push!(
+                cheatcodes,
+                chisel_state,
+                coverage,
+                fuzzer,
+                log_collector,
+                printer,
+                opcode_printer,
+                tracer
+            );
             if self.enable_isolation {
                 enabled.push("isolation");
             }
@@ -429,6 +444,37 @@ pub fn print(&mut self, yes: bool) {
         self.printer = yes.then(Default::default);
     }
 
+    /// Set the printer of executed opcodes.
+    #[inline]
+    pub fn print_opcodes(&mut self, printer: Option<OpcodePrinter>) {
+        self.opcode_printer = printer;
+    }
+
+    /// Set the maximum estimated size of the traces, in bytes, above which the execution is
+    /// aborted with an error.
+    #[inline]
+    pub fn set_max_trace_bytes(&mut self, max_bytes: Option<usize>) {
+        self.trace_size_limit = max_bytes.map(TraceSizeLimit::new);
+    }
+
+    /// Set the precompiles to stub, which return the given output instead of being executed.
+    ///
+    /// The calls to these addresses are still traced.
+    #[inline]
+    pub fn set_precompile_overrides(&mut self, overrides: AddressHashMap<Bytes>) {
+        self.precompile_overrides = overrides;
+    }
+
+    /// Set the calls to stub, which return the given output instead of executing the code of the
+    /// called account.
+    ///
+    /// Calls are matched on their target address and the selector of their input, and take
+    /// precedence over the stubbed precompiles. The stubbed calls are still traced, and use no gas.
+    #[inline]
+    pub fn set_call_return_overrides(&mut self, overrides: HashMap<(Address, Selector), Bytes>) {
+        self.call_return_overrides = overrides;
+    }
+
     /// Set whether to enable the tracer.
     #[inline]
     pub fn tracing(&mut self, mode: TraceMode) {
@@ -502,6 +548,24 @@ fn adjust_evm_data_for_inner_context(&mut self, ecx: &mut EvmContext<&mut dyn Da
         ecx.env.tx.caller = inner_context_data.original_origin;
     }
 
+    /// Records a trace event against the trace size limit, if any.
+    ///
+    /// Returns `true` if the traces exceed the limit, in which case an error is set on the EVM
+    /// context to abort the execution.
+    fn check_trace_size(&mut self, ecx: &mut EvmContext<&mut dyn DatabaseExt>) -> bool {
+        let InspectorStackInner { trace_size_limit: Some(limit), tracer: Some(tracer), .. } =
+            &mut *self.inner
+        else {
+            return false;
+        };
+        let Some(size) = limit.record(tracer) else { return false };
+        ecx.error = Err(EVMError::Custom(format!(
+            "the trace exceeded the maximum size of {} bytes (estimated {size} bytes)",
+            limit.max_bytes()
+        )));
+        true
+    }
+
     fn do_call_end(
         &mut self,
         ecx: &mut EvmContext<&mut dyn DatabaseExt>,
@@ -785,7 +849,8 @@ fn step(&mut self, interpreter: &mut Interpreter, ecx: &mut EvmContext<&mut dyn
                 &mut self.coverage,
                 &mut self.cheatcodes,
                 &mut self.script_execution_inspector,
-                &mut self.printer
+                &mut self.printer,
+                &mut self.opcode_printer
             ],
             |inspector| inspector.step(interpreter, ecx),
         );
@@ -797,9 +862,19 @@ fn step_end(
         ecx: &mut EvmContext<&mut dyn DatabaseExt>,
     ) {
         call_inspectors!(
-            [&mut self.tracer, &mut self.cheatcodes, &mut self.chisel_state, &mut self.printer],
+            [
+                &mut self.tracer,
+                &mut self.cheatcodes,
+                &mut self.chisel_state,
+                &mut self.printer,
+                &mut self.opcode_printer
+            ],
             |inspector| inspector.step_end(interpreter, ecx),
         );
+
+        if self.check_trace_size(ecx) {
+            interpreter.instruction_result = InstructionResult::FatalExternalError;
+        }
     }
 
     fn log(
@@ -862,6 +937,22 @@ fn call(
             }
         }
 
+        let call_override = call.input.get(..4).and_then(|selector| {
+            self.call_return_overrides.get(&(call.target_address, Selector::from_slice(selector)))
+        });
+        if let Some(output) =
+            call_override.or_else(|| self.precompile_overrides.get(&call.bytecode_address))
+        {
+            return Some(CallOutcome {
+                result: InterpreterResult {
+                    result: InstructionResult::Return,
+                    output: output.clone(),
+                    gas: Gas::new(call.gas_limit),
+                },
+                memory_offset: call.return_memory_offset.clone(),
+            });
+        }
+
         if self.enable_isolation && !self.in_inner_context && ecx.journaled_state.depth == 1 {
             match call.scheme {
                 // Isolate CALLs
@@ -921,6 +1012,7 @@ fn call_end(
         }
 
         let outcome = self.do_call_end(ecx, inputs, outcome);
+        self.check_trace_size(ecx);
 
         if ecx.journaled_state.depth == 0 {
             self.top_level_frame_end(ecx, outcome.result.result);
@@ -981,6 +1073,7 @@ fn create_end(
         }
 
         let outcome = self.do_create_end(ecx, call, outcome);
+        self.check_trace_size(ecx);
 
         if ecx.journaled_state.depth == 0 {
             self.top_level_frame_end(ecx, outcome.result.result);
@@ -0,0 +1,121 @@
+use foundry_evm_traces::{CallTraceArena, CallTraceNode, TracingInspector};
+use revm_inspectors::tracing::types::{CallLog, CallTraceStep};
+use std::mem::size_of;
+
+/// The minimum number of trace events between two checks of the trace size.
+const MIN_CHECK_INTERVAL: usize = 1024;
+
+/// Aborts the execution once the estimated size of the recorded traces exceeds a limit, so that
+/// pathological executions fail with an error instead of exhausting the memory.
+///
+/// Estimating the size walks the whole arena, so it is only done after a number of trace events
+/// that grows with the trace, and the limit can be exceeded by a fraction before it is detected.
+#[derive(Clone, Debug)]
+pub struct TraceSizeLimit {
+    /// The maximum estimated size of the traces, in bytes.
+    max_bytes: usize,
+    /// The number of trace events recorded so far.
+    events: usize,
+    /// The number of trace events at which to check the size next.
+    next_check: usize,
+}
+
+impl TraceSizeLimit {
+    /// Creates a new limit of `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes, events: 0, next_check: MIN_CHECK_INTERVAL }
+    }
+
+    /// Returns the maximum estimated size of the traces, in bytes.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Records a trace event, returning the estimated size of the traces of `tracer` if it was
+    /// checked and exceeds the limit.
+    pub fn record(&mut self, tracer: &TracingInspector) -> Option<usize> {
+        self.events += 1;
+        if self.events < self.next_check {
+            return None;
+        }
+        self.next_check = self.events + (self.events / 8).max(MIN_CHECK_INTERVAL);
+        let size = estimate_trace_size(tracer.traces());
+        (size > self.max_bytes).then_some(size)
+    }
+}
+
+/// Returns the approximate size in memory of the traces of `arena`, in bytes.
+pub fn estimate_trace_size(arena: &CallTraceArena) -> usize {
+    arena.nodes().iter().map(estimate_node_size).sum()
+}
+
+fn estimate_node_size(node: &CallTraceNode) -> usize {
+    let trace = &node.trace;
+    let logs = node.logs.iter().map(|log| size_of::<CallLog>() + log.raw_log.data.len());
+    let steps = trace.steps.iter().map(|step| {
+        let words =
+            step.stack.as_ref().map_or(0, Vec::len) + step.push_stack.as_ref().map_or(0, Vec::len);
+        size_of::<CallTraceStep>() +
+            words * 32 +
+            step.memory.as_ref().map_or(0, |memory| memory.len())
+    });
+    size_of::<CallTraceNode>() +
+        node.children.len() * size_of::<usize>() +
+        node.ordering.len() * 16 +
+        trace.data.len() +
+        trace.output.len() +
+        logs.sum::<usize>() +
+        steps.sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes, U256};
+    use foundry_evm_traces::TracingInspectorConfig;
+    use revm::interpreter::{opcode::OpCode, InstructionResult};
+
+    #[test]
+    fn estimates_trace_size() {
+        let mut arena = CallTraceArena::default();
+        let empty = estimate_trace_size(&arena);
+        assert_eq!(empty, size_of::<CallTraceNode>());
+
+        let node = &mut arena.nodes_mut()[0];
+        node.trace.data = Bytes::from(vec![0; 100]);
+        node.trace.steps.push(CallTraceStep {
+            depth: 0,
+            pc: 0,
+            code_section_idx: 0,
+            op: OpCode::ADD,
+            contract: Address::ZERO,
+            stack: Some(vec![U256::ZERO; 2]),
+            push_stack: None,
+            memory: None,
+            returndata: Default::default(),
+            gas_remaining: 0,
+            gas_refund_counter: 0,
+            gas_used: 0,
+            gas_cost: 0,
+            storage_change: None,
+            status: InstructionResult::Continue,
+            immediate_bytes: None,
+            decoded: None,
+        });
+        assert_eq!(estimate_trace_size(&arena), empty + 100 + size_of::<CallTraceStep>() + 64);
+    }
+
+    #[test]
+    fn checks_size_periodically() {
+        let tracer = TracingInspector::new(TracingInspectorConfig::none());
+        let mut limit = TraceSizeLimit::new(0);
+        for _ in 1..MIN_CHECK_INTERVAL {
+            assert_eq!(limit.record(&tracer), None);
+        }
+        assert_eq!(limit.record(&tracer), Some(size_of::<CallTraceNode>()));
+        assert_eq!(limit.record(&tracer), None);
+
+        let mut limit = TraceSizeLimit::new(usize::MAX);
+        assert!((0..10 * MIN_CHECK_INTERVAL).all(|_| limit.record(&tracer).is_none()));
+    }
+}
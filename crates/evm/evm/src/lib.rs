@@ -11,7 +11,9 @@
 pub mod executors;
 pub mod inspectors;
 
-pub use foundry_evm_core::{backend, constants, decode, fork, opts, utils, InspectorExt};
+pub use foundry_evm_core::{
+    backend, constants, decode, fork, opts, precompiles, utils, InspectorExt,
+};
 pub use foundry_evm_coverage as coverage;
 pub use foundry_evm_fuzz as fuzz;
 pub use foundry_evm_traces as traces;
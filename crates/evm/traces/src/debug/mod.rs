@@ -15,18 +15,31 @@
 pub struct DebugTraceIdentifier {
     /// Source map of contract sources
     contracts_sources: ContractSources,
+    /// Whether to only identify the internal functions defined outside of the called contract and
+    /// the contracts it inherits from.
+    boundaries_only: bool,
 }
 
 impl DebugTraceIdentifier {
     pub fn new(contracts_sources: ContractSources) -> Self {
-        Self { contracts_sources }
+        Self { contracts_sources, boundaries_only: false }
+    }
+
+    /// Only identifies the internal functions defined outside of the called contract, such as
+    /// library functions, skipping the ones of the contract itself and of the contracts it
+    /// inherits from.
+    pub fn with_boundaries_only(mut self, yes: bool) -> Self {
+        self.boundaries_only = yes;
+        self
     }
 
     /// Identifies internal function invocations in a given [CallTraceNode].
     ///
     /// Accepts the node itself and identified name of the contract which node corresponds to.
     pub fn identify_node_steps(&self, node: &mut CallTraceNode, contract_name: &str) {
-        DebugStepsWalker::new(node, &self.contracts_sources, contract_name).walk();
+        DebugStepsWalker::new(node, &self.contracts_sources, contract_name)
+            .boundaries_only(self.boundaries_only)
+            .walk();
     }
 }
 
@@ -62,6 +75,7 @@ struct DebugStepsWalker<'a> {
     stack: Vec<(String, usize)>,
     sources: &'a ContractSources,
     contract_name: &'a str,
+    boundaries_only: bool,
 }
 
 impl<'a> DebugStepsWalker<'a> {
@@ -70,7 +84,19 @@ pub fn new(
         sources: &'a ContractSources,
         contract_name: &'a str,
     ) -> Self {
-        Self { node, current_step: 0, stack: Vec::new(), sources, contract_name }
+        Self {
+            node,
+            current_step: 0,
+            stack: Vec::new(),
+            sources,
+            contract_name,
+            boundaries_only: false,
+        }
+    }
+
+    pub fn boundaries_only(mut self, yes: bool) -> Self {
+        self.boundaries_only = yes;
+        self
     }
 
     fn current_step(&self) -> &CallTraceStep {
@@ -140,6 +166,12 @@ fn jump_out(&mut self) {
         // are considered invalid.
         let (func_name, start_idx) = self.stack.split_off(i).swap_remove(0);
 
+        // Functions are still matched to keep the stack consistent, but the ones of the called
+        // contract itself are not decoded.
+        if self.boundaries_only && self.is_own_function(&func_name) {
+            return;
+        }
+
         // Try to decode function inputs and outputs from the stack and memory.
         let (inputs, outputs) = self
             .src_map(start_idx + 1)
@@ -164,6 +196,14 @@ fn jump_out(&mut self) {
         ));
     }
 
+    /// Returns whether `func_name`, in the format `Contract::function`, is defined in the called
+    /// contract or in one of the contracts it inherits from.
+    fn is_own_function(&self, func_name: &str) -> bool {
+        func_name
+            .split_once("::")
+            .is_some_and(|(contract, _)| self.sources.inherits_from(self.contract_name, contract))
+    }
+
     fn process(&mut self) {
         // We are only interested in JUMPs.
         if self.current_step().op != OpCode::JUMP && self.current_step().op != OpCode::JUMPDEST {
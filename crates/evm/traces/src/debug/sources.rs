@@ -28,11 +28,14 @@ pub struct SourceData {
     /// Maps contract name to (start, end) of the contract definition in the source code.
     /// This is useful for determining which contract contains given function definition.
     contract_definitions: Vec<(String, Range<usize>)>,
+    /// Maps contract name to the names of the contracts it directly inherits from.
+    contract_bases: HashMap<String, Vec<String>>,
 }
 
 impl SourceData {
     pub fn new(source: Arc<String>, language: MultiCompilerLanguage, path: PathBuf) -> Self {
         let mut contract_definitions = Vec::new();
+        let mut contract_bases = HashMap::new();
 
         match language {
             MultiCompilerLanguage::Vyper(_) => {
@@ -53,6 +56,11 @@ pub fn new(source: Arc<String>, language: MultiCompilerLanguage, path: PathBuf)
                         if let solar_parse::ast::ItemKind::Contract(contract) = &item.kind {
                             let range = item.span.lo().to_usize()..item.span.hi().to_usize();
                             contract_definitions.push((contract.name.to_string(), range));
+                            let bases = contract.bases.iter();
+                            contract_bases.insert(
+                                contract.name.to_string(),
+                                bases.map(|base| base.name.last().to_string()).collect(),
+                            );
                         }
                     }
                     Ok(())
@@ -60,7 +68,7 @@ pub fn new(source: Arc<String>, language: MultiCompilerLanguage, path: PathBuf)
             }
         }
 
-        Self { source, language, path, contract_definitions }
+        Self { source, language, path, contract_definitions, contract_bases }
     }
 
     /// Finds name of contract that contains given loc.
@@ -248,6 +256,34 @@ pub fn get_sources(
         })
     }
 
+    /// Returns whether the contract `name` is `base` or inherits from it, directly or not.
+    ///
+    /// The bases are looked up by name in the sources of the build of the contract.
+    pub fn inherits_from(&self, name: &str, base: &str) -> bool {
+        let sources = self
+            .artifacts_by_name
+            .get(name)
+            .and_then(|artifacts| artifacts.first())
+            .and_then(|artifact| self.sources_by_id.get(&artifact.build_id));
+        let mut seen = HashSet::new();
+        let mut queue = vec![name];
+        while let Some(name) = queue.pop() {
+            if name == base {
+                return true;
+            }
+            if !seen.insert(name) {
+                continue;
+            }
+            let bases = sources
+                .into_iter()
+                .flat_map(|sources| sources.values())
+                .filter_map(|source| source.contract_bases.get(name))
+                .flatten();
+            queue.extend(bases.map(String::as_str));
+        }
+        false
+    }
+
     /// Returns all (name, bytecode, source) sets.
     pub fn entries(&self) -> impl Iterator<Item = (&str, &ArtifactData, &SourceData)> {
         self.artifacts_by_name.iter().flat_map(|(name, artifacts)| {
@@ -305,3 +341,47 @@ pub fn find_source_mapping(
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers::artifacts::SolcLanguage;
+
+    #[test]
+    fn resolves_inherited_contracts() {
+        let source = r#"
+            abstract contract Context {}
+            abstract contract Ownable is Context {
+                function _checkOwner() internal view {}
+            }
+            contract MyToken is Ownable {}
+            library Math {}
+        "#;
+        let source = SourceData::new(
+            Arc::new(source.to_string()),
+            MultiCompilerLanguage::Solc(SolcLanguage::Solidity),
+            PathBuf::from("src/MyToken.sol"),
+        );
+        let artifact = ArtifactData {
+            source_map: None,
+            source_map_runtime: None,
+            pc_ic_map: None,
+            pc_ic_map_runtime: None,
+            build_id: "build".to_string(),
+            file_id: 0,
+        };
+        let sources = ContractSources {
+            sources_by_id: HashMap::from([(
+                "build".to_string(),
+                HashMap::from([(0, Arc::new(source))]),
+            )]),
+            artifacts_by_name: HashMap::from([("MyToken".to_string(), vec![artifact])]),
+        };
+
+        assert!(sources.inherits_from("MyToken", "MyToken"));
+        assert!(sources.inherits_from("MyToken", "Ownable"));
+        assert!(sources.inherits_from("MyToken", "Context"));
+        assert!(!sources.inherits_from("MyToken", "Math"));
+        assert!(!sources.inherits_from("Unknown", "Ownable"));
+    }
+}
@@ -13,6 +13,7 @@
     abi::get_indexed_event, fmt::format_token, get_contract_name, selectors::SelectorKind,
     ContractsByArtifact, SELECTOR_LEN,
 };
+use foundry_compilers::artifacts::StorageLayout;
 use foundry_evm_core::{
     abi::{console, Vm},
     constants::{
@@ -89,6 +90,16 @@ pub fn with_signature_identifier(mut self, identifier: SignaturesIdentifier) ->
         self
     }
 
+    /// Add the storage layouts of contracts, keyed by `"<artifact>:<contract>"`.
+    #[inline]
+    pub fn with_storage_layouts(
+        mut self,
+        layouts: impl IntoIterator<Item = (String, StorageLayout)>,
+    ) -> Self {
+        self.decoder.storage_layouts.extend(layouts);
+        self
+    }
+
     /// Sets the debug identifier for the decoder.
     #[inline]
     pub fn with_debug_identifier(mut self, identifier: DebugTraceIdentifier) -> Self {
@@ -140,6 +151,22 @@ pub struct CallTraceDecoder {
 
     /// Optional identifier of individual trace steps.
     pub debug_identifier: Option<DebugTraceIdentifier>,
+
+    /// Storage layouts of the contracts, used to label storage changes.
+    ///
+    /// The keys are in the same form as the values of [`contracts`](Self::contracts).
+    pub storage_layouts: HashMap<String, StorageLayout>,
+
+    /// Decoded constructor arguments of the created contracts, along with the names of the
+    /// constructor parameters.
+    ///
+    /// Only filled by [`identify_constructor_args`](Self::identify_constructor_args).
+    pub constructor_args: HashMap<Address, Vec<(String, DynSolValue)>>,
+
+    /// Minimal proxies mapped to their implementation.
+    ///
+    /// The proxies are labelled after their implementation once it is identified.
+    pub proxies: HashMap<Address, Address>,
 }
 
 impl CallTraceDecoder {
@@ -194,6 +221,12 @@ fn init() -> Self {
             verbosity: 0,
 
             debug_identifier: None,
+
+            storage_layouts: Default::default(),
+
+            constructor_args: Default::default(),
+
+            proxies: Default::default(),
         }
     }
 
@@ -208,6 +241,27 @@ pub fn clear_addresses(&mut self) {
 
         self.receive_contracts.clear();
         self.fallback_contracts.clear();
+        self.constructor_args.clear();
+    }
+
+    /// Decodes the constructor arguments of the contracts created in the specified call trace that
+    /// are identified with the given local artifacts.
+    ///
+    /// The arguments are rendered with [`label_constructor_args`](crate::label_constructor_args).
+    pub fn identify_constructor_args(
+        &mut self,
+        arena: &CallTraceArena,
+        identifier: &LocalTraceIdentifier<'_>,
+    ) {
+        for node in arena.nodes() {
+            let trace = &node.trace;
+            if !trace.kind.is_any_create() || !trace.success {
+                continue;
+            }
+            if let Some(args) = identifier.identify_constructor_args(&trace.output, &trace.data) {
+                self.constructor_args.insert(trace.address, args);
+            }
+        }
     }
 
     /// Identify unknown addresses in the specified call trace using the specified identifier.
@@ -260,14 +314,15 @@ pub fn push_error(&mut self, error: Error) {
     }
 
     fn collect_identified_addresses(&mut self, mut addrs: Vec<IdentifiedAddress<'_>>) {
-        addrs.sort_by_key(|identity| identity.address);
+        // Prefer identities of the contract over the ones only providing an ABI.
+        addrs.sort_by_key(|identity| (identity.address, identity.contract.is_none()));
         addrs.dedup_by_key(|identity| identity.address);
         if addrs.is_empty() {
             return;
         }
 
         trace!(target: "evm::traces", len=addrs.len(), "collecting address identities");
-        for IdentifiedAddress { address, label, contract, abi, artifact_id: _ } in addrs {
+        for IdentifiedAddress { address, label, contract, abi, implementation, .. } in addrs {
             let _span = trace_span!(target: "evm::traces", "identity", ?contract, ?label).entered();
 
             if let Some(contract) = contract {
@@ -281,6 +336,27 @@ fn collect_identified_addresses(&mut self, mut addrs: Vec<IdentifiedAddress<'_>>
             if let Some(abi) = abi {
                 self.collect_abi(&abi, Some(address));
             }
+
+            if let Some(implementation) = implementation {
+                self.proxies.insert(address, implementation);
+            }
+        }
+
+        self.label_proxies();
+    }
+
+    /// Labels the unlabelled minimal proxies whose implementation is identified.
+    fn label_proxies(&mut self) {
+        for (&proxy, implementation) in &self.proxies {
+            if self.labels.contains_key(&proxy) {
+                continue;
+            }
+            let Some(label) = self.labels.get(implementation) else { continue };
+            let label = format!("{label} (proxy)");
+            if let Some(contract) = self.contracts.get(implementation).cloned() {
+                self.contracts.entry(proxy).or_insert(contract);
+            }
+            self.labels.insert(proxy, label);
         }
     }
 
@@ -697,7 +773,7 @@ pub async fn prefetch_signatures(&self, nodes: &[CallTraceNode]) {
     }
 
     /// Pretty-prints a value.
-    fn format_value(&self, value: &DynSolValue) -> String {
+    pub(crate) fn format_value(&self, value: &DynSolValue) -> String {
         if let DynSolValue::Address(addr) = value {
             if let Some(label) = self.labels.get(addr) {
                 return format!("{label}: [{addr}]");
@@ -1112,4 +1188,26 @@ fn test_should_redact() {
             assert_eq!(result, expected, "Output case failed for: {function_signature}");
         }
     }
+
+    #[test]
+    fn labels_proxies_after_their_implementation() {
+        let mut decoder = CallTraceDecoder::new().clone();
+        let [proxy, implementation] = [1, 2].map(Address::with_last_byte);
+        let identity = |address, label: Option<&str>, implementation| IdentifiedAddress {
+            address,
+            label: label.map(str::to_string),
+            contract: label.map(|label| format!("src/{label}.sol:{label}")),
+            abi: None,
+            artifact_id: None,
+            confidence: None,
+            implementation,
+        };
+
+        decoder.collect_identified_addresses(vec![identity(proxy, None, Some(implementation))]);
+        assert!(!decoder.labels.contains_key(&proxy));
+
+        decoder.collect_identified_addresses(vec![identity(implementation, Some("Vault"), None)]);
+        assert_eq!(decoder.labels[&proxy], "Vault (proxy)");
+        assert_eq!(decoder.contracts[&proxy], "src/Vault.sol:Vault");
+    }
 }
@@ -0,0 +1,123 @@
+use super::{IdentifiedAddress, TraceIdentifier};
+use alloy_primitives::map::AddressHashSet;
+use revm_inspectors::tracing::types::CallTraceNode;
+
+/// A trace identifier that tries multiple identifiers in order.
+///
+/// Each address is resolved by the first identifier that returns a non-empty identity for it;
+/// later identifiers are only queried for the addresses that are still unresolved.
+#[derive(Default)]
+pub struct ChainedTraceIdentifier<'a> {
+    /// The identifiers, in order of priority.
+    pub identifiers: Vec<Box<dyn TraceIdentifier + 'a>>,
+}
+
+impl<'a> ChainedTraceIdentifier<'a> {
+    /// Creates a new instance from the given identifiers, in order of priority.
+    pub fn new(identifiers: Vec<Box<dyn TraceIdentifier + 'a>>) -> Self {
+        Self { identifiers }
+    }
+
+    /// Appends an identifier, with a lower priority than the existing ones.
+    pub fn with(mut self, identifier: impl TraceIdentifier + 'a) -> Self {
+        self.identifiers.push(Box::new(identifier));
+        self
+    }
+}
+
+impl TraceIdentifier for ChainedTraceIdentifier<'_> {
+    fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddress<'_>> {
+        let mut identities = Vec::with_capacity(nodes.len());
+        let mut resolved = AddressHashSet::default();
+        let mut remaining = nodes.to_vec();
+        for identifier in &mut self.identifiers {
+            if remaining.is_empty() {
+                break;
+            }
+            for identity in identifier.identify_addresses(&remaining) {
+                let is_empty = identity.contract.is_none() &&
+                    identity.label.is_none() &&
+                    identity.abi.is_none();
+                if is_empty {
+                    // The later identifiers may still identify the proxy itself.
+                    if identity.implementation.is_some() {
+                        identities.push(identity);
+                    }
+                } else if resolved.insert(identity.address) {
+                    identities.push(identity);
+                }
+            }
+            remaining.retain(|node| !resolved.contains(&node.trace.address));
+        }
+        identities
+    }
+
+    fn is_local(&self) -> bool {
+        self.identifiers.iter().all(|identifier| identifier.is_local())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identifier::LocalTraceIdentifier;
+    use alloy_primitives::{map::AddressHashMap, Address};
+    use foundry_common::ContractsByArtifact;
+
+    /// Labels the addresses it knows about.
+    struct LabelIdentifier(AddressHashMap<&'static str>);
+
+    impl TraceIdentifier for LabelIdentifier {
+        fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddress<'_>> {
+            nodes
+                .iter()
+                .map(|node| IdentifiedAddress {
+                    address: node.trace.address,
+                    label: self.0.get(&node.trace.address).map(|label| label.to_string()),
+                    contract: None,
+                    abi: None,
+                    artifact_id: None,
+                    confidence: None,
+                    implementation: None,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn merges_identities_in_order() {
+        let nodes = (1..=3)
+            .map(|i| {
+                let mut node = CallTraceNode::default();
+                node.trace.address = Address::with_last_byte(i);
+                node
+            })
+            .collect::<Vec<_>>();
+        let nodes = nodes.iter().collect::<Vec<_>>();
+        let [a, b] = [1, 2].map(Address::with_last_byte);
+
+        let first = LabelIdentifier([(a, "first")].into_iter().collect());
+        let second = LabelIdentifier([(a, "second"), (b, "second")].into_iter().collect());
+        let mut identifier = ChainedTraceIdentifier::default().with(first).with(second);
+
+        let identities = identifier.identify_addresses(&nodes);
+        let labels = identities
+            .iter()
+            .map(|identity| (identity.address, identity.label.as_deref().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, [(a, "first"), (b, "second")]);
+    }
+
+    #[test]
+    fn is_local_if_all_identifiers_are() {
+        let contracts = ContractsByArtifact::default();
+        let local = || LocalTraceIdentifier::new(&contracts);
+        assert!(local().is_local());
+        assert!(!LabelIdentifier(Default::default()).is_local());
+
+        let identifier = ChainedTraceIdentifier::default().with(local()).with(local());
+        assert!(identifier.is_local());
+        let identifier = identifier.with(LabelIdentifier(Default::default()));
+        assert!(!identifier.is_local());
+    }
+}
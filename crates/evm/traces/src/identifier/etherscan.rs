@@ -15,7 +15,7 @@
 use revm_inspectors::tracing::types::CallTraceNode;
 use std::{
     borrow::Cow,
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -34,6 +34,8 @@ pub struct EtherscanIdentifier {
     /// prevent any further attempts
     invalid_api_key: Arc<AtomicBool>,
     pub contracts: BTreeMap<Address, Metadata>,
+    /// Addresses that Etherscan reported as not verified, so that they are not fetched again.
+    unverified: HashSet<Address>,
     pub sources: BTreeMap<u32, String>,
 }
 
@@ -52,6 +54,7 @@ pub fn new(config: &Config, chain: Option<Chain>) -> eyre::Result<Option<Self>>
             client: Arc::new(config.into_client()?),
             invalid_api_key: Arc::new(AtomicBool::new(false)),
             contracts: BTreeMap::new(),
+            unverified: HashSet::new(),
             sources: BTreeMap::new(),
         }))
     }
@@ -107,6 +110,8 @@ fn identify_from_metadata(
             contract: Some(label),
             abi,
             artifact_id: None,
+            confidence: None,
+            implementation: None,
         }
     }
 }
@@ -127,17 +132,18 @@ fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddr
             Arc::clone(&self.invalid_api_key),
         );
 
+        let mut queued = HashSet::new();
         for &node in nodes {
             let address = node.trace.address;
             if let Some(metadata) = self.contracts.get(&address) {
                 identities.push(self.identify_from_metadata(address, metadata));
-            } else {
+            } else if !self.unverified.contains(&address) && queued.insert(address) {
                 fetcher.push(address);
             }
         }
 
         let fetched_identities = foundry_common::block_on(
-            fetcher
+            (&mut fetcher)
                 .map(|(address, metadata)| {
                     let addr = self.identify_from_metadata(address, &metadata);
                     self.contracts.insert(address, metadata);
@@ -146,6 +152,9 @@ fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddr
                 .collect::<Vec<IdentifiedAddress<'_>>>(),
         );
 
+        // Addresses whose lookup failed, e.g. because of a timeout, are fetched again next time.
+        self.unverified.extend(fetcher.unverified);
+
         identities.extend(fetched_identities);
         identities
     }
@@ -172,6 +181,8 @@ struct EtherscanFetcher {
     in_progress: FuturesUnordered<EtherscanFuture>,
     /// tracks whether the API key provides was marked as invalid
     invalid_api_key: Arc<AtomicBool>,
+    /// The addresses that Etherscan reported as not verified
+    unverified: Vec<Address>,
 }
 
 impl EtherscanFetcher {
@@ -189,6 +200,7 @@ fn new(
             queue: Vec::new(),
             in_progress: FuturesUnordered::new(),
             invalid_api_key,
+            unverified: Vec::new(),
         }
     }
 
@@ -236,6 +248,10 @@ fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::It
                             if let Some(item) = metadata.items.pop() {
                                 return Poll::Ready(Some((addr, item)))
                             }
+                            pin.unverified.push(addr);
+                        }
+                        Err(EtherscanError::ContractCodeNotVerified(_)) => {
+                            pin.unverified.push(addr);
                         }
                         Err(EtherscanError::RateLimitExceeded) => {
                             warn!(target: "traces::etherscan", "rate limit exceeded on attempt");
@@ -1,10 +1,56 @@
 use super::{IdentifiedAddress, TraceIdentifier};
-use alloy_dyn_abi::JsonAbiExt;
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
 use alloy_json_abi::JsonAbi;
-use foundry_common::contracts::{bytecode_diff_score, ContractsByArtifact};
+use alloy_primitives::{hex, keccak256, map::HashMap, Address, Selector, B256};
+use foundry_common::contracts::{bytecode_diff_score_masked, ContractData, ContractsByArtifact};
 use foundry_compilers::ArtifactId;
+use rayon::prelude::*;
 use revm_inspectors::tracing::types::CallTraceNode;
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+/// The default maximum bytecode diff score for an artifact to be considered a match.
+pub const DEFAULT_DIFF_SCORE_THRESHOLD: f64 = 0.85;
+
+/// The default bounds of the runtime code length of the candidate artifacts, relative to the
+/// length of the code to identify.
+pub const DEFAULT_LENGTH_WINDOW: (f64, f64) = (0.9, 1.1);
+
+/// The minimum padding of the runtime code length window, in bytes, so that it does not collapse
+/// to the exact length for small contracts.
+pub const MIN_LENGTH_PADDING: usize = 32;
+
+/// The code surrounding the implementation address of the known minimal proxy variants, along with
+/// the position of their `JUMPDEST` when the address is pushed with `PUSH20`.
+const MINIMAL_PROXIES: &[(&[u8], &[u8], u8)] = &[
+    // EIP-1167.
+    (&hex!("363d3d373d3d3d363d"), &hex!("5af43d82803e903d9160"), 0x2b),
+    // 0age's more minimal proxy.
+    (&hex!("3d3d3d3d363d3d37363d"), &hex!("5af43d3d93803e60"), 0x2a),
+    // EIP-7511, using `PUSH0`.
+    (&hex!("365f5f375f5f365f"), &hex!("5af43d5f5f3e5f3d9160"), 0x2a),
+];
+
+/// The code shared by the end of all the minimal proxy variants, after the `JUMPDEST` position.
+const MINIMAL_PROXY_END: [u8; 4] = hex!("57fd5bf3");
+
+/// Returns the implementation address of a minimal proxy (EIP-1167), given its runtime code.
+///
+/// The vanity variants pushing an address with leading zero bytes with a shorter `PUSH` are
+/// supported, as well as the minimal proxies of 0age and EIP-7511.
+pub fn minimal_proxy_implementation(code: &[u8]) -> Option<Address> {
+    MINIMAL_PROXIES.iter().find_map(|&(prefix, suffix, jumpdest)| {
+        let (&push, code) = code.strip_prefix(prefix)?.split_first()?;
+        // `PUSH1` to `PUSH20`.
+        let len = push.checked_sub(0x5f).filter(|len| (1..=20).contains(len))?;
+        let (address, code) = code.split_at_checked(len as usize)?;
+        let (&dest, end) = code.strip_prefix(suffix)?.split_first()?;
+        (dest == jumpdest - (20 - len) && end == MINIMAL_PROXY_END)
+            .then(|| Address::left_padding_from(address))
+    })
+}
 
 /// A trace identifier that tries to identify addresses using local contracts.
 pub struct LocalTraceIdentifier<'a> {
@@ -12,6 +58,18 @@ pub struct LocalTraceIdentifier<'a> {
     known_contracts: &'a ContractsByArtifact,
     /// Vector of pairs of artifact ID and the runtime code length of the given artifact.
     ordered_ids: Vec<(&'a ArtifactId, usize)>,
+    /// Minimum diff score of the best creation code match for the deployed code to be compared
+    /// too.
+    fallback_threshold: f64,
+    /// Maximum bytecode diff score for an artifact to be considered a match.
+    accept_threshold: f64,
+    /// Bounds of the runtime code length of the candidate artifacts, relative to the length of the
+    /// code to identify.
+    length_window: (f64, f64),
+    /// Function selector -> first artifact whose ABI contains it, built on first use.
+    selectors: OnceLock<HashMap<Selector, &'a ArtifactId>>,
+    /// Hashes of the runtime and creation code -> best matching artifact and its diff score.
+    cache: Mutex<HashMap<(B256, B256), Option<(&'a ArtifactId, f64)>>>,
 }
 
 impl<'a> LocalTraceIdentifier<'a> {
@@ -24,7 +82,67 @@ pub fn new(known_contracts: &'a ContractsByArtifact) -> Self {
             .map(|(id, bytecode)| (id, bytecode.len()))
             .collect::<Vec<_>>();
         ordered_ids.sort_by_key(|(_, len)| *len);
-        Self { known_contracts, ordered_ids }
+        Self::from_parts(known_contracts, ordered_ids)
+    }
+
+    /// Creates a new local trace identifier from the artifact IDs of the known contracts sorted by
+    /// runtime code length, as returned by [`ordered_ids`](Self::ordered_ids).
+    ///
+    /// This avoids sorting the known contracts again when creating multiple identifiers for the
+    /// same contracts.
+    #[inline]
+    pub fn from_parts(
+        known_contracts: &'a ContractsByArtifact,
+        ordered_ids: Vec<(&'a ArtifactId, usize)>,
+    ) -> Self {
+        debug_assert!(ordered_ids.is_sorted_by_key(|(_, len)| *len));
+        Self {
+            known_contracts,
+            ordered_ids,
+            fallback_threshold: DEFAULT_DIFF_SCORE_THRESHOLD,
+            accept_threshold: DEFAULT_DIFF_SCORE_THRESHOLD,
+            length_window: DEFAULT_LENGTH_WINDOW,
+            selectors: OnceLock::new(),
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Sets both the [fallback](Self::with_fallback_threshold) and the
+    /// [acceptance](Self::with_accept_threshold) thresholds.
+    #[inline]
+    pub fn with_threshold(self, threshold: f64) -> Self {
+        self.with_fallback_threshold(threshold).with_accept_threshold(threshold)
+    }
+
+    /// Sets the diff score of the best creation code match from which the deployed code is
+    /// compared too, as a fallback.
+    ///
+    /// Defaults to [`DEFAULT_DIFF_SCORE_THRESHOLD`]. A lower threshold runs the fallback more
+    /// eagerly.
+    #[inline]
+    pub fn with_fallback_threshold(mut self, threshold: f64) -> Self {
+        self.fallback_threshold = threshold;
+        self
+    }
+
+    /// Sets the maximum bytecode diff score for an artifact to be considered a match.
+    ///
+    /// Defaults to [`DEFAULT_DIFF_SCORE_THRESHOLD`].
+    #[inline]
+    pub fn with_accept_threshold(mut self, threshold: f64) -> Self {
+        self.accept_threshold = threshold;
+        self
+    }
+
+    /// Sets the bounds of the runtime code length of the artifacts whose creation code is compared,
+    /// relative to the length of the code to identify.
+    ///
+    /// Defaults to [`DEFAULT_LENGTH_WINDOW`]. The window is always padded by at least
+    /// [`MIN_LENGTH_PADDING`] bytes on both sides.
+    #[inline]
+    pub fn with_length_window(mut self, lo: f64, hi: f64) -> Self {
+        self.length_window = (lo, hi);
+        self
     }
 
     /// Returns the known contracts.
@@ -33,98 +151,257 @@ pub fn contracts(&self) -> &'a ContractsByArtifact {
         self.known_contracts
     }
 
+    /// Returns the artifact IDs of the known contracts along with their runtime code length,
+    /// sorted by length.
+    #[inline]
+    pub fn ordered_ids(&self) -> &[(&'a ArtifactId, usize)] {
+        &self.ordered_ids
+    }
+
     /// Identifies the artifact based on score computed for both creation and deployed bytecodes.
+    ///
+    /// Either code can be empty if it is not available, in which case only the other one is
+    /// compared.
     pub fn identify_code(
         &self,
         runtime_code: &[u8],
         creation_code: &[u8],
     ) -> Option<(&'a ArtifactId, &'a JsonAbi)> {
-        let len = runtime_code.len();
-
-        let mut min_score = f64::MAX;
-        let mut min_score_id = None;
-
-        let mut check = |id, is_creation, min_score: &mut f64| {
-            let contract = self.known_contracts.get(id)?;
-            // Select bytecodes to compare based on `is_creation` flag.
-            let (contract_bytecode, current_bytecode) = if is_creation {
-                (contract.bytecode(), creation_code)
-            } else {
-                (contract.deployed_bytecode(), runtime_code)
-            };
-
-            if let Some(bytecode) = contract_bytecode {
-                let mut current_bytecode = current_bytecode;
-                if is_creation && current_bytecode.len() > bytecode.len() {
-                    // Try to decode ctor args with contract abi.
-                    if let Some(constructor) = contract.abi.constructor() {
-                        let constructor_args = &current_bytecode[bytecode.len()..];
-                        if constructor.abi_decode_input(constructor_args, false).is_ok() {
-                            // If we can decode args with current abi then remove args from
-                            // code to compare.
-                            current_bytecode = &current_bytecode[..bytecode.len()]
-                        }
-                    }
-                }
+        let (id, abi, _) = self.identify_code_with_score(runtime_code, creation_code)?;
+        Some((id, abi))
+    }
 
-                let score = bytecode_diff_score(bytecode, current_bytecode);
-                if score == 0.0 {
-                    trace!(target: "evm::traces::local", "found exact match");
-                    return Some((id, &contract.abi));
-                }
-                if score < *min_score {
-                    *min_score = score;
-                    min_score_id = Some((id, &contract.abi));
-                }
+    /// Same as [`identify_code`](Self::identify_code), but also returns the diff score of the
+    /// identified artifact.
+    ///
+    /// Results are cached by the hashes of both codes, so that identifying the same contract again
+    /// is cheap.
+    pub fn identify_code_with_score(
+        &self,
+        runtime_code: &[u8],
+        creation_code: &[u8],
+    ) -> Option<(&'a ArtifactId, &'a JsonAbi, f64)> {
+        let key = (keccak256(runtime_code), keccak256(creation_code));
+        let cached = self.cache.lock().unwrap_or_else(PoisonError::into_inner).get(&key).copied();
+        let best = match cached {
+            Some(best) => best,
+            None => {
+                let best = self.score_code(runtime_code, creation_code);
+                self.cache.lock().unwrap_or_else(PoisonError::into_inner).insert(key, best);
+                best
             }
-            None
         };
+        best.map(|(id, score)| (id, &self.known_contracts[id].abi, score))
+    }
 
-        // Check `[len * 0.9, ..., len * 1.1]`.
-        let max_len = (len * 11) / 10;
-
-        // Start at artifacts with the same code length: `len..len*1.1`.
-        let same_length_idx = self.find_index(len);
-        for idx in same_length_idx..self.ordered_ids.len() {
-            let (id, len) = self.ordered_ids[idx];
-            if len > max_len {
-                break;
-            }
-            if let found @ Some(_) = check(id, true, &mut min_score) {
-                return found;
-            }
+    /// Returns the artifact matching the given code with the lowest diff score, if it is below the
+    /// acceptance threshold.
+    fn score_code(
+        &self,
+        runtime_code: &[u8],
+        creation_code: &[u8],
+    ) -> Option<(&'a ArtifactId, f64)> {
+        // No deployable artifacts, e.g. when the project wasn't compiled yet.
+        if self.ordered_ids.is_empty() {
+            return None;
         }
 
-        // Iterate over the remaining artifacts with less code length: `len*0.9..len`.
-        let min_len = (len * 9) / 10;
-        let idx = self.find_index(min_len);
-        for i in idx..same_length_idx {
-            let (id, _) = self.ordered_ids[i];
-            if let found @ Some(_) = check(id, true, &mut min_score) {
-                return found;
-            }
-        }
+        let mut best = if creation_code.is_empty() {
+            None
+        } else {
+            let candidates = self.candidates(runtime_code.len());
+            self.best_match(candidates, runtime_code, creation_code, true)
+        };
+        let mut min_score = best.map_or(f64::MAX, |(_, score)| score);
 
         // Fallback to comparing deployed code if min score greater than threshold.
-        if min_score >= 0.85 {
-            for (artifact, _) in &self.ordered_ids {
-                if let found @ Some(_) = check(artifact, false, &mut min_score) {
-                    return found;
+        if min_score > 0.0 && min_score >= self.fallback_threshold && !runtime_code.is_empty() {
+            if let Some((id, score)) =
+                self.best_match(&self.ordered_ids, runtime_code, creation_code, false)
+            {
+                if score < min_score {
+                    min_score = score;
+                    best = Some((id, score));
                 }
             }
         }
 
-        trace!(target: "evm::traces::local", %min_score, "no exact match found");
+        if min_score == 0.0 {
+            trace!(target: "evm::traces::local", "found exact match");
+        } else {
+            trace!(target: "evm::traces::local", %min_score, "no exact match found");
+        }
 
         // Note: the diff score can be inaccurate for small contracts so we're using a relatively
         // high threshold here to avoid filtering out too many contracts.
-        if min_score < 0.85 {
-            min_score_id
+        if min_score < self.accept_threshold {
+            best
         } else {
             None
         }
     }
 
+    /// Identifies a created contract like [`identify_code`](Self::identify_code), and decodes the
+    /// constructor arguments appended to its creation code, along with the names of the
+    /// constructor parameters.
+    ///
+    /// Returns `None` if the contract is not identified, or if its constructor takes no arguments.
+    pub fn identify_constructor_args(
+        &self,
+        runtime_code: &[u8],
+        creation_code: &[u8],
+    ) -> Option<Vec<(String, DynSolValue)>> {
+        let (id, abi, _) = self.identify_code_with_score(runtime_code, creation_code)?;
+        let constructor = abi.constructor().filter(|constructor| !constructor.inputs.is_empty())?;
+        let args = decode_constructor_args(&self.known_contracts[id], creation_code)?;
+        let names = constructor.inputs.iter().map(|param| param.name.clone());
+        Some(names.zip(args).collect())
+    }
+
+    /// Returns the first artifact whose ABI contains a function with the given selector.
+    ///
+    /// This is a best-effort fallback for contracts whose code does not match any artifact, such
+    /// as proxies or unverified deployments, so that at least their function calls can be decoded.
+    pub fn identify_selector(&self, selector: Selector) -> Option<(&'a ArtifactId, &'a JsonAbi)> {
+        let selectors = self.selectors.get_or_init(|| {
+            let mut selectors = HashMap::default();
+            for (id, contract) in self.known_contracts.iter() {
+                for function in contract.abi.functions() {
+                    selectors.entry(function.selector()).or_insert(id);
+                }
+            }
+            selectors
+        });
+        let id = *selectors.get(&selector)?;
+        Some((id, &self.known_contracts[id].abi))
+    }
+
+    /// Returns all the artifacts whose runtime code length is within the length window of the given
+    /// runtime code length, along with the diff score of their creation code, sorted by ascending
+    /// score.
+    ///
+    /// If the runtime code is empty, all the artifacts are compared. If the creation code is empty,
+    /// no artifacts are returned.
+    ///
+    /// This is the first pass of [`identify_code`](Self::identify_code), which does not include
+    /// the fallback comparison of deployed code.
+    pub fn identify_code_verbose(
+        &self,
+        runtime_code: &[u8],
+        creation_code: &[u8],
+    ) -> Vec<(&'a ArtifactId, f64)> {
+        if creation_code.is_empty() || self.ordered_ids.is_empty() {
+            return Vec::new();
+        }
+
+        let mut candidates = self
+            .candidates(runtime_code.len())
+            .par_iter()
+            .filter_map(|&(id, _)| Some((id, self.score(id, runtime_code, creation_code, true)?)))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        candidates
+    }
+
+    /// Returns the artifacts whose runtime code length is within the length window of the given
+    /// length, or all the artifacts if the length is unknown.
+    fn candidates(&self, len: usize) -> &[(&'a ArtifactId, usize)] {
+        let (min_len, max_len) = self.length_bounds(len);
+        let start_idx = self.find_index(min_len);
+        let count = self.ordered_ids[start_idx..].partition_point(|(_, len)| *len <= max_len);
+        &self.ordered_ids[start_idx..start_idx + count]
+    }
+
+    /// Returns the bounds of the runtime code length of the candidate artifacts for the given
+    /// length, e.g. `[len * 0.9, len * 1.1]` by default.
+    fn length_bounds(&self, len: usize) -> (usize, usize) {
+        if len == 0 {
+            return (0, usize::MAX);
+        }
+        let (lo, hi) = self.length_window;
+        let min_len = ((len as f64 * lo) as usize).min(len.saturating_sub(MIN_LENGTH_PADDING));
+        let max_len = ((len as f64 * hi) as usize).max(len + MIN_LENGTH_PADDING);
+        (min_len, max_len)
+    }
+
+    /// Returns the artifact with the lowest diff score among the given ones, scoring them in
+    /// parallel.
+    ///
+    /// Stops as soon as an exact match is found. Ties are broken by the order of the artifacts,
+    /// so that the result does not depend on scheduling unless there are multiple exact matches.
+    fn best_match(
+        &self,
+        ids: &[(&'a ArtifactId, usize)],
+        runtime_code: &[u8],
+        creation_code: &[u8],
+        is_creation: bool,
+    ) -> Option<(&'a ArtifactId, f64)> {
+        type Candidate<'a> = (usize, &'a ArtifactId, f64);
+
+        fn min<'a>(a: Option<Candidate<'a>>, b: Option<Candidate<'a>>) -> Option<Candidate<'a>> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(if (b.2, b.0) < (a.2, a.0) { b } else { a }),
+                (a, b) => a.or(b),
+            }
+        }
+
+        let best = ids
+            .par_iter()
+            .enumerate()
+            .filter_map(|(idx, &(id, _))| {
+                Some((idx, id, self.score(id, runtime_code, creation_code, is_creation)?))
+            })
+            .try_fold(
+                || None,
+                |best, candidate| {
+                    // Short-circuit the whole search on an exact match.
+                    if candidate.2 == 0.0 {
+                        return Err(candidate);
+                    }
+                    Ok(min(best, Some(candidate)))
+                },
+            )
+            .try_reduce(|| None, |a, b| Ok(min(a, b)));
+
+        let (_, id, score) = match best {
+            Ok(best) => best?,
+            Err(exact) => exact,
+        };
+        Some((id, score))
+    }
+
+    /// Computes the diff score of an artifact against either the creation or the runtime code.
+    ///
+    /// The compiler metadata and the immutable variables of the runtime code are ignored, see
+    /// [`bytecode_diff_score_masked`].
+    ///
+    /// Returns `None` if the artifact does not have the corresponding bytecode.
+    fn score(
+        &self,
+        id: &ArtifactId,
+        runtime_code: &[u8],
+        creation_code: &[u8],
+        is_creation: bool,
+    ) -> Option<f64> {
+        let contract = self.known_contracts.get(id)?;
+        // Select bytecodes to compare based on `is_creation` flag. Immutables are only set in the
+        // deployed code.
+        let (contract_bytecode, mut current_bytecode, immutable_ranges) = if is_creation {
+            (contract.bytecode()?, creation_code, Vec::new())
+        } else {
+            let immutable_ranges = contract.deployed_bytecode.as_ref()?.immutable_ranges();
+            (contract.deployed_bytecode()?, runtime_code, immutable_ranges)
+        };
+
+        // If we can decode ctor args with the contract abi then remove them from the code to
+        // compare.
+        if is_creation && decode_constructor_args(contract, current_bytecode).is_some() {
+            current_bytecode = &current_bytecode[..contract_bytecode.len()]
+        }
+
+        Some(bytecode_diff_score_masked(contract_bytecode, current_bytecode, &immutable_ranges))
+    }
+
     /// Returns the index of the artifact with the given code length, or the index of the first
     /// artifact with a greater code length if the exact code length is not found.
     fn find_index(&self, len: usize) -> usize {
@@ -140,6 +417,16 @@ fn find_index(&self, len: usize) -> usize {
     }
 }
 
+/// Decodes the constructor arguments appended to the given creation code with the ABI of the
+/// artifact, if the creation code is longer than the one of the artifact.
+fn decode_constructor_args(
+    contract: &ContractData,
+    creation_code: &[u8],
+) -> Option<Vec<DynSolValue>> {
+    let args = creation_code.get(contract.bytecode()?.len()..).filter(|args| !args.is_empty())?;
+    contract.abi.constructor()?.abi_decode_input(args, false).ok()
+}
+
 impl TraceIdentifier for LocalTraceIdentifier<'_> {
     fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddress<'_>> {
         if nodes.is_empty() {
@@ -148,30 +435,338 @@ fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddr
 
         trace!(target: "evm::traces::local", "identify {} addresses", nodes.len());
 
+        // The code of the contracts created in the traces, to identify the implementations of
+        // minimal proxies.
+        let created = nodes
+            .iter()
+            .filter(|node| node.trace.kind.is_any_create())
+            .map(|node| (node.trace.address, (&node.trace.output[..], &node.trace.data[..])))
+            .collect::<HashMap<_, _>>();
+
         nodes
             .iter()
-            .map(|&node| {
-                (
-                    node.trace.address,
-                    node.trace.kind.is_any_create().then_some(&node.trace.output[..]),
-                    node.trace.kind.is_any_create().then_some(&node.trace.data[..]),
-                )
-            })
-            .filter_map(|(address, runtime_code, creation_code)| {
+            .filter_map(|&node| {
+                let address = node.trace.address;
                 let _span =
                     trace_span!(target: "evm::traces::local", "identify", %address).entered();
 
-                let (id, abi) = self.identify_code(runtime_code?, creation_code?)?;
-                trace!(target: "evm::traces::local", id=%id.identifier(), "identified");
+                // Having either the runtime or the creation code is enough to identify.
+                let is_create = node.trace.kind.is_any_create();
+                let (runtime_code, creation_code) = if is_create {
+                    (&node.trace.output[..], &node.trace.data[..])
+                } else {
+                    (&[][..], &[][..])
+                };
+                if let Some(implementation) = minimal_proxy_implementation(runtime_code) {
+                    // Only the code of the implementations created in the traces is known, the
+                    // other ones are left for the decoder to resolve once they are identified.
+                    let Some((id, abi, score)) =
+                        created.get(&implementation).and_then(|(runtime_code, creation_code)| {
+                            self.identify_code_with_score(runtime_code, creation_code)
+                        })
+                    else {
+                        trace!(target: "evm::traces::local", %implementation, "unidentified proxy implementation");
+                        return Some(IdentifiedAddress {
+                            address,
+                            contract: None,
+                            label: None,
+                            abi: None,
+                            artifact_id: None,
+                            confidence: None,
+                            implementation: Some(implementation),
+                        });
+                    };
+                    trace!(target: "evm::traces::local", id=%id.identifier(), %implementation, score, "identified proxy");
+
+                    // The code of the proxy itself does not match the artifact.
+                    return Some(IdentifiedAddress {
+                        address,
+                        contract: Some(id.identifier()),
+                        label: Some(format!("{} (proxy)", id.name)),
+                        abi: Some(Cow::Borrowed(abi)),
+                        artifact_id: None,
+                        confidence: Some(1.0 - score),
+                        implementation: Some(implementation),
+                    });
+                }
+                if !runtime_code.is_empty() || !creation_code.is_empty() {
+                    if let Some((id, abi, score)) =
+                        self.identify_code_with_score(runtime_code, creation_code)
+                    {
+                        trace!(target: "evm::traces::local", id=%id.identifier(), score, "identified");
+
+                        return Some(IdentifiedAddress {
+                            address,
+                            contract: Some(id.identifier()),
+                            label: Some(id.name.clone()),
+                            abi: Some(Cow::Borrowed(abi)),
+                            artifact_id: Some(id.clone()),
+                            confidence: Some(1.0 - score),
+                            implementation: None,
+                        });
+                    }
+                }
+
+                // Fall back to any ABI containing the called function, without identifying the
+                // contract itself.
+                if is_create {
+                    return None;
+                }
+                let (id, abi) = self.identify_selector(node.selector()?)?;
+                trace!(target: "evm::traces::local", id=%id.identifier(), "identified by selector");
 
                 Some(IdentifiedAddress {
                     address,
-                    contract: Some(id.identifier()),
-                    label: Some(id.name.clone()),
+                    contract: None,
+                    label: None,
                     abi: Some(Cow::Borrowed(abi)),
-                    artifact_id: Some(id.clone()),
+                    artifact_id: None,
+                    confidence: None,
+                    implementation: None,
                 })
             })
             .collect()
     }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use foundry_compilers::artifacts::CompactContractBytecode;
+    use revm_inspectors::tracing::types::CallKind;
+
+    /// The creation code of the test artifacts.
+    const CREATION_CODE: [u8; 14] = hex!("6080604052600a600c5f395ff3fe");
+    /// The runtime code of the test artifacts.
+    const RUNTIME_CODE: [u8; 16] = hex!("6080604052348015600e575f5ffd5b50");
+
+    /// Returns the artifact of a contract with the given name and ABI, along with its id. Its code
+    /// is [`CREATION_CODE`] and [`RUNTIME_CODE`].
+    fn artifact(name: &str, abi: serde_json::Value) -> (ArtifactId, CompactContractBytecode) {
+        let id = serde_json::from_value(serde_json::json!({
+            "path": format!("out/{name}.sol/{name}.json"),
+            "name": name,
+            "source": format!("src/{name}.sol"),
+            "version": "0.8.28",
+            "build_id": "0",
+            "profile": "default",
+        }))
+        .unwrap();
+        let artifact = serde_json::from_value(serde_json::json!({
+            "abi": abi,
+            "bytecode": { "object": hex::encode_prefixed(CREATION_CODE) },
+            "deployedBytecode": { "object": hex::encode_prefixed(RUNTIME_CODE) },
+        }))
+        .unwrap();
+        (id, artifact)
+    }
+
+    #[test]
+    fn identifies_nothing_without_artifacts() {
+        let contracts = ContractsByArtifact::default();
+        let identifier = LocalTraceIdentifier::new(&contracts);
+        assert!(identifier.ordered_ids.is_empty());
+
+        let runtime_code = RUNTIME_CODE;
+        let creation_code = CREATION_CODE;
+        for (runtime_code, creation_code) in [
+            (&runtime_code[..], &creation_code[..]),
+            (&runtime_code[..], &[][..]),
+            (&[][..], &creation_code[..]),
+            (&[][..], &[][..]),
+        ] {
+            assert!(identifier.identify_code(runtime_code, creation_code).is_none());
+            assert!(identifier.identify_code_verbose(runtime_code, creation_code).is_empty());
+        }
+        assert!(identifier.candidates(runtime_code.len()).is_empty());
+        assert!(identifier.candidates(0).is_empty());
+    }
+
+    #[test]
+    fn caches_identified_code() {
+        let (id, artifact) = artifact("Counter", serde_json::json!([]));
+        let contracts = ContractsByArtifact::new([(id.clone(), artifact)]);
+        let identifier = LocalTraceIdentifier::new(&contracts);
+
+        let runtime_code = RUNTIME_CODE;
+        let creation_code = CREATION_CODE;
+        for _ in 0..2 {
+            let (found, _) = identifier.identify_code(&runtime_code, &creation_code).unwrap();
+            assert_eq!(found, &id);
+        }
+        assert!(identifier.identify_code(&runtime_code, &[]).is_some());
+        assert_eq!(identifier.cache.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn splits_fallback_and_accept_thresholds() {
+        let (id, artifact) = artifact("Counter", serde_json::json!([]));
+        let contracts = ContractsByArtifact::new([(id, artifact)]);
+
+        // Half of the creation code differs, while the runtime code matches exactly.
+        let runtime_code = RUNTIME_CODE;
+        let creation_code = hex!("6080604052600a00000000000000");
+        let score = |identifier: LocalTraceIdentifier<'_>| {
+            identifier.identify_code_with_score(&runtime_code, &creation_code).map(|(.., s)| s)
+        };
+
+        let creation_score = score(LocalTraceIdentifier::new(&contracts)).unwrap();
+        assert!(creation_score > 0.3 && creation_score < 0.6, "{creation_score}");
+        let identifier = LocalTraceIdentifier::new(&contracts).with_fallback_threshold(0.3);
+        assert_eq!(score(identifier), Some(0.0));
+        let identifier = LocalTraceIdentifier::new(&contracts).with_accept_threshold(0.3);
+        assert_eq!(score(identifier), None);
+        let identifier = LocalTraceIdentifier::new(&contracts)
+            .with_fallback_threshold(0.3)
+            .with_accept_threshold(0.3);
+        assert_eq!(score(identifier), Some(0.0));
+    }
+
+    #[test]
+    fn scores_identified_addresses() {
+        let (id, artifact) = artifact("Counter", serde_json::json!([]));
+        let contracts = ContractsByArtifact::new([(id, artifact)]);
+        let mut identifier = LocalTraceIdentifier::new(&contracts).with_fallback_threshold(1.0);
+
+        let created = |address: u8, creation_code: &[u8]| {
+            let mut node = CallTraceNode::default();
+            node.trace.kind = CallKind::Create;
+            node.trace.address = Address::with_last_byte(address);
+            node.trace.data = creation_code.to_vec().into();
+            node.trace.output = RUNTIME_CODE.to_vec().into();
+            node
+        };
+        let exact = created(1, &CREATION_CODE);
+        // Half of the creation code differs.
+        let fuzzy = created(2, &hex!("6080604052600a00000000000000"));
+
+        let identities = identifier.identify_addresses(&[&exact, &fuzzy]);
+        let [exact, fuzzy] = &identities[..] else { panic!("expected two identities") };
+        assert_eq!(exact.confidence, Some(1.0));
+        let confidence = fuzzy.confidence.unwrap();
+        assert!(confidence > 0.4 && confidence < 0.7, "{confidence}");
+    }
+
+    #[test]
+    fn pads_length_window() {
+        let contracts = ContractsByArtifact::default();
+        let identifier = LocalTraceIdentifier::new(&contracts);
+        assert_eq!(identifier.length_bounds(0), (0, usize::MAX));
+        assert_eq!(identifier.length_bounds(10), (0, 42));
+        assert_eq!(identifier.length_bounds(100), (68, 132));
+        assert_eq!(identifier.length_bounds(1000), (900, 1100));
+
+        let identifier = identifier.with_length_window(0.5, 2.0);
+        assert_eq!(identifier.length_bounds(1000), (500, 2000));
+    }
+
+    #[test]
+    fn finds_minimal_proxy_implementation() {
+        let implementation = Address::repeat_byte(0xbe);
+        let eip1167 = hex!(
+            "363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3"
+        );
+        assert_eq!(minimal_proxy_implementation(&eip1167), Some(implementation));
+
+        let more_minimal =
+            hex!("3d3d3d3d363d3d37363d73bebebebebebebebebebebebebebebebebebebebe5af43d3d93803e602a57fd5bf3");
+        assert_eq!(minimal_proxy_implementation(&more_minimal), Some(implementation));
+
+        let eip7511 =
+            hex!("365f5f375f5f365f73bebebebebebebebebebebebebebebebebebebebe5af43d5f5f3e5f3d91602a57fd5bf3");
+        assert_eq!(minimal_proxy_implementation(&eip7511), Some(implementation));
+
+        // Vanity address with 4 leading zero bytes, pushed with `PUSH16`.
+        let vanity = hex!(
+            "363d3d373d3d3d363d6fbebebebebebebebebebebebebebebebe5af43d82803e903d91602757fd5bf3"
+        );
+        let mut expected = implementation;
+        expected[..4].fill(0);
+        assert_eq!(minimal_proxy_implementation(&vanity), Some(expected));
+
+        // Wrong jump destination for the address length.
+        let invalid = hex!(
+            "363d3d373d3d3d363d6fbebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3"
+        );
+        assert_eq!(minimal_proxy_implementation(&invalid), None);
+        assert_eq!(minimal_proxy_implementation(&eip1167[..44]), None);
+        assert_eq!(minimal_proxy_implementation(&[]), None);
+    }
+
+    #[test]
+    fn leaves_proxies_of_unknown_implementations_to_the_decoder() {
+        let (id, artifact) = artifact("Counter", serde_json::json!([]));
+        let contracts = ContractsByArtifact::new([(id, artifact)]);
+        let mut identifier = LocalTraceIdentifier::new(&contracts);
+
+        // The implementation was deployed before the traced call.
+        let mut proxy = CallTraceNode::default();
+        proxy.trace.kind = CallKind::Create;
+        proxy.trace.address = Address::with_last_byte(1);
+        proxy.trace.output = hex!(
+            "363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3"
+        )
+        .to_vec()
+        .into();
+
+        let identities = identifier.identify_addresses(&[&proxy]);
+        let [identity] = &identities[..] else { panic!("expected one identity") };
+        assert_eq!(identity.implementation, Some(Address::repeat_byte(0xbe)));
+        assert!(identity.label.is_none() && identity.contract.is_none());
+    }
+
+    #[test]
+    fn decodes_constructor_args() {
+        let abi = serde_json::json!([{
+            "type": "constructor",
+            "inputs": [
+                { "name": "owner", "type": "address", "internalType": "address" },
+                { "name": "cap", "type": "uint256", "internalType": "uint256" },
+            ],
+            "stateMutability": "nonpayable",
+        }]);
+        let contracts = ContractsByArtifact::new([artifact("Vault", abi)]);
+        let identifier = LocalTraceIdentifier::new(&contracts);
+
+        let owner = Address::with_last_byte(1);
+        let args = DynSolValue::Tuple(vec![
+            DynSolValue::Address(owner),
+            DynSolValue::Uint(U256::from(100), 256),
+        ]);
+        let runtime_code = RUNTIME_CODE;
+        let creation_code = [&CREATION_CODE[..], &args.abi_encode_params()].concat();
+        let decoded = identifier.identify_constructor_args(&runtime_code, &creation_code).unwrap();
+        assert_eq!(
+            decoded,
+            [
+                ("owner".to_string(), DynSolValue::Address(owner)),
+                ("cap".to_string(), DynSolValue::Uint(U256::from(100), 256)),
+            ]
+        );
+
+        // Deployed without arguments.
+        let creation_code = CREATION_CODE;
+        assert!(identifier.identify_constructor_args(&runtime_code, &creation_code).is_none());
+    }
+
+    #[test]
+    fn skips_constructors_without_args() {
+        let abi = serde_json::json!([{
+            "type": "constructor",
+            "inputs": [],
+            "stateMutability": "nonpayable",
+        }]);
+        let contracts = ContractsByArtifact::new([artifact("Vault", abi)]);
+        let identifier = LocalTraceIdentifier::new(&contracts);
+
+        // Data appended to the creation code, which is not decoded as arguments.
+        let creation_code = [&CREATION_CODE[..], &[0; 32]].concat();
+        assert!(identifier.identify_code(&RUNTIME_CODE, &creation_code).is_some());
+        assert!(identifier.identify_constructor_args(&RUNTIME_CODE, &creation_code).is_none());
+    }
 }
@@ -7,11 +7,17 @@
 use std::borrow::Cow;
 
 mod local;
-pub use local::LocalTraceIdentifier;
+pub use local::{
+    minimal_proxy_implementation, LocalTraceIdentifier, DEFAULT_DIFF_SCORE_THRESHOLD,
+    DEFAULT_LENGTH_WINDOW, MIN_LENGTH_PADDING,
+};
 
 mod etherscan;
 pub use etherscan::EtherscanIdentifier;
 
+mod chained;
+pub use chained::ChainedTraceIdentifier;
+
 mod signatures;
 pub use signatures::{SignaturesCache, SignaturesIdentifier};
 
@@ -29,12 +35,30 @@ pub struct IdentifiedAddress<'a> {
     pub abi: Option<Cow<'a, JsonAbi>>,
     /// The artifact ID of the contract, if any.
     pub artifact_id: Option<ArtifactId>,
+    /// How confident the identifier is that the address is the identified contract, from `0.0`
+    /// to `1.0` for an exact match.
+    ///
+    /// `None` if the identifier does not score its matches.
+    pub confidence: Option<f64>,
+    /// The implementation all calls are delegated to, if the address is a minimal proxy.
+    ///
+    /// The proxy may be left unidentified if its implementation is not, in which case the
+    /// decoder labels it once the implementation gets identified.
+    pub implementation: Option<Address>,
 }
 
 /// Trace identifiers figure out what ABIs and labels belong to all the addresses of the trace.
 pub trait TraceIdentifier {
     /// Attempts to identify an address in one or more call traces.
     fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddress<'_>>;
+
+    /// Returns `true` if the identifier doesn't perform any network lookups.
+    ///
+    /// Local identifiers are cheap, and can be run before the network-backed ones to reduce the
+    /// number of addresses these have to look up.
+    fn is_local(&self) -> bool {
+        false
+    }
 }
 
 /// A collection of trace identifiers.
@@ -56,7 +80,10 @@ fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddr
         let mut identities = Vec::with_capacity(nodes.len());
         if let Some(local) = &mut self.local {
             identities.extend(local.identify_addresses(nodes));
-            if identities.len() >= nodes.len() {
+            // Addresses only identified by a function selector may still be fully identified.
+            if identities.iter().filter(|identity| identity.contract.is_some()).count() >=
+                nodes.len()
+            {
                 return identities;
             }
         }
@@ -65,6 +92,10 @@ fn identify_addresses(&mut self, nodes: &[&CallTraceNode]) -> Vec<IdentifiedAddr
         }
         identities
     }
+
+    fn is_local(&self) -> bool {
+        self.etherscan.is_none()
+    }
 }
 
 impl<'a> TraceIdentifiers<'a> {
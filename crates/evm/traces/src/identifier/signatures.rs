@@ -144,6 +144,22 @@ pub fn get(&self, key: &SelectorKind) -> Option<Option<String>> {
     pub fn contains_key(&self, key: &SelectorKind) -> bool {
         self.signatures.contains_key(key)
     }
+
+    /// Returns the sorted signatures of the cached functions with the given name.
+    pub fn function_signatures(&self, name: &str) -> Vec<String> {
+        let mut signatures = self
+            .signatures
+            .iter()
+            .filter_map(|(kind, signature)| match kind {
+                SelectorKind::Function(_) => signature.as_ref(),
+                _ => None,
+            })
+            .filter(|signature| signature.split('(').next() == Some(name))
+            .cloned()
+            .collect::<Vec<_>>();
+        signatures.sort_unstable();
+        signatures
+    }
 }
 
 /// An identifier that tries to identify functions and events using signatures found at
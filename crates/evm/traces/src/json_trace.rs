@@ -0,0 +1,182 @@
+use alloy_primitives::{Address, Bytes, Selector, B256, U256};
+use revm_inspectors::tracing::{
+    types::{CallLog, CallTraceNode, CallTraceStep, DecodedTraceStep, TraceMemberOrder},
+    CallTraceArena,
+};
+use serde::{Deserialize, Serialize};
+
+/// Builds a structured JSON representation of the call tree of a call trace arena.
+///
+/// Internal function calls are only included if the arena has been decoded with a
+/// [`DebugTraceIdentifier`](crate::DebugTraceIdentifier).
+pub fn build(arena: &CallTraceArena) -> JsonCallTrace {
+    JsonCallTrace::from_node(arena.nodes(), 0)
+}
+
+/// A call in the JSON call tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonCallTrace {
+    /// The kind of call, e.g. `CALL`, `STATICCALL` or `CREATE`.
+    pub kind: String,
+    /// The caller address.
+    pub caller: Address,
+    /// The callee address, or the created contract.
+    pub address: Address,
+    /// The label of the callee, if identified.
+    pub label: Option<String>,
+    /// The selector of the called function, if any.
+    pub selector: Option<Selector>,
+    /// The decoded function signature, if identified.
+    pub signature: Option<String>,
+    /// The decoded function arguments, if identified.
+    pub args: Option<Vec<String>>,
+    /// The decoded return data or revert reason, if identified.
+    pub return_data: Option<String>,
+    /// The value transferred in the call.
+    pub value: U256,
+    /// The raw calldata, or init code for creations.
+    pub input: Bytes,
+    /// The raw output of the call.
+    pub output: Bytes,
+    /// Gas used by the call, including its subcalls.
+    pub gas_used: u64,
+    /// The gas limit of the call.
+    pub gas_limit: u64,
+    /// Whether the call succeeded.
+    pub success: bool,
+    /// Logs emitted directly by this call.
+    pub logs: Vec<JsonCallLog>,
+    /// Internal function calls made in this call context.
+    pub internal_calls: Vec<JsonInternalCall>,
+    /// Subcalls made by this call.
+    pub calls: Vec<Self>,
+}
+
+impl JsonCallTrace {
+    fn from_node(nodes: &[CallTraceNode], idx: usize) -> Self {
+        let node = &nodes[idx];
+        let trace = &node.trace;
+        let decoded = &trace.decoded;
+
+        let mut calls = Vec::new();
+        let mut internal_calls = InternalCallsBuilder::default();
+        for order in &node.ordering {
+            match order {
+                TraceMemberOrder::Call(child_idx) => {
+                    calls.push(Self::from_node(nodes, node.children[*child_idx]));
+                }
+                TraceMemberOrder::Step(step_idx) => {
+                    internal_calls.process_step(&trace.steps, *step_idx)
+                }
+                TraceMemberOrder::Log(_) => {}
+            }
+        }
+
+        Self {
+            kind: trace.kind.to_string(),
+            caller: trace.caller,
+            address: trace.address,
+            label: decoded.label.clone(),
+            selector: if trace.kind.is_any_create() { None } else { node.selector() },
+            signature: decoded.call_data.as_ref().map(|cd| cd.signature.clone()),
+            args: decoded.call_data.as_ref().map(|cd| cd.args.clone()),
+            return_data: decoded.return_data.clone(),
+            value: trace.value,
+            input: trace.data.clone(),
+            output: trace.output.clone(),
+            gas_used: trace.gas_used,
+            gas_limit: trace.gas_limit,
+            success: trace.success,
+            logs: node.logs.iter().map(JsonCallLog::from).collect(),
+            internal_calls: internal_calls.build(),
+            calls,
+        }
+    }
+}
+
+/// A log in the JSON call tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonCallLog {
+    /// The raw log topics.
+    pub topics: Vec<B256>,
+    /// The raw log data.
+    pub data: Bytes,
+    /// The decoded event name, if identified.
+    pub name: Option<String>,
+    /// The decoded event parameters as `(name, value)` pairs, if identified.
+    pub params: Option<Vec<(String, String)>>,
+}
+
+impl From<&CallLog> for JsonCallLog {
+    fn from(log: &CallLog) -> Self {
+        Self {
+            topics: log.raw_log.topics().to_vec(),
+            data: log.raw_log.data.clone(),
+            name: log.decoded.name.clone(),
+            params: log.decoded.params.clone(),
+        }
+    }
+}
+
+/// An internal function call in the JSON call tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonInternalCall {
+    /// The name of the internal function.
+    pub name: String,
+    /// The decoded function arguments, if available.
+    pub args: Option<Vec<String>>,
+    /// The decoded return values, if available.
+    pub return_data: Option<Vec<String>>,
+    /// Gas used by the internal call.
+    pub gas_used: u64,
+    /// Nested internal function calls.
+    pub internal_calls: Vec<Self>,
+}
+
+/// Nests the decoded internal calls of a call context, in the order they are entered.
+#[derive(Default)]
+struct InternalCallsBuilder {
+    /// Finished top-level internal calls.
+    calls: Vec<JsonInternalCall>,
+    /// Currently open internal calls, along with the index of the step they end at.
+    stack: Vec<(JsonInternalCall, usize)>,
+}
+
+impl InternalCallsBuilder {
+    fn process_step(&mut self, steps: &[CallTraceStep], step_idx: usize) {
+        // Exit all the internal calls that end before this step.
+        while self.stack.last().is_some_and(|(_, end_idx)| *end_idx <= step_idx) {
+            self.exit();
+        }
+
+        let step = &steps[step_idx];
+        if let Some(DecodedTraceStep::InternalCall(decoded, end_idx)) = &step.decoded {
+            let call = JsonInternalCall {
+                name: decoded.func_name.clone(),
+                args: decoded.args.clone(),
+                return_data: decoded.return_data.clone(),
+                gas_used: steps[*end_idx].gas_used.saturating_sub(step.gas_used),
+                internal_calls: Vec::new(),
+            };
+            self.stack.push((call, *end_idx));
+        }
+    }
+
+    fn exit(&mut self) {
+        let Some((call, _)) = self.stack.pop() else { return };
+        match self.stack.last_mut() {
+            Some((parent, _)) => parent.internal_calls.push(call),
+            None => self.calls.push(call),
+        }
+    }
+
+    fn build(mut self) -> Vec<JsonInternalCall> {
+        while !self.stack.is_empty() {
+            self.exit();
+        }
+        self.calls
+    }
+}
@@ -15,24 +15,26 @@
     contracts::{ContractsByAddress, ContractsByArtifact},
     shell,
 };
-use revm::interpreter::OpCode;
-use revm_inspectors::tracing::{
-    types::{DecodedTraceStep, TraceMemberOrder},
-    OpcodeFilter,
-};
+use revm::interpreter::{InstructionResult, OpCode};
+use revm_inspectors::tracing::{types::DecodedTraceStep, OpcodeFilter};
 use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     collections::BTreeSet,
+    io,
     ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
-use alloy_primitives::map::HashMap;
+use alloy_primitives::{
+    map::{AddressHashSet, HashMap},
+    Address,
+};
 
 pub use revm_inspectors::tracing::{
     types::{
-        CallKind, CallLog, CallTrace, CallTraceNode, DecodedCallData, DecodedCallLog,
-        DecodedCallTrace,
+        CallKind, CallLog, CallTrace, CallTraceNode, CallTraceStep, DecodedCallData,
+        DecodedCallLog, DecodedCallTrace, TraceMemberOrder,
     },
     CallTraceArena, FourByteInspector, GethTraceBuilder, ParityTraceBuilder, StackSnapshotType,
     TraceWriter, TracingInspector, TracingInspectorConfig,
@@ -52,6 +54,11 @@
 
 pub mod folded_stack_trace;
 
+pub mod json_trace;
+
+pub mod storage;
+pub use storage::{label_storage_changes, label_storage_reads, record_storage_reads};
+
 pub type Traces = Vec<(TraceKind, SparsedTraceArena)>;
 
 /// Trace arena keeping track of ignored trace items.
@@ -179,6 +186,225 @@ pub async fn decode_trace_arena(arena: &mut CallTraceArena, decoder: &CallTraceD
     decoder.populate_traces(arena.nodes_mut()).await;
 }
 
+/// Limits the depth of the rendered call tree, replacing the calls nested deeper than `max_depth`
+/// with a `... N nested calls omitted` marker.
+///
+/// Decoded internal function calls count as a level of depth as well. This only affects how the
+/// trace is rendered, and must be applied to an arena without ignored trace items.
+pub fn limit_trace_depth(arena: &mut CallTraceArena, max_depth: usize) {
+    limit_node_depth(arena.nodes_mut(), 0, 0, max_depth);
+}
+
+fn limit_node_depth(nodes: &mut [CallTraceNode], idx: usize, depth: usize, max_depth: usize) {
+    // End steps of the currently open internal calls.
+    let mut internal_call_ends = Vec::new();
+    // End step of the omitted internal call whose items are being skipped.
+    let mut skip_until = None;
+    let mut omitted = 0;
+
+    for item in std::mem::take(&mut nodes[idx].ordering) {
+        let internal_call_end = match item {
+            TraceMemberOrder::Step(step_idx) => match &nodes[idx].trace.steps[step_idx].decoded {
+                Some(DecodedTraceStep::InternalCall(_, end_idx)) => Some(*end_idx),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(end_idx) = skip_until {
+            omitted += count_item_calls(nodes, idx, item);
+            if item == TraceMemberOrder::Step(end_idx) {
+                skip_until = None;
+            }
+            continue;
+        }
+
+        if let TraceMemberOrder::Step(step_idx) = item {
+            if internal_call_ends.last() == Some(&step_idx) {
+                internal_call_ends.pop();
+            }
+        }
+
+        let item_depth = depth + internal_call_ends.len() + 1;
+        match (item, internal_call_end) {
+            (TraceMemberOrder::Call(child_idx), _) if item_depth > max_depth => {
+                omitted += 1 + count_calls(nodes, nodes[idx].children[child_idx]);
+                continue;
+            }
+            (TraceMemberOrder::Call(child_idx), _) => {
+                limit_node_depth(nodes, nodes[idx].children[child_idx], item_depth, max_depth);
+            }
+            (_, Some(end_idx)) if item_depth > max_depth => {
+                omitted += 1;
+                skip_until = Some(end_idx);
+                continue;
+            }
+            (_, Some(end_idx)) => internal_call_ends.push(end_idx),
+            _ => {}
+        }
+
+        push_omitted_marker(&mut nodes[idx], &mut omitted);
+        nodes[idx].ordering.push(item);
+    }
+    push_omitted_marker(&mut nodes[idx], &mut omitted);
+}
+
+/// Prunes the rendered call tree to the calls to the given addresses, along with the calls leading
+/// to them and the calls they make. The other calls are replaced with a `... N nested calls
+/// omitted` marker.
+///
+/// This only affects how the trace is rendered, and must be applied to an arena without ignored
+/// trace items.
+pub fn filter_trace_addresses(arena: &mut CallTraceArena, addresses: &AddressHashSet) {
+    let nodes = arena.nodes_mut();
+    // Whether each node is a call to one of the addresses, or leads to one. Children are always
+    // after their parent in the arena.
+    let mut leads_to_match = vec![false; nodes.len()];
+    for idx in (0..nodes.len()).rev() {
+        leads_to_match[idx] = addresses.contains(&nodes[idx].trace.address) ||
+            nodes[idx].children.iter().any(|&child| leads_to_match[child]);
+    }
+    if !nodes.is_empty() {
+        filter_node_addresses(nodes, 0, addresses, &leads_to_match);
+    }
+}
+
+fn filter_node_addresses(
+    nodes: &mut [CallTraceNode],
+    idx: usize,
+    addresses: &AddressHashSet,
+    leads_to_match: &[bool],
+) {
+    // All the calls made by a matching call are kept.
+    if addresses.contains(&nodes[idx].trace.address) {
+        return;
+    }
+
+    let mut omitted = 0;
+    for item in std::mem::take(&mut nodes[idx].ordering) {
+        if let TraceMemberOrder::Call(child_idx) = item {
+            let child = nodes[idx].children[child_idx];
+            if !leads_to_match[child] {
+                omitted += 1 + count_calls(nodes, child);
+                continue;
+            }
+            filter_node_addresses(nodes, child, addresses, leads_to_match);
+        }
+
+        push_omitted_marker(&mut nodes[idx], &mut omitted);
+        nodes[idx].ordering.push(item);
+    }
+    push_omitted_marker(&mut nodes[idx], &mut omitted);
+}
+
+/// Returns the number of calls made in a node, including decoded internal calls.
+fn count_calls(nodes: &[CallTraceNode], idx: usize) -> usize {
+    nodes[idx].ordering.iter().map(|item| count_item_calls(nodes, idx, *item)).sum()
+}
+
+/// Returns the number of calls an item of a node accounts for, including nested calls.
+fn count_item_calls(nodes: &[CallTraceNode], idx: usize, item: TraceMemberOrder) -> usize {
+    match item {
+        TraceMemberOrder::Call(child_idx) => 1 + count_calls(nodes, nodes[idx].children[child_idx]),
+        TraceMemberOrder::Step(step_idx) => matches!(
+            nodes[idx].trace.steps[step_idx].decoded,
+            Some(DecodedTraceStep::InternalCall(..))
+        ) as usize,
+        TraceMemberOrder::Log(_) => 0,
+    }
+}
+
+/// Appends a marker line for the omitted calls to the node, if any.
+fn push_omitted_marker(node: &mut CallTraceNode, omitted: &mut usize) {
+    if *omitted == 0 {
+        return;
+    }
+
+    push_line(node, format!("... {omitted} nested calls omitted"));
+    *omitted = 0;
+}
+
+/// Appends a line to the items of the node, rendered as is by the trace writer.
+pub(crate) fn push_line(node: &mut CallTraceNode, line: String) {
+    node.ordering.push(TraceMemberOrder::Step(node.trace.steps.len()));
+    node.trace.steps.push(CallTraceStep {
+        depth: node.trace.depth as u64,
+        pc: 0,
+        code_section_idx: 0,
+        op: OpCode::STOP,
+        contract: node.trace.address,
+        stack: None,
+        push_stack: None,
+        memory: None,
+        returndata: Default::default(),
+        gas_remaining: 0,
+        gas_refund_counter: 0,
+        gas_used: 0,
+        gas_cost: 0,
+        storage_change: None,
+        status: InstructionResult::Stop,
+        immediate_bytes: None,
+        decoded: Some(DecodedTraceStep::Line(line)),
+    });
+}
+
+/// Renders the `SELFDESTRUCT`s of the traces as a `SELFDESTRUCT <contract> -> <beneficiary>` item
+/// at the end of the calls that executed them, along with the balance sent to the beneficiary.
+///
+/// The addresses are rendered with their label if they have one.
+pub fn label_selfdestructs(arena: &mut CallTraceArena, decoder: &CallTraceDecoder) {
+    let label = |address: Address| {
+        decoder.labels.get(&address).cloned().unwrap_or_else(|| address.to_string())
+    };
+    for node in arena.nodes_mut() {
+        let CallTrace {
+            selfdestruct_address: Some(contract),
+            selfdestruct_refund_target: Some(beneficiary),
+            selfdestruct_transferred_value: value,
+            ..
+        } = node.trace
+        else {
+            continue;
+        };
+        let line = format!(
+            "SELFDESTRUCT {} -> {}{{value: {}}}",
+            label(contract),
+            label(beneficiary),
+            value.unwrap_or_default()
+        );
+        push_line(node, line);
+    }
+}
+
+/// Renders the constructor arguments decoded with
+/// [`CallTraceDecoder::identify_constructor_args`] as a `constructor(<name>: <value>, ..)` item at
+/// the start of the creation of the contracts.
+pub fn label_constructor_args(arena: &mut CallTraceArena, decoder: &CallTraceDecoder) {
+    if decoder.constructor_args.is_empty() {
+        return;
+    }
+    for node in arena.nodes_mut() {
+        if !node.trace.kind.is_any_create() {
+            continue;
+        }
+        let Some(args) = decoder.constructor_args.get(&node.trace.address) else { continue };
+        let args = args
+            .iter()
+            .map(|(name, value)| {
+                let value = decoder.format_value(value);
+                if name.is_empty() {
+                    value
+                } else {
+                    format!("{name}: {value}")
+                }
+            })
+            .collect::<Vec<_>>();
+        push_line(node, format!("constructor({})", args.join(", ")));
+        // Rendered before the calls made by the constructor.
+        node.ordering.rotate_right(1);
+    }
+}
+
 /// Render a collection of call traces to a string.
 pub fn render_trace_arena(arena: &SparsedTraceArena) -> String {
     render_trace_arena_inner(arena, false, false)
@@ -191,25 +417,34 @@ pub fn render_trace_arena_inner(
     with_bytecodes: bool,
     with_storage_changes: bool,
 ) -> String {
+    let mut out = Vec::new();
+    write_trace_arena(arena, with_bytecodes, with_storage_changes, &mut out)
+        .expect("Failed to write traces");
+    String::from_utf8(out).expect("trace writer wrote invalid UTF-8")
+}
+
+/// Writes a collection of call traces to the given writer as they are rendered, instead of
+/// buffering them, see [`render_trace_arena_inner`].
+pub fn write_trace_arena<W: io::Write>(
+    arena: &SparsedTraceArena,
+    with_bytecodes: bool,
+    with_storage_changes: bool,
+    writer: W,
+) -> io::Result<()> {
     if shell::is_json() {
-        return serde_json::to_string(&arena.resolve_arena()).expect("Failed to write traces");
+        return serde_json::to_writer(writer, &arena.resolve_arena()).map_err(Into::into);
     }
 
-    let mut w = TraceWriter::new(Vec::<u8>::new())
+    let mut w = TraceWriter::new(writer)
         .color_cheatcodes(true)
-        .use_colors(convert_color_choice(shell::color_choice()))
+        .use_colors(if shell::out_supports_color() {
+            revm_inspectors::ColorChoice::Always
+        } else {
+            revm_inspectors::ColorChoice::Never
+        })
         .write_bytecodes(with_bytecodes)
         .with_storage_changes(with_storage_changes);
-    w.write_arena(&arena.resolve_arena()).expect("Failed to write traces");
-    String::from_utf8(w.into_writer()).expect("trace writer wrote invalid UTF-8")
-}
-
-fn convert_color_choice(choice: shell::ColorChoice) -> revm_inspectors::ColorChoice {
-    match choice {
-        shell::ColorChoice::Auto => revm_inspectors::ColorChoice::Auto,
-        shell::ColorChoice::Always => revm_inspectors::ColorChoice::Always,
-        shell::ColorChoice::Never => revm_inspectors::ColorChoice::Never,
-    }
+    w.write_arena(&arena.resolve_arena())
 }
 
 /// Specifies the kind of trace.
@@ -271,16 +506,42 @@ pub enum InternalTraceMode {
     None,
     /// Traces internal functions without decoding inputs/outputs from memory.
     Simple,
+    /// Same as `Full`, but only decodes the internal functions defined outside of the called
+    /// contract and the contracts it inherits from, such as library functions.
+    Boundaries,
     /// Same as `Simple`, but also tracks memory snapshots.
     Full,
 }
 
+impl InternalTraceMode {
+    pub const fn is_none(self) -> bool {
+        matches!(self, Self::None)
+    }
+}
+
+impl FromStr for InternalTraceMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "simple" => Ok(Self::Simple),
+            "boundaries" => Ok(Self::Boundaries),
+            "full" => Ok(Self::Full),
+            _ => Err(format!(
+                "invalid internal trace mode `{s}`, expected one of `none`, `simple`, `boundaries` \
+                 or `full`"
+            )),
+        }
+    }
+}
+
 impl From<InternalTraceMode> for TraceMode {
     fn from(mode: InternalTraceMode) -> Self {
         match mode {
             InternalTraceMode::None => Self::None,
             InternalTraceMode::Simple => Self::JumpSimple,
-            InternalTraceMode::Full => Self::Jump,
+            InternalTraceMode::Boundaries | InternalTraceMode::Full => Self::Jump,
         }
     }
 }
@@ -386,3 +647,219 @@ pub fn into_config(self) -> Option<TracingInspectorConfig> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_primitives::U256;
+    use revm_inspectors::tracing::types::DecodedInternalCall;
+
+    #[test]
+    fn parses_internal_trace_mode() {
+        assert_eq!("boundaries".parse(), Ok(InternalTraceMode::Boundaries));
+        assert_eq!("full".parse(), Ok(InternalTraceMode::Full));
+        assert!("internal".parse::<InternalTraceMode>().is_err());
+
+        assert_eq!(
+            TraceMode::Call.with_decode_internal(InternalTraceMode::Boundaries),
+            TraceMode::Jump
+        );
+    }
+
+    #[test]
+    fn filters_trace_addresses() {
+        // 0 -> 1 -> 2 (target) -> 3
+        //   -> 4 -> 5
+        let mut arena = CallTraceArena::default();
+        let parents = [None, Some(0), Some(1), Some(2), Some(0), Some(4)];
+        for (idx, parent) in parents.into_iter().enumerate() {
+            if idx > 0 {
+                arena.nodes_mut().push(CallTraceNode { idx, parent, ..Default::default() });
+            }
+            arena.nodes_mut()[idx].trace.address = Address::with_last_byte(idx as u8);
+            if let Some(parent) = parent {
+                let parent = &mut arena.nodes_mut()[parent];
+                parent.ordering.push(TraceMemberOrder::Call(parent.children.len()));
+                parent.children.push(idx);
+            }
+        }
+
+        filter_trace_addresses(&mut arena, &[Address::with_last_byte(2)].into_iter().collect());
+
+        let nodes = arena.nodes();
+        assert_eq!(nodes[0].ordering.len(), 2);
+        assert_eq!(nodes[0].ordering[0], TraceMemberOrder::Call(0));
+        let TraceMemberOrder::Step(marker) = nodes[0].ordering[1] else { panic!("no marker") };
+        assert_eq!(
+            nodes[0].trace.steps[marker].decoded,
+            Some(DecodedTraceStep::Line("... 2 nested calls omitted".to_string()))
+        );
+        assert_eq!(nodes[1].ordering, [TraceMemberOrder::Call(0)]);
+        assert_eq!(nodes[2].ordering, [TraceMemberOrder::Call(0)]);
+    }
+
+    #[test]
+    fn limits_trace_depth() {
+        // 0 -> 1 -> 2 -> 3, with the call to 1 optionally made in an internal call of 0.
+        let arena = |internal_call: bool| {
+            let mut arena = CallTraceArena::default();
+            for idx in 1..4 {
+                let parent = Some(idx - 1);
+                arena.nodes_mut().push(CallTraceNode { idx, parent, ..Default::default() });
+                let parent = &mut arena.nodes_mut()[idx - 1];
+                parent.ordering.push(TraceMemberOrder::Call(0));
+                parent.children.push(idx);
+            }
+            if internal_call {
+                let node = &mut arena.nodes_mut()[0];
+                push_line(node, String::new());
+                push_line(node, String::new());
+                node.trace.steps[0].decoded = Some(DecodedTraceStep::InternalCall(
+                    DecodedInternalCall {
+                        func_name: "Vault::_transfer".to_string(),
+                        args: None,
+                        return_data: None,
+                    },
+                    1,
+                ));
+                node.ordering = vec![
+                    TraceMemberOrder::Step(0),
+                    TraceMemberOrder::Call(0),
+                    TraceMemberOrder::Step(1),
+                ];
+            }
+            arena
+        };
+        let marker = |node: &CallTraceNode, item: TraceMemberOrder| {
+            let TraceMemberOrder::Step(step) = item else { panic!("no marker") };
+            node.trace.steps[step].decoded.clone()
+        };
+        let omitted = |count: usize| {
+            Some(DecodedTraceStep::Line(format!("... {count} nested calls omitted")))
+        };
+
+        let mut calls = arena(false);
+        limit_trace_depth(&mut calls, 1);
+        let nodes = calls.nodes();
+        assert_eq!(nodes[0].ordering, [TraceMemberOrder::Call(0)]);
+        assert_eq!(nodes[1].ordering.len(), 1);
+        assert_eq!(marker(&nodes[1], nodes[1].ordering[0]), omitted(2));
+
+        // The internal call is a level of depth, so the call to 1 is omitted instead.
+        let mut internal_calls = arena(true);
+        limit_trace_depth(&mut internal_calls, 1);
+        let node = &internal_calls.nodes()[0];
+        assert_eq!(
+            node.ordering,
+            [TraceMemberOrder::Step(0), TraceMemberOrder::Step(2), TraceMemberOrder::Step(1)]
+        );
+        assert_eq!(marker(node, node.ordering[1]), omitted(3));
+
+        // An omitted internal call is counted along with the calls made in it.
+        let mut internal_calls = arena(true);
+        limit_trace_depth(&mut internal_calls, 0);
+        let node = &internal_calls.nodes()[0];
+        assert_eq!(node.ordering, [TraceMemberOrder::Step(2)]);
+        assert_eq!(marker(node, node.ordering[0]), omitted(4));
+    }
+
+    #[test]
+    fn labels_selfdestructs() {
+        let contract = Address::with_last_byte(1);
+        let beneficiary = Address::with_last_byte(2);
+        let mut arena = CallTraceArena::default();
+        let trace = &mut arena.nodes_mut()[0].trace;
+        trace.address = contract;
+        trace.selfdestruct_address = Some(contract);
+        trace.selfdestruct_refund_target = Some(beneficiary);
+        trace.selfdestruct_transferred_value = Some(U256::from(100));
+
+        let decoder = CallTraceDecoderBuilder::new()
+            .with_labels([(beneficiary, "Vault".to_string())])
+            .build();
+        label_selfdestructs(&mut arena, &decoder);
+
+        let node = &arena.nodes()[0];
+        let [TraceMemberOrder::Step(step)] = node.ordering[..] else { panic!("no selfdestruct") };
+        assert_eq!(
+            node.trace.steps[step].decoded,
+            Some(DecodedTraceStep::Line(format!("SELFDESTRUCT {contract} -> Vault{{value: 100}}")))
+        );
+    }
+
+    #[test]
+    fn labels_constructor_args() {
+        let vault = Address::with_last_byte(1);
+        let owner = Address::with_last_byte(2);
+        let mut arena = CallTraceArena::default();
+        arena.nodes_mut()[0].trace.kind = CallKind::Create;
+        arena.nodes_mut()[0].trace.address = vault;
+        arena.nodes_mut().push(CallTraceNode { idx: 1, parent: Some(0), ..Default::default() });
+        arena.nodes_mut()[0].children.push(1);
+        arena.nodes_mut()[0].ordering.push(TraceMemberOrder::Call(0));
+
+        let mut decoder =
+            CallTraceDecoderBuilder::new().with_labels([(owner, "Owner".to_string())]).build();
+        decoder.constructor_args.insert(
+            vault,
+            vec![
+                ("owner".to_string(), DynSolValue::Address(owner)),
+                (String::new(), DynSolValue::Uint(U256::from(100), 256)),
+            ],
+        );
+        label_constructor_args(&mut arena, &decoder);
+
+        let node = &arena.nodes()[0];
+        let [TraceMemberOrder::Step(step), TraceMemberOrder::Call(0)] = node.ordering[..] else {
+            panic!("constructor arguments not rendered first")
+        };
+        assert_eq!(
+            node.trace.steps[step].decoded,
+            Some(DecodedTraceStep::Line(format!("constructor(owner: Owner: [{owner}], 100)")))
+        );
+    }
+
+    #[test]
+    fn streams_large_trace_arenas() {
+        // A chain of 100 nested calls, the deepest of which makes 1000 calls.
+        let mut arena = CallTraceArena::default();
+        for idx in 1..1100 {
+            let parent = idx.min(100) - 1;
+            arena.nodes_mut().push(CallTraceNode {
+                idx,
+                parent: Some(parent),
+                ..Default::default()
+            });
+            arena.nodes_mut()[idx].trace.address = Address::with_last_byte(idx as u8);
+            let parent = &mut arena.nodes_mut()[parent];
+            parent.ordering.push(TraceMemberOrder::Call(parent.children.len()));
+            parent.children.push(idx);
+        }
+        let arena = SparsedTraceArena { arena, ignored: Default::default() };
+
+        /// Records every write, to check that the trace is not written at once.
+        #[derive(Default)]
+        struct Recorder {
+            out: Vec<u8>,
+            writes: usize,
+        }
+
+        impl io::Write for Recorder {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.writes += 1;
+                self.out.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut recorder = Recorder::default();
+        write_trace_arena(&arena, false, false, &mut recorder).unwrap();
+        assert!(recorder.writes > arena.nodes().len());
+        assert_eq!(String::from_utf8(recorder.out).unwrap(), render_trace_arena(&arena));
+    }
+}
@@ -0,0 +1,543 @@
+//! Labeling of storage changes and reads with the storage layouts of the contracts.
+
+use crate::{push_line, CallTraceArena, CallTraceDecoder};
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{keccak256, map::B256HashMap, Address, Bytes, B256, I256, U256};
+use foundry_common::fmt::format_token;
+use foundry_compilers::artifacts::{StorageLayout, StorageType};
+use revm::interpreter::OpCode;
+use revm_inspectors::tracing::{
+    types::CallTraceNode, OpcodeFilter, StackSnapshotType, TracingInspectorConfig,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+/// Same as the pipe of the trace writer.
+const PIPE: &str = "  │ ";
+
+/// Labels the storage changes of the contracts with a known storage layout.
+///
+/// The changed slots are labeled with the variables they hold, and the values are decoded with
+/// their types. Elements of mappings are labeled with their key, recovered from the `KECCAK256`
+/// preimages recorded in the memory snapshots of the trace; if the key can't be decoded, the slot
+/// is labeled with the type of the mapping.
+///
+/// The labeled changes replace the ones of the trace writer, which are left untouched for the
+/// contracts without a storage layout.
+pub fn label_storage_changes(arena: &mut CallTraceArena, decoder: &CallTraceDecoder) {
+    if decoder.storage_layouts.is_empty() {
+        return;
+    }
+
+    let preimages = keccak_preimages(arena.nodes());
+    for idx in 0..arena.nodes().len() {
+        let nodes = arena.nodes_mut();
+        let Some(layout) = decoder
+            .contracts
+            .get(&nodes[idx].trace.address)
+            .and_then(|contract| decoder.storage_layouts.get(contract))
+        else {
+            continue;
+        };
+        let changes = storage_changes(&nodes[idx]);
+        if changes.is_empty() {
+            continue;
+        }
+
+        let pipes = node_pipes(nodes, idx);
+        let labeler = SlotLabeler { layout, preimages: &preimages };
+        let mut lines = String::from(" storage changes:");
+        for (slot, before, after) in changes {
+            for change in labeler.format_change(slot, before, after) {
+                let _ = write!(lines, "\n{pipes}  @ {change}");
+            }
+        }
+
+        let node = &mut nodes[idx];
+        for step in &mut node.trace.steps {
+            step.storage_change = None;
+        }
+        push_line(node, lines);
+    }
+}
+
+/// Configures the tracer to record the `SLOAD` steps along with their stack, so that the storage
+/// reads can be rendered with [`label_storage_reads`].
+///
+/// The other steps are only recorded if they already were.
+pub fn record_storage_reads(config: &mut TracingInspectorConfig) {
+    if !config.record_steps {
+        config.record_steps = true;
+        config.record_opcodes_filter = Some(OpcodeFilter::new());
+    }
+    if let Some(filter) = &mut config.record_opcodes_filter {
+        filter.enable(OpCode::SLOAD);
+    }
+    config.record_stack_snapshots = StackSnapshotType::All;
+}
+
+/// Renders the storage slots read by each call, in the order they were first read.
+///
+/// Repeated reads of a slot within a call are only rendered again if the value changed. Slots are
+/// labeled as in [`label_storage_changes`] for the contracts with a known storage layout.
+pub fn label_storage_reads(arena: &mut CallTraceArena, decoder: &CallTraceDecoder) {
+    let preimages = keccak_preimages(arena.nodes());
+    for idx in 0..arena.nodes().len() {
+        let nodes = arena.nodes_mut();
+        let reads = storage_reads(&nodes[idx]);
+        if reads.is_empty() {
+            continue;
+        }
+        let labeler = decoder
+            .contracts
+            .get(&nodes[idx].trace.address)
+            .and_then(|contract| decoder.storage_layouts.get(contract))
+            .map(|layout| SlotLabeler { layout, preimages: &preimages });
+
+        let pipes = node_pipes(nodes, idx);
+        let mut lines = String::from(" storage reads:");
+        for (slot, value) in reads {
+            let reads = match &labeler {
+                Some(labeler) => labeler.format_read(slot, value),
+                None => vec![format!("{}: {}", num_or_hex(slot), num_or_hex(value))],
+            };
+            for read in reads {
+                let _ = write!(lines, "\n{pipes}  @ {read}");
+            }
+        }
+        push_line(&mut nodes[idx], lines);
+    }
+}
+
+/// Returns the pipes preceding the items of the node in the trace writer, which are indented once
+/// more than its parent's.
+fn node_pipes(nodes: &[CallTraceNode], idx: usize) -> String {
+    let mut level = 1;
+    let mut parent = nodes[idx].parent;
+    while let Some(parent_idx) = parent {
+        level += 1;
+        parent = nodes[parent_idx].parent;
+    }
+    format!("  {}{PIPE}", PIPE.repeat(level - 1))
+}
+
+/// Collects the inputs of all the `KECCAK256` steps of the trace, by their hash.
+fn keccak_preimages(nodes: &[CallTraceNode]) -> B256HashMap<Bytes> {
+    let mut preimages = B256HashMap::default();
+    for step in nodes.iter().flat_map(|node| &node.trace.steps) {
+        if step.op != OpCode::KECCAK256 {
+            continue;
+        }
+        let (Some(stack), Some(memory)) = (&step.stack, &step.memory) else { continue };
+        let [.., size, offset] = stack.as_slice() else { continue };
+        let (Ok(offset), Ok(size)) = (usize::try_from(*offset), usize::try_from(*size)) else {
+            continue;
+        };
+        if let Some(data) = memory.as_bytes().get(offset..offset.saturating_add(size)) {
+            preimages.insert(keccak256(data), Bytes::copy_from_slice(data));
+        }
+    }
+    preimages
+}
+
+/// Returns the storage reads of the node as `(slot, value)`, without the repeated ones.
+fn storage_reads(node: &CallTraceNode) -> Vec<(U256, U256)> {
+    let mut seen = HashSet::new();
+    node.trace
+        .steps
+        .iter()
+        .filter(|step| step.op == OpCode::SLOAD && !step.status.is_error())
+        .filter_map(|step| {
+            Some((*step.stack.as_ref()?.last()?, *step.push_stack.as_ref()?.first()?))
+        })
+        .filter(|read| seen.insert(*read))
+        .collect()
+}
+
+/// Returns the storage changes of the node as `(slot, before, after)`, ordered by slot.
+///
+/// Like the trace writer, only the first and last values of each slot are kept.
+fn storage_changes(node: &CallTraceNode) -> Vec<(U256, U256, U256)> {
+    let mut changes = BTreeMap::new();
+    for change in node.trace.steps.iter().filter_map(|step| step.storage_change.as_ref()) {
+        changes
+            .entry(change.key)
+            .or_insert((change.had_value.unwrap_or_default(), change.value))
+            .1 = change.value;
+    }
+    changes
+        .into_iter()
+        .filter(|(_, (before, after))| before != after)
+        .map(|(slot, (before, after))| (slot, before, after))
+        .collect()
+}
+
+/// A variable held in a storage slot.
+struct SlotVariable<'a> {
+    /// The name of the variable, including the keys of the mapping elements.
+    name: String,
+    /// The type of the variable.
+    ty: Option<&'a StorageType>,
+    /// The offset of the variable in the slot, in bytes.
+    offset: usize,
+}
+
+/// Labels the slots of a contract with its storage layout.
+struct SlotLabeler<'a> {
+    layout: &'a StorageLayout,
+    preimages: &'a B256HashMap<Bytes>,
+}
+
+impl<'a> SlotLabeler<'a> {
+    /// Formats the change of a slot, with one line for each changed variable of the slot.
+    fn format_change(&self, slot: U256, before: U256, after: U256) -> Vec<String> {
+        let variables = self.variables(slot);
+        if variables.is_empty() {
+            return vec![format!(
+                "{}: {} → {}",
+                num_or_hex(slot),
+                num_or_hex(before),
+                num_or_hex(after)
+            )];
+        }
+
+        variables
+            .iter()
+            .filter_map(|variable| {
+                let before = format_value(variable, before);
+                let after = format_value(variable, after);
+                // Skip the packed variables that were not changed.
+                (variables.len() == 1 || before != after).then(|| {
+                    format!(
+                        "{} ({}: {}): {before} → {after}",
+                        num_or_hex(slot),
+                        variable.name,
+                        variable.ty.map_or("?", |ty| &ty.label)
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Formats the read of a slot, with one line for each variable of the slot.
+    fn format_read(&self, slot: U256, value: U256) -> Vec<String> {
+        let variables = self.variables(slot);
+        if variables.is_empty() {
+            return vec![format!("{}: {}", num_or_hex(slot), num_or_hex(value))];
+        }
+
+        variables
+            .iter()
+            .map(|variable| {
+                format!(
+                    "{} ({}: {}): {}",
+                    num_or_hex(slot),
+                    variable.name,
+                    variable.ty.map_or("?", |ty| &ty.label),
+                    format_value(variable, value)
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the variables held in the given slot.
+    fn variables(&self, slot: U256) -> Vec<SlotVariable<'a>> {
+        let variables = self
+            .layout
+            .storage
+            .iter()
+            .filter(|storage| U256::from_str(&storage.slot).ok() == Some(slot))
+            .map(|storage| SlotVariable {
+                name: storage.label.clone(),
+                ty: self.layout.types.get(&storage.storage_type),
+                offset: storage.offset as usize,
+            })
+            .collect::<Vec<_>>();
+        if !variables.is_empty() {
+            return variables;
+        }
+
+        // The slot of a mapping element is the hash of its key followed by the mapping slot.
+        let Some(preimage) = self.preimages.get(&B256::from(slot)) else { return vec![] };
+        let Some((key, base)) = preimage.len().checked_sub(32).map(|at| preimage.split_at(at))
+        else {
+            return vec![];
+        };
+        self.variables(U256::from_be_slice(base))
+            .into_iter()
+            .filter_map(|mapping| {
+                let ty = mapping.ty.filter(|ty| ty.encoding == "mapping")?;
+                let key_ty = ty.key.as_ref().and_then(|key| self.layout.types.get(key));
+                let value_ty = ty.value.as_ref().and_then(|value| self.layout.types.get(value));
+                Some(match key_ty.and_then(|key_ty| decode_key(key_ty, key)) {
+                    Some(key) => SlotVariable {
+                        name: format!("{}[{}]", mapping.name, format_token(&key)),
+                        ty: value_ty,
+                        offset: 0,
+                    },
+                    None => SlotVariable { name: mapping.name, ty: Some(ty), offset: 0 },
+                })
+            })
+            .collect()
+    }
+}
+
+/// Formats the value of the variable in the given slot value, or the whole slot if the variable
+/// is not a value type.
+fn format_value(variable: &SlotVariable<'_>, value: U256) -> String {
+    variable
+        .ty
+        .filter(|ty| ty.encoding == "inplace")
+        .and_then(|ty| {
+            let size = ty.number_of_bytes.parse::<usize>().ok()?;
+            let end = 32usize.checked_sub(variable.offset)?;
+            let word = value.to_be_bytes::<32>();
+            decode_value(&ty.label, word.get(end.checked_sub(size)?..end)?)
+        })
+        .map_or_else(|| num_or_hex(value), |value| format_token(&value))
+}
+
+/// Decodes the key of a mapping element from the preimage of its slot.
+fn decode_key(ty: &StorageType, key: &[u8]) -> Option<DynSolValue> {
+    match ty.label.as_str() {
+        "string" => Some(DynSolValue::String(String::from_utf8_lossy(key).into_owned())),
+        "bytes" => Some(DynSolValue::Bytes(key.to_vec())),
+        label => {
+            let size = ty.number_of_bytes.parse::<usize>().ok().filter(|size| *size <= 32)?;
+            let word: &[u8; 32] = key.try_into().ok()?;
+            // Fixed-size byte arrays are left-aligned, all the other value types right-aligned.
+            let bytes = if is_fixed_bytes(label) { &word[..size] } else { &word[32 - size..] };
+            decode_value(label, bytes)
+        }
+    }
+}
+
+/// Decodes a value type from its big-endian bytes.
+fn decode_value(label: &str, bytes: &[u8]) -> Option<DynSolValue> {
+    let bits = bytes.len() * 8;
+    let value = match label {
+        "bool" => match U256::try_from_be_slice(bytes)? {
+            value if value.is_zero() => DynSolValue::Bool(false),
+            value if value == U256::from(1) => DynSolValue::Bool(true),
+            _ => return None,
+        },
+        "address" | "address payable" => DynSolValue::Address(Address::try_from(bytes).ok()?),
+        _ if label.starts_with("contract ") => DynSolValue::Address(Address::try_from(bytes).ok()?),
+        _ if label.starts_with("uint") || label.starts_with("enum ") => {
+            DynSolValue::Uint(U256::try_from_be_slice(bytes)?, bits)
+        }
+        _ if label.starts_with("int") => {
+            let mut value = U256::try_from_be_slice(bytes)?;
+            // Sign-extend the value to 256 bits.
+            if bits > 0 && bits < 256 && value.bit(bits - 1) {
+                value |= U256::MAX << bits;
+            }
+            DynSolValue::Int(I256::from_raw(value), bits)
+        }
+        _ if is_fixed_bytes(label) => {
+            DynSolValue::FixedBytes(B256::right_padding_from(bytes), bytes.len())
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Returns `true` if the type is a fixed-size byte array, e.g. `bytes32`.
+fn is_fixed_bytes(label: &str) -> bool {
+    label.strip_prefix("bytes").is_some_and(|size| size.parse::<u8>().is_ok())
+}
+
+/// Formats a slot or value like the trace writer: decimal if it is short, hexadecimal otherwise.
+fn num_or_hex(x: U256) -> String {
+    if x < U256::from(1e6 as u128) {
+        x.to_string()
+    } else {
+        B256::from(x).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TraceWriter;
+    use revm::interpreter::InstructionResult;
+    use revm_inspectors::tracing::types::{
+        CallTraceStep, StorageChange, StorageChangeReason, TraceMemberOrder,
+    };
+
+    fn step(op: OpCode) -> CallTraceStep {
+        CallTraceStep {
+            depth: 0,
+            pc: 0,
+            code_section_idx: 0,
+            op,
+            contract: Address::ZERO,
+            stack: None,
+            push_stack: None,
+            memory: None,
+            returndata: Default::default(),
+            gas_remaining: 0,
+            gas_refund_counter: 0,
+            gas_used: 0,
+            gas_cost: 0,
+            storage_change: None,
+            status: InstructionResult::Continue,
+            immediate_bytes: None,
+            decoded: None,
+        }
+    }
+
+    fn sstore(key: U256, had_value: U256, value: U256) -> CallTraceStep {
+        CallTraceStep {
+            storage_change: Some(StorageChange {
+                key,
+                value,
+                had_value: Some(had_value),
+                reason: StorageChangeReason::SSTORE,
+            }),
+            ..step(OpCode::SSTORE)
+        }
+    }
+
+    #[test]
+    fn labels_storage_changes() {
+        let layout: StorageLayout = serde_json::from_value(serde_json::json!({
+            "storage": [
+                { "astId": 1, "contract": "src/C.sol:C", "label": "number", "offset": 0, "slot": "0", "type": "t_uint256" },
+                { "astId": 2, "contract": "src/C.sol:C", "label": "owner", "offset": 0, "slot": "1", "type": "t_address" },
+                { "astId": 3, "contract": "src/C.sol:C", "label": "paused", "offset": 20, "slot": "1", "type": "t_bool" },
+                { "astId": 4, "contract": "src/C.sol:C", "label": "balances", "offset": 0, "slot": "2", "type": "t_mapping(t_address,t_int256)" },
+                { "astId": 5, "contract": "src/C.sol:C", "label": "names", "offset": 0, "slot": "3", "type": "t_mapping(t_bool,t_uint256)" }
+            ],
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_int256": { "encoding": "inplace", "label": "int256", "numberOfBytes": "32" },
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_mapping(t_address,t_int256)": {
+                    "encoding": "mapping", "key": "t_address", "label": "mapping(address => int256)",
+                    "numberOfBytes": "32", "value": "t_int256"
+                },
+                "t_mapping(t_bool,t_uint256)": {
+                    "encoding": "mapping", "key": "t_bool", "label": "mapping(bool => uint256)",
+                    "numberOfBytes": "32", "value": "t_uint256"
+                }
+            }
+        }))
+        .unwrap();
+
+        let contract = Address::repeat_byte(0xcc);
+        let holder = Address::repeat_byte(0x11);
+        let mut decoder = CallTraceDecoder::default();
+        decoder.contracts.insert(contract, "src/C.sol:C".to_string());
+        decoder.storage_layouts.insert("src/C.sol:C".to_string(), layout);
+
+        // `balances[holder]`, and an element of `names` with a key that is not a bool.
+        let balance_preimage = [holder.into_word(), B256::with_last_byte(2)].concat();
+        let name_preimage = [B256::repeat_byte(0xff), B256::with_last_byte(3)].concat();
+        let keccak = |preimage: &[u8]| CallTraceStep {
+            stack: Some(vec![U256::from(preimage.len()), U256::ZERO]),
+            memory: Some(
+                serde_json::from_value(serde_json::json!(Bytes::from(preimage.to_vec()))).unwrap(),
+            ),
+            ..step(OpCode::KECCAK256)
+        };
+
+        let mut arena = CallTraceArena::default();
+        let node = &mut arena.nodes_mut()[0];
+        node.trace.address = contract;
+        node.trace.steps = vec![
+            keccak(&balance_preimage),
+            keccak(&name_preimage),
+            sstore(U256::ZERO, U256::ZERO, U256::from(111)),
+            sstore(U256::ZERO, U256::from(111), U256::from(112)),
+            sstore(U256::from(1), U256::ZERO, U256::from(1) << 160),
+            sstore(keccak256(&balance_preimage).into(), U256::ZERO, U256::MAX),
+            sstore(keccak256(&name_preimage).into(), U256::ZERO, U256::from(7)),
+            sstore(U256::from(9), U256::ZERO, U256::from(1)),
+        ];
+        node.ordering = (0..node.trace.steps.len()).map(TraceMemberOrder::Step).collect();
+
+        label_storage_changes(&mut arena, &decoder);
+
+        let mut writer = TraceWriter::new(Vec::<u8>::new())
+            .use_colors(revm_inspectors::ColorChoice::Never)
+            .with_storage_changes(true);
+        writer.write_arena(&arena).unwrap();
+        let rendered = String::from_utf8(writer.into_writer()).unwrap();
+        let changes = rendered.lines().filter(|line| line.contains('@')).collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "    │   @ 0 (number: uint256): 0 → 112",
+                "    │   @ 1 (paused: bool): false → true",
+                "    │   @ 9: 0 → 1",
+                &format!(
+                    "    │   @ {} (balances[{holder}]: int256): 0 → -1",
+                    keccak256(&balance_preimage)
+                ),
+                &format!(
+                    "    │   @ {} (names: mapping(bool => uint256)): 0 → 7",
+                    keccak256(&name_preimage)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_storage_reads() {
+        let layout: StorageLayout = serde_json::from_value(serde_json::json!({
+            "storage": [
+                { "astId": 1, "contract": "src/C.sol:C", "label": "owner", "offset": 0, "slot": "1", "type": "t_address" }
+            ],
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" }
+            }
+        }))
+        .unwrap();
+
+        let contract = Address::repeat_byte(0xcc);
+        let owner = Address::repeat_byte(0x11);
+        let mut decoder = CallTraceDecoder::default();
+        decoder.contracts.insert(contract, "src/C.sol:C".to_string());
+        decoder.storage_layouts.insert("src/C.sol:C".to_string(), layout);
+
+        let sload = |slot: U256, value: U256| CallTraceStep {
+            stack: Some(vec![U256::from(42), slot]),
+            push_stack: Some(vec![value]),
+            ..step(OpCode::SLOAD)
+        };
+
+        let mut arena = CallTraceArena::default();
+        let node = &mut arena.nodes_mut()[0];
+        node.trace.address = contract;
+        node.trace.steps = vec![
+            sload(U256::from(1), owner.into_word().into()),
+            sload(U256::from(9), U256::from(5)),
+            sload(U256::from(1), owner.into_word().into()),
+            sload(U256::from(9), U256::from(6)),
+        ];
+        node.ordering = (0..node.trace.steps.len()).map(TraceMemberOrder::Step).collect();
+
+        let mut config = TracingInspectorConfig::none();
+        record_storage_reads(&mut config);
+        assert!(config.record_steps);
+        assert!(config.record_opcodes_filter.as_ref().unwrap().is_enabled(OpCode::SLOAD));
+        assert!(!config.record_opcodes_filter.as_ref().unwrap().is_enabled(OpCode::JUMP));
+
+        label_storage_reads(&mut arena, &decoder);
+
+        let mut writer =
+            TraceWriter::new(Vec::<u8>::new()).use_colors(revm_inspectors::ColorChoice::Never);
+        writer.write_arena(&arena).unwrap();
+        let rendered = String::from_utf8(writer.into_writer()).unwrap();
+        let reads = rendered.lines().filter(|line| line.contains('@')).collect::<Vec<_>>();
+        assert_eq!(
+            reads,
+            [&format!("    │   @ 1 (owner: address): {owner}"), "    │   @ 9: 5", "    │   @ 9: 6",]
+        );
+    }
+}
@@ -117,6 +117,7 @@
         eth_rpc_url: Some("localhost".to_string()),
         eth_rpc_jwt: None,
         eth_rpc_timeout: None,
+        eth_rpc_retries: None,
         eth_rpc_headers: None,
         etherscan_api_key: None,
         etherscan: Default::default(),
@@ -135,6 +136,7 @@
             endpoints: CachedEndpoints::Remote,
         },
         no_storage_caching: true,
+        rpc_cache_path: None,
         no_rpc_rate_limit: true,
         use_literal_content: false,
         bytecode_hash: Default::default(),
@@ -1157,6 +1159,7 @@
   "eth_rpc_url": null,
   "eth_rpc_jwt": null,
   "eth_rpc_timeout": null,
+  "eth_rpc_retries": null,
   "eth_rpc_headers": null,
   "etherscan_api_key": null,
   "ignored_error_codes": [